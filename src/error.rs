@@ -0,0 +1,71 @@
+use std::fmt::{Display, Formatter, Result};
+
+/// Errors returned by the todo list operations
+#[derive(Debug, Clone, PartialEq)]
+pub enum TodoError {
+    /// No task has the given ID
+    NotFound(String),
+    /// No todo list has the given name
+    ListNotFound(String),
+    /// Reading or writing a file failed
+    Io(String),
+    /// Stored data could not be understood (e.g. malformed JSON or task IDs)
+    Parse(String),
+    /// The todo list could not be serialized
+    Serialization(String),
+    /// The requested change is not allowed or an argument is invalid
+    Validation(String),
+    /// The todo list is locked by another process
+    Locked(String),
+}
+
+impl TodoError {
+    /// Process exit code reported by the command line interface for this error.
+    /// Code 2 is left to the usage errors reported by clap.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TodoError::Validation(_) => 8,
+            TodoError::NotFound(_) | TodoError::ListNotFound(_) => 3,
+            TodoError::Parse(_) => 4,
+            TodoError::Io(_) => 5,
+            TodoError::Serialization(_) => 6,
+            TodoError::Locked(_) => 7,
+        }
+    }
+
+    /// Name of the error variant used by the structured command line output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TodoError::NotFound(_) => "not_found",
+            TodoError::ListNotFound(_) => "list_not_found",
+            TodoError::Io(_) => "io",
+            TodoError::Parse(_) => "parse",
+            TodoError::Serialization(_) => "serialization",
+            TodoError::Validation(_) => "validation",
+            TodoError::Locked(_) => "locked",
+        }
+    }
+}
+
+impl Display for TodoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            TodoError::NotFound(id) => write!(f, "Task with ID: {} not found", id),
+            TodoError::ListNotFound(name) => write!(f, "List '{}' not found", name),
+            TodoError::Io(message)
+            | TodoError::Parse(message)
+            | TodoError::Serialization(message)
+            | TodoError::Validation(message)
+            | TodoError::Locked(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for TodoError {}
+
+// The console displayers report their own failures as plain messages
+impl From<TodoError> for String {
+    fn from(error: TodoError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,453 @@
+use crate::error::TodoError;
+use crate::exchange::format::Import;
+use crate::model::due::Due;
+use crate::model::priority::Priority;
+use crate::model::recurrence::Recurrence;
+use crate::model::tags::normalize_tag;
+use crate::model::task::Task;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::BTreeSet;
+
+/// Task field stored in a CSV column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Description,
+    Priority,
+    Completed,
+    Due,
+    Tags,
+    Parent,
+    DependsOn,
+    Recurrence,
+    CreatedAt,
+    UpdatedAt,
+    CompletedAt,
+}
+
+/// Columns given to the task fields by `--map`, e.g. `description=Title,priority=Prio`.
+/// A column is a name of the header row, or a number starting at 1 for the files without
+/// one. The fields that are not mapped use their own name, e.g. `due`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnMap {
+    columns: Vec<(Field, String)>,
+}
+
+impl Field {
+    /// Every field, in the order of the exported columns
+    pub const ALL: [Field; 12] = [
+        Field::Id,
+        Field::Description,
+        Field::Priority,
+        Field::Completed,
+        Field::Due,
+        Field::Tags,
+        Field::Parent,
+        Field::DependsOn,
+        Field::Recurrence,
+        Field::CreatedAt,
+        Field::UpdatedAt,
+        Field::CompletedAt,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Id => "id",
+            Field::Description => "description",
+            Field::Priority => "priority",
+            Field::Completed => "completed",
+            Field::Due => "due",
+            Field::Tags => "tags",
+            Field::Parent => "parent",
+            Field::DependsOn => "depends_on",
+            Field::Recurrence => "recurrence",
+            Field::CreatedAt => "created_at",
+            Field::UpdatedAt => "updated_at",
+            Field::CompletedAt => "completed_at",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        Field::ALL.into_iter().find(|field| field.name() == name)
+    }
+
+    /// Value of the field for a task. Lists are joined with `;` so that they stay in a
+    /// single column, and recurrences use the syntax of `add --every`.
+    fn value(self, task: &Task) -> String {
+        let timestamp = |timestamp: Option<NaiveDateTime>| {
+            timestamp
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+                .unwrap_or_default()
+        };
+        match self {
+            Field::Id => task.id.clone(),
+            Field::Description => task.description.clone(),
+            Field::Priority => task.priority.to_string(),
+            Field::Completed => task.completed.to_string(),
+            Field::Due => task.due.map(|due| due.to_string()).unwrap_or_default(),
+            Field::Tags => task.tags.iter().cloned().collect::<Vec<_>>().join(";"),
+            Field::Parent => task.parent.clone().unwrap_or_default(),
+            Field::DependsOn => task
+                .depends_on
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(";"),
+            Field::Recurrence => task
+                .recurrence
+                .as_ref()
+                .map(Recurrence::rule)
+                .unwrap_or_default(),
+            Field::CreatedAt => timestamp(task.created_at),
+            Field::UpdatedAt => timestamp(task.updated_at),
+            Field::CompletedAt => timestamp(task.completed_at),
+        }
+    }
+
+    /// Set the field of a task from a non empty value, or give why the value is invalid
+    fn set(self, task: &mut Task, value: &str) -> Result<(), String> {
+        let invalid = || format!("invalid {} '{}'", self.name().replace('_', " "), value);
+        match self {
+            Field::Id => task.id = value.to_string(),
+            Field::Description => task.description = value.to_string(),
+            Field::Priority => {
+                task.priority = Priority::str_to_priority(value).map_err(|_| invalid())?
+            }
+            Field::Completed => task.completed = parse_bool(value).ok_or_else(invalid)?,
+            Field::Due => task.due = Some(Due::parse(value).map_err(|_| invalid())?),
+            Field::Tags => {
+                for tag in split_list(value) {
+                    task.tags.insert(normalize_tag(tag).map_err(|_| invalid())?);
+                }
+            }
+            Field::Parent => task.parent = Some(value.to_string()),
+            Field::DependsOn => task
+                .depends_on
+                .extend(split_list(value).map(str::to_string)),
+            Field::Recurrence => {
+                task.recurrence = Some(Recurrence::parse(value).map_err(|_| invalid())?)
+            }
+            Field::CreatedAt => task.created_at = Some(parse_timestamp(value).ok_or_else(invalid)?),
+            Field::UpdatedAt => task.updated_at = Some(parse_timestamp(value).ok_or_else(invalid)?),
+            Field::CompletedAt => {
+                task.completed_at = Some(parse_timestamp(value).ok_or_else(invalid)?)
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ColumnMap {
+    /// Parse comma separated `field=column` pairs
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut columns: Vec<(Field, String)> = Vec::new();
+        for pair in text
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let Some((name, column)) = pair
+                .split_once('=')
+                .map(|(name, column)| (name.trim(), column.trim()))
+                .filter(|(_, column)| !column.is_empty())
+            else {
+                return Err(format!(
+                    "Invalid mapping '{}', expected field=column, e.g. description=Title",
+                    pair
+                ));
+            };
+            let field = Field::from_name(name).ok_or_else(|| {
+                let names = Field::ALL.map(Field::name);
+                format!(
+                    "Unknown field '{}', expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })?;
+            if columns.iter().any(|(other, _)| *other == field) {
+                return Err(format!("Field '{}' is mapped twice", field.name()));
+            }
+            if columns
+                .iter()
+                .any(|(_, other)| other.eq_ignore_ascii_case(column))
+            {
+                return Err(format!("Column '{}' is mapped to several fields", column));
+            }
+            columns.push((field, column.to_string()));
+        }
+        if columns.is_empty() {
+            return Err("Empty mapping, expected e.g. description=Title,priority=Prio".to_string());
+        }
+        Ok(ColumnMap { columns })
+    }
+
+    /// Column of a field: the mapped one, else its own name unless another field is
+    /// mapped to it
+    pub fn column(&self, field: Field) -> Option<&str> {
+        match self.mapped(field) {
+            Some(column) => Some(column),
+            None if self
+                .columns
+                .iter()
+                .any(|(_, column)| column.eq_ignore_ascii_case(field.name())) =>
+            {
+                None
+            }
+            None => Some(field.name()),
+        }
+    }
+
+    fn mapped(&self, field: Field) -> Option<&str> {
+        self.columns
+            .iter()
+            .find(|(other, _)| *other == field)
+            .map(|(_, column)| column.as_str())
+    }
+}
+
+/// Write the tasks as CSV, with a header row naming the columns of the mapping
+pub fn export(tasks: &[Task], map: &ColumnMap) -> String {
+    let header = Field::ALL
+        .map(|field| map.column(field).unwrap_or(field.name()).to_string())
+        .to_vec();
+    std::iter::once(header)
+        .chain(
+            tasks
+                .iter()
+                .map(|task| Field::ALL.map(|field| field.value(task)).to_vec()),
+        )
+        .map(|row| {
+            let row = row.iter().map(|value| escape(value)).collect::<Vec<_>>();
+            row.join(",") + "\n"
+        })
+        .collect()
+}
+
+/// Read the tasks of CSV text. The first row is a header when it names one of the
+/// columns of the mapping. Without header, the columns are the mapped numbers, or
+/// the exported columns in order when nothing is mapped. The rows with a missing or
+/// invalid value are skipped, the reason being given in the warnings.
+pub fn import(text: &str, map: &ColumnMap) -> Result<Import, TodoError> {
+    let mut rows = parse_rows(text.trim_start_matches('\u{feff}'))
+        .map_err(TodoError::Parse)?
+        .into_iter()
+        .peekable();
+    let mut import = Import::default();
+    let header = rows.next_if(|(_, row)| {
+        Field::ALL
+            .iter()
+            .filter_map(|field| map.column(*field))
+            .filter(|column| column.parse::<usize>().is_err())
+            .any(|column| {
+                row.iter()
+                    .any(|cell| cell.trim().eq_ignore_ascii_case(column))
+            })
+    });
+    let columns = match &header {
+        Some((_, header)) => header_columns(header, map, &mut import.warnings)?,
+        None => numbered_columns(map)?,
+    };
+
+    for (line, row) in rows {
+        let mut task = Task {
+            id: format!("#{}", line),
+            description: String::new(),
+            priority: Priority::Low,
+            completed: false,
+            due: None,
+            tags: BTreeSet::new(),
+            parent: None,
+            depends_on: BTreeSet::new(),
+            recurrence: None,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            uuid: None,
+        };
+        let result = columns.iter().try_for_each(|(field, index)| {
+            match row.get(*index).map(|value| value.trim()) {
+                Some(value) if !value.is_empty() => field.set(&mut task, value),
+                _ => Ok(()),
+            }
+        });
+        let result = result.and_then(|_| match task.description.is_empty() {
+            true => Err("no description".to_string()),
+            false => Ok(()),
+        });
+        if let Err(reason) = result {
+            import
+                .warnings
+                .push(format!("Line {}: {}, skipped", line, reason));
+            continue;
+        }
+        if import.tasks.iter().any(|other| other.id == task.id) {
+            import.warnings.push(format!(
+                "Line {}: duplicate id '{}', the task gets a new one",
+                line, task.id
+            ));
+            task.id = format!("#{}", line);
+        }
+        import.tasks.push(task);
+    }
+    import.resolve_references();
+    Ok(import)
+}
+
+/// Quote the fields containing separators, quotes or line breaks (RFC 4180)
+pub(crate) fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Index of the column of each field found in the header row
+fn header_columns(
+    header: &[String],
+    map: &ColumnMap,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<(Field, usize)>, TodoError> {
+    let mut columns = Vec::new();
+    for field in Field::ALL {
+        let Some(column) = map.column(field) else {
+            continue;
+        };
+        let index = match column.parse::<usize>() {
+            Ok(number) => column_index(number, column)?,
+            Err(_) => match header
+                .iter()
+                .position(|cell| cell.trim().eq_ignore_ascii_case(column))
+            {
+                Some(index) => index,
+                None if map.mapped(field).is_some() => {
+                    return Err(TodoError::Validation(format!(
+                        "Column '{}' of the {} field not found in the header row",
+                        column,
+                        field.name()
+                    )));
+                }
+                None => continue,
+            },
+        };
+        columns.push((field, index));
+    }
+    if !columns
+        .iter()
+        .any(|(field, _)| *field == Field::Description)
+    {
+        return Err(TodoError::Validation(
+            "No description column, please give it with --map description=<column>".to_string(),
+        ));
+    }
+    for (index, cell) in header.iter().enumerate() {
+        if !columns.iter().any(|(_, other)| *other == index) {
+            warnings.push(format!("Column '{}' is not imported", cell.trim()));
+        }
+    }
+    Ok(columns)
+}
+
+/// Index of the column of each field of a file without header row
+fn numbered_columns(map: &ColumnMap) -> Result<Vec<(Field, usize)>, TodoError> {
+    if map.columns.is_empty() {
+        return Ok(Field::ALL.into_iter().zip(0..).collect());
+    }
+    let columns = map
+        .columns
+        .iter()
+        .map(|(field, column)| match column.parse::<usize>() {
+            Ok(number) => Ok((*field, column_index(number, column)?)),
+            Err(_) => Err(TodoError::Validation(format!(
+                "Column '{}' not found, the file has no header row",
+                column
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if map.mapped(Field::Description).is_none() {
+        return Err(TodoError::Validation(
+            "No description column, please give it with --map description=<column>".to_string(),
+        ));
+    }
+    Ok(columns)
+}
+
+/// Columns are numbered from 1
+fn column_index(number: usize, column: &str) -> Result<usize, TodoError> {
+    number.checked_sub(1).ok_or_else(|| {
+        TodoError::Validation(format!(
+            "Invalid column '{}', columns are numbered from 1",
+            column
+        ))
+    })
+}
+
+/// Rows of CSV text with the line each one starts on. Quoted fields may contain commas,
+/// doubled quotes and line breaks. Blank rows are left out.
+fn parse_rows(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let (mut line, mut start) = (1, 1);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => field.push('"'),
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                rows.push((start, std::mem::take(&mut row)));
+                line += 1;
+                start = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(format!("Line {}: unterminated quoted field", start));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((start, row));
+    }
+    rows.retain(|(_, row)| row.iter().any(|field| !field.trim().is_empty()));
+    Ok(rows)
+}
+
+/// Lists are separated by `;` or `,`
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "y" | "1" | "x" | "done" => Some(true),
+        "false" | "no" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// `YYYY-MM-DDTHH:MM:SS`, with a space instead of `T`, without seconds, or a date alone
+/// read as midnight
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    ["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
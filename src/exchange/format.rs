@@ -0,0 +1,192 @@
+use crate::exchange::taskwarrior;
+use crate::model::task::Task;
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use clap::ValueEnum;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+/// Format of the files the tasks are imported from and exported to
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExchangeFormat {
+    /// One task per line, as read by the todo.txt apps
+    #[value(alias = "todotxt")]
+    TodoTxt,
+    /// Comma separated values with a header row, e.g. from a spreadsheet
+    Csv,
+    /// GitHub checklist, `- [ ] task` per line
+    #[value(alias = "md")]
+    Markdown,
+    /// iCalendar file with a VTODO per task, read by the calendar clients
+    #[value(alias = "ics", alias = "icalendar")]
+    Ical,
+    /// JSON of `task export`, to migrate from and to Taskwarrior
+    #[value(alias = "tw")]
+    Taskwarrior,
+}
+
+impl ExchangeFormat {
+    /// Format matching the extension of a file, e.g. `.txt` for todo.txt and `.csv` for CSV
+    pub fn for_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "txt" => Some(ExchangeFormat::TodoTxt),
+            "csv" => Some(ExchangeFormat::Csv),
+            "md" | "markdown" => Some(ExchangeFormat::Markdown),
+            "ics" | "ical" => Some(ExchangeFormat::Ical),
+            _ => None,
+        }
+    }
+}
+
+/// Tasks read from a file of another format, with the problems met along the way
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Import {
+    /// Tasks in the order of the file. Their IDs only link them together through their
+    /// parents and dependencies, they are given fresh IDs when added to a todo list.
+    pub tasks: Vec<Task>,
+    /// What was skipped or changed, and why
+    pub warnings: Vec<String>,
+}
+
+impl Import {
+    /// Leave out the tasks whose Taskwarrior UUID is already in the todo list or in its
+    /// archive, as they were imported or exported before: the UUIDs of the tasks that did
+    /// not come from Taskwarrior are the ones their export derives. The parents and
+    /// dependencies referring to them are linked to their task in the todo list, and
+    /// dropped for the archived ones.
+    pub fn skip_existing(&mut self, existing: &[Task], archived: &[Task]) {
+        let find = |tasks: &[Task], task: &Task| {
+            let uuid = task.uuid.as_deref()?;
+            tasks
+                .iter()
+                .find(|other| taskwarrior::uuid(other) == uuid)
+                .map(|other| other.id.clone())
+        };
+        // ID of the task of the todo list each skipped task is, None for the archived ones
+        let mut skipped = BTreeMap::new();
+        for task in &self.tasks {
+            let (place, id, linked) = match (find(existing, task), find(archived, task)) {
+                (Some(id), _) => ("todo list", id.clone(), Some(id)),
+                (None, Some(id)) => ("archive", id, None),
+                (None, None) => continue,
+            };
+            self.warnings.push(format!(
+                "Task '{}': already in the {} as task {}, skipped",
+                task.description, place, id
+            ));
+            skipped.insert(task.id.clone(), linked);
+        }
+        self.tasks.retain(|task| !skipped.contains_key(&task.id));
+
+        for task in self.tasks.iter_mut() {
+            if let Some(parent) = task.parent.take() {
+                task.parent = match skipped.get(&parent) {
+                    None => Some(parent),
+                    Some(Some(id)) => Some(id.clone()),
+                    Some(None) => {
+                        self.warnings.push(format!(
+                            "Task '{}': parent '{}' is archived, imported as a top level task",
+                            task.description, parent
+                        ));
+                        None
+                    }
+                };
+            }
+            for dependency in task.depends_on.clone() {
+                let Some(linked) = skipped.get(&dependency) else {
+                    continue;
+                };
+                task.depends_on.remove(&dependency);
+                match linked {
+                    Some(id) => {
+                        task.depends_on.insert(id.clone());
+                    }
+                    None => self.warnings.push(format!(
+                        "Task '{}': dependency '{}' is archived, dropped",
+                        task.description, dependency
+                    )),
+                }
+            }
+        }
+    }
+
+    /// Drop the parents and dependencies referring to no imported task, and the parents
+    /// that would make a task its own ancestor
+    pub fn resolve_references(&mut self) {
+        let ids = self
+            .tasks
+            .iter()
+            .map(|task| task.id.clone())
+            .collect::<BTreeSet<_>>();
+        for task in self.tasks.iter_mut() {
+            if let Some(parent) = &task.parent
+                && !ids.contains(parent)
+            {
+                self.warnings.push(format!(
+                    "Task '{}': unknown parent '{}', imported as a top level task",
+                    task.description, parent
+                ));
+                task.parent = None;
+            }
+            let unknown = task
+                .depends_on
+                .iter()
+                .filter(|dependency| !ids.contains(*dependency))
+                .cloned()
+                .collect::<Vec<_>>();
+            for dependency in unknown {
+                self.warnings.push(format!(
+                    "Task '{}': unknown dependency '{}', dropped",
+                    task.description, dependency
+                ));
+                task.depends_on.remove(&dependency);
+            }
+        }
+
+        for index in 0..self.tasks.len() {
+            let mut visited = vec![self.tasks[index].id.clone()];
+            let mut current = self.tasks[index].parent.clone();
+            while let Some(parent) = current {
+                // A cycle not going through this task is broken by one of its own tasks
+                if visited[1..].contains(&parent) {
+                    break;
+                }
+                if parent == visited[0] {
+                    let task = &mut self.tasks[index];
+                    self.warnings.push(format!(
+                        "Task '{}': parent '{}' would make it its own ancestor, imported as a top level task",
+                        task.description, parent
+                    ));
+                    task.parent = None;
+                    break;
+                }
+                current = self
+                    .tasks
+                    .iter()
+                    .find(|task| task.id == parent)
+                    .and_then(|task| task.parent.clone());
+                visited.push(parent);
+            }
+        }
+    }
+}
+
+/// Local time as a UTC date-time, e.g. `20261018T073000Z`
+pub fn format_utc(timestamp: NaiveDateTime) -> String {
+    let utc = Local
+        .from_local_datetime(&timestamp)
+        .earliest()
+        .map_or(timestamp, |local| local.with_timezone(&Utc).naive_utc());
+    format!("{}Z", utc.format("%Y%m%dT%H%M%S"))
+}
+
+/// Local time of a UTC date-time such as `20261018T073000Z`
+pub fn parse_utc(value: &str) -> Option<NaiveDateTime> {
+    let utc = value.trim().strip_suffix(['Z', 'z'])?;
+    let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+    Some(
+        Utc.from_utc_datetime(&utc)
+            .with_timezone(&Local)
+            .naive_local(),
+    )
+}
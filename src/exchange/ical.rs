@@ -0,0 +1,571 @@
+use crate::exchange::format::{Import, format_utc, parse_utc};
+use crate::model::due::Due;
+use crate::model::priority::Priority;
+use crate::model::recurrence::Recurrence;
+use crate::model::tags::normalize_tag;
+use crate::model::task::Task;
+use crate::model::timestamp;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// Suffix of the UIDs of the exported tasks, e.g. `3-1b4e28ba2fa1d2e4@todo` for the task 3
+const UID_SUFFIX: &str = "@todo";
+
+/// Length of the token of the todo list in the UIDs
+const TOKEN_LENGTH: usize = 16;
+
+/// Time zones that are UTC, the only ones whose DUE times can be converted
+const UTC_ZONES: &[&str] = &["UTC", "ETC/UTC", "GMT", "ETC/GMT"];
+
+/// Content lines longer than this are folded (RFC 5545)
+const LINE_LENGTH: usize = 75;
+
+/// Properties of a VTODO that have no task field but need not be reported
+const IGNORED_PROPERTIES: &[&str] = &["DTSTAMP", "SEQUENCE"];
+
+/// Token of a todo list in the UIDs of its tasks, so that the tasks of different lists keep
+/// different UIDs in the calendars they are exported to. It is derived from where the list
+/// is stored and when it was created, so it does not change between exports.
+pub fn list_token(location: &str, created_at: Option<NaiveDateTime>) -> String {
+    let created_at = created_at.map(format_utc).unwrap_or_default();
+    let name = format!("todo:{}:{}", location, created_at);
+    let mut token = Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes())
+        .simple()
+        .to_string();
+    token.truncate(TOKEN_LENGTH);
+    token
+}
+
+/// Write the tasks as an iCalendar file with a VTODO per task. Its UID is the task ID and
+/// the token of the list followed by `@todo`, PRIORITY is 1, 5 or 9 for High, Medium and
+/// Low, and STATUS is `COMPLETED` or `NEEDS-ACTION`. Subtasks and dependencies refer to
+/// the UID of their tasks with RELATED-TO.
+pub fn export(tasks: &[Task], token: &str) -> String {
+    let uid = |id: &str| format!("{}-{}{}", id, token, UID_SUFFIX);
+    let stamp = format_utc(timestamp::now());
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//ToDo//ToDo {}//EN", env!("CARGO_PKG_VERSION")),
+    ];
+    for task in tasks {
+        lines.push("BEGIN:VTODO".to_string());
+        lines.push(format!("UID:{}", uid(&task.id)));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("SUMMARY:{}", escape(&task.description)));
+        lines.push(format!("PRIORITY:{}", priority_level(task.priority)));
+        lines.push(match task.completed {
+            true => "STATUS:COMPLETED".to_string(),
+            false => "STATUS:NEEDS-ACTION".to_string(),
+        });
+        if let Some(due) = task.due {
+            lines.push(match due.time {
+                Some(time) => format!("DUE:{}", format_local(due.date.and_time(time))),
+                None => format!("DUE;VALUE=DATE:{}", due.date.format("%Y%m%d")),
+            });
+        }
+        let timestamps = [
+            ("CREATED", task.created_at),
+            ("LAST-MODIFIED", task.updated_at),
+            ("COMPLETED", task.completed_at),
+        ];
+        for (name, timestamp) in timestamps {
+            if let Some(timestamp) = timestamp {
+                lines.push(format!("{}:{}", name, format_utc(timestamp)));
+            }
+        }
+        if !task.tags.is_empty() {
+            let tags = task.tags.iter().map(|tag| escape(tag)).collect::<Vec<_>>();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        if let Some(parent) = &task.parent {
+            lines.push(format!("RELATED-TO;RELTYPE=PARENT:{}", uid(parent)));
+        }
+        for dependency in &task.depends_on {
+            lines.push(format!("RELATED-TO;RELTYPE=DEPENDS-ON:{}", uid(dependency)));
+        }
+        if let Some(recurrence) = &task.recurrence {
+            lines.push(match rrule(recurrence) {
+                Some(rule) => format!("RRULE:{}", rule),
+                // Repeating after the completion has no iCalendar rule
+                None => format!("X-TODO-RECURRENCE:{}", recurrence.rule()),
+            });
+        }
+        lines.push("END:VTODO".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// Read the VTODO components of an iCalendar file. Their UID becomes the ID linking the
+/// tasks together, and the properties and components that have no task field are
+/// reported. The other components, e.g. VEVENT, are left out with a warning.
+pub fn import(text: &str) -> Import {
+    let mut import = Import::default();
+    // The task being read, with the line of its BEGIN and its unsupported properties
+    let mut current: Option<(Task, usize, BTreeSet<String>)> = None;
+    // Components around the current line, e.g. VCALENDAR then VTODO
+    let mut components: Vec<String> = Vec::new();
+    for (number, line) in unfold(text) {
+        let Some(ContentLine {
+            name,
+            parameters,
+            value,
+        }) = parse_line(&line)
+        else {
+            import
+                .warnings
+                .push(format!("Line {}: invalid content line, skipped", number));
+            continue;
+        };
+        let in_task = components
+            .last()
+            .is_some_and(|component| component == "VTODO");
+        match name.as_str() {
+            "BEGIN" => {
+                let component = value.trim().to_uppercase();
+                match &mut current {
+                    Some((_, _, unsupported)) => {
+                        if in_task {
+                            unsupported.insert(format!("{} component", component));
+                        }
+                    }
+                    None if component == "VTODO" => {
+                        current = Some((new_task(number), number, BTreeSet::new()));
+                    }
+                    None => {
+                        let top_level = components.iter().all(|other| other == "VCALENDAR");
+                        if top_level && !["VCALENDAR", "VTIMEZONE"].contains(&component.as_str()) {
+                            import.warnings.push(format!(
+                                "Line {}: {} component not imported",
+                                number, component
+                            ));
+                        }
+                    }
+                }
+                components.push(component);
+            }
+            "END" => {
+                let ended = components.pop();
+                if ended.is_some_and(|component| component == "VTODO")
+                    && !components.iter().any(|component| component == "VTODO")
+                    && let Some((task, begin, unsupported)) = current.take()
+                {
+                    finish_task(&mut import, task, begin, unsupported);
+                }
+            }
+            _ => {
+                if in_task && let Some((task, _, unsupported)) = &mut current {
+                    if let Err(reason) = set_property(task, &name, &parameters, &value) {
+                        import.warnings.push(format!("Line {}: {}", number, reason));
+                    } else if !is_supported(&name) {
+                        unsupported.insert(name);
+                    }
+                }
+            }
+        }
+    }
+    if let Some((_, begin, _)) = current {
+        import
+            .warnings
+            .push(format!("Line {}: VTODO without END, skipped", begin));
+    }
+    import.resolve_references();
+    import
+}
+
+fn new_task(number: usize) -> Task {
+    Task {
+        id: format!("#{}", number),
+        description: String::new(),
+        priority: Priority::Low,
+        completed: false,
+        due: None,
+        tags: BTreeSet::new(),
+        parent: None,
+        depends_on: BTreeSet::new(),
+        recurrence: None,
+        created_at: None,
+        updated_at: None,
+        completed_at: None,
+        uuid: None,
+    }
+}
+
+fn finish_task(import: &mut Import, mut task: Task, begin: usize, unsupported: BTreeSet<String>) {
+    if task.description.is_empty() {
+        import
+            .warnings
+            .push(format!("Line {}: VTODO without SUMMARY, skipped", begin));
+        return;
+    }
+    if !unsupported.is_empty() {
+        let unsupported = unsupported.into_iter().collect::<Vec<_>>();
+        import.warnings.push(format!(
+            "Task '{}': unsupported {} dropped",
+            task.description,
+            unsupported.join(", ")
+        ));
+    }
+    if import.tasks.iter().any(|other| other.id == task.id) {
+        import.warnings.push(format!(
+            "Line {}: duplicate UID '{}', the task gets a new one",
+            begin, task.id
+        ));
+        task.id = format!("#{}", begin);
+    }
+    import.tasks.push(task);
+}
+
+fn is_supported(name: &str) -> bool {
+    matches!(
+        name,
+        "UID"
+            | "SUMMARY"
+            | "PRIORITY"
+            | "STATUS"
+            | "DUE"
+            | "CREATED"
+            | "LAST-MODIFIED"
+            | "COMPLETED"
+            | "CATEGORIES"
+            | "RELATED-TO"
+            | "RRULE"
+            | "X-TODO-RECURRENCE"
+    ) || IGNORED_PROPERTIES.contains(&name)
+}
+
+/// Set the task field of a property. The unsupported properties are left for the caller
+/// to report, the invalid values give why they are dropped.
+fn set_property(
+    task: &mut Task,
+    name: &str,
+    parameters: &[(String, String)],
+    value: &str,
+) -> Result<(), String> {
+    let invalid = || format!("invalid {} '{}', dropped", name, value);
+    match name {
+        // The UIDs of the exported tasks give back their IDs, for the links between them
+        "UID" => task.id = task_id(value).to_string(),
+        "SUMMARY" => task.description = unescape(value).trim().to_string(),
+        "PRIORITY" => {
+            task.priority = match value.trim().parse::<u8>() {
+                Ok(1..=4) => Priority::High,
+                Ok(5) => Priority::Medium,
+                Ok(0 | 6..=9) => Priority::Low,
+                _ => return Err(invalid()),
+            }
+        }
+        "STATUS" => match value.to_uppercase().as_str() {
+            "COMPLETED" => task.completed = true,
+            "NEEDS-ACTION" | "IN-PROCESS" => task.completed = false,
+            "CANCELLED" => {
+                task.completed = true;
+                return Err(format!("status {} imported as completed", value));
+            }
+            _ => return Err(invalid()),
+        },
+        "COMPLETED" => {
+            task.completed_at = Some(parse_timestamp(value).ok_or_else(invalid)?);
+            task.completed = true;
+        }
+        "CREATED" => task.created_at = Some(parse_timestamp(value).ok_or_else(invalid)?),
+        "LAST-MODIFIED" => task.updated_at = Some(parse_timestamp(value).ok_or_else(invalid)?),
+        "DUE" => {
+            let zone = parameters
+                .iter()
+                .find(|(name, _)| name == "TZID")
+                .map(|(_, zone)| zone.trim_start_matches('/'));
+            let utc = zone.is_some_and(|zone| UTC_ZONES.contains(&zone.to_uppercase().as_str()));
+            task.due = Some(match parse_date(value) {
+                Some(date) => Due { date, time: None },
+                None => {
+                    let due = match utc {
+                        true => parse_utc(&format!("{}Z", value.trim())),
+                        false => parse_timestamp(value),
+                    };
+                    let due = due.ok_or_else(invalid)?;
+                    Due {
+                        date: due.date(),
+                        time: Some(due.time()),
+                    }
+                }
+            });
+            // Converting the other time zones needs their rules, which are not available
+            let timed = task.due.is_some_and(|due| due.time.is_some());
+            if let Some(zone) = zone.filter(|_| timed && !utc) {
+                return Err(format!(
+                    "unsupported time zone {} of DUE, read as a local time",
+                    zone
+                ));
+            }
+        }
+        "CATEGORIES" => {
+            for tag in split_text(value) {
+                match normalize_tag(&tag) {
+                    Ok(tag) => {
+                        task.tags.insert(tag);
+                    }
+                    Err(_) => return Err(format!("invalid category '{}', dropped", tag)),
+                }
+            }
+        }
+        "RELATED-TO" => {
+            let id = task_id(value);
+            let relation = parameters
+                .iter()
+                .find(|(name, _)| name == "RELTYPE")
+                .map_or("PARENT".to_string(), |(_, value)| value.to_uppercase());
+            match relation.as_str() {
+                "PARENT" => task.parent = Some(id.to_string()),
+                "DEPENDS-ON" => {
+                    task.depends_on.insert(id.to_string());
+                }
+                _ => {
+                    return Err(format!(
+                        "unsupported relation {} to '{}', dropped",
+                        relation, value
+                    ));
+                }
+            }
+        }
+        "RRULE" => task.recurrence = Some(parse_rrule(value).ok_or_else(invalid)?),
+        "X-TODO-RECURRENCE" => {
+            task.recurrence = Some(Recurrence::parse(value).map_err(|_| invalid())?)
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// 1 to 4 are high priorities, 5 medium and 6 to 9 low (0 being undefined)
+fn priority_level(priority: Priority) -> u8 {
+    match priority {
+        Priority::High => 1,
+        Priority::Medium => 5,
+        Priority::Low => 9,
+    }
+}
+
+fn rrule(recurrence: &Recurrence) -> Option<String> {
+    match recurrence {
+        Recurrence::Daily => Some("FREQ=DAILY".to_string()),
+        Recurrence::Weekly { weekdays } if weekdays.is_empty() => Some("FREQ=WEEKLY".to_string()),
+        Recurrence::Weekly { weekdays } => {
+            let weekdays = weekdays
+                .iter()
+                .map(|weekday| weekday.to_string()[..2].to_uppercase())
+                .collect::<Vec<_>>();
+            Some(format!("FREQ=WEEKLY;BYDAY={}", weekdays.join(",")))
+        }
+        Recurrence::Monthly { day: None } => Some("FREQ=MONTHLY".to_string()),
+        Recurrence::Monthly { day: Some(day) } => Some(format!("FREQ=MONTHLY;BYMONTHDAY={}", day)),
+        Recurrence::AfterCompletion { .. } => None,
+    }
+}
+
+/// The rules written by `export`, other ones having no recurrence to match them
+fn parse_rrule(value: &str) -> Option<Recurrence> {
+    let mut frequency = None;
+    let mut weekdays = Vec::new();
+    let mut day = None;
+    for part in value.split(';') {
+        let (key, value) = part.split_once('=')?;
+        match key.to_uppercase().as_str() {
+            "FREQ" => frequency = Some(value.to_uppercase()),
+            "BYDAY" => {
+                for weekday in value.split(',') {
+                    let weekday = parse_weekday(weekday)?;
+                    if !weekdays.contains(&weekday) {
+                        weekdays.push(weekday);
+                    }
+                }
+            }
+            "BYMONTHDAY" => {
+                day = Some(
+                    value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|day| (1..=31).contains(day))?,
+                )
+            }
+            "INTERVAL" if value == "1" => {}
+            "WKST" => {}
+            _ => return None,
+        }
+    }
+    weekdays.sort_by_key(Weekday::num_days_from_monday);
+    match frequency?.as_str() {
+        "DAILY" if weekdays.is_empty() && day.is_none() => Some(Recurrence::Daily),
+        "WEEKLY" if day.is_none() => Some(Recurrence::Weekly { weekdays }),
+        "MONTHLY" if weekdays.is_empty() => Some(Recurrence::Monthly { day }),
+        _ => None,
+    }
+}
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    match text.trim().to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Local time without time zone, e.g. `20261103T170000`
+fn format_local(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// UTC date-times (ending with `Z`) are converted to local time, the others are taken as
+/// local times. Dates alone are read as midnight.
+fn parse_timestamp(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if value.ends_with(['Z', 'z']) {
+        return parse_utc(value);
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .ok()
+        .or_else(|| parse_date(value).map(|date| date.and_time(NaiveTime::MIN)))
+}
+
+/// ID of a task from its UID: the exported UIDs give back the ID of their task, for the
+/// links between them, and the other UIDs are kept whole
+fn task_id(uid: &str) -> &str {
+    let Some(id) = uid.strip_suffix(UID_SUFFIX) else {
+        return uid;
+    };
+    match id.rsplit_once('-') {
+        Some((id, token))
+            if token.len() == TOKEN_LENGTH && token.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            id
+        }
+        // Exported before the UIDs had the token of the list
+        _ => id,
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y%m%d").ok()
+}
+
+/// Escape the backslashes, separators and line breaks of a text value
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            },
+            (c, false) => result.push(c),
+        }
+    }
+    result
+}
+
+/// Values of a comma separated list of texts, the escaped commas being kept
+fn split_text(value: &str) -> Vec<String> {
+    let mut values = vec![String::new()];
+    let mut escaped = false;
+    for c in value.chars() {
+        match c {
+            ',' if !escaped => values.push(String::new()),
+            _ => {
+                if let Some(last) = values.last_mut() {
+                    last.push(c);
+                }
+            }
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    values
+        .iter()
+        .map(|value| unescape(value).trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// Split the lines longer than 75 octets, the next ones starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+/// Content lines with the number of the line they start on, the folded lines being joined
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some((_, last))) => last.push_str(continuation),
+            _ if line.trim().is_empty() => {}
+            _ => lines.push((index + 1, line.to_string())),
+        }
+    }
+    lines
+}
+
+/// Unfolded line of an iCalendar file, e.g. `RELATED-TO;RELTYPE=PARENT:1@todo`
+struct ContentLine {
+    /// Upper case name of the property, or `BEGIN` and `END` around the components
+    name: String,
+    /// Upper case names of the parameters with their unquoted values
+    parameters: Vec<(String, String)>,
+    value: String,
+}
+
+/// Parameter values may be quoted, and contain `:` when they are
+fn parse_line(line: &str) -> Option<ContentLine> {
+    let mut quoted = false;
+    let separator = line.char_indices().find_map(|(index, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == ':' && !quoted).then_some(index)
+    })?;
+    let (head, value) = (&line[..separator], &line[separator + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let parameters = parts
+        .filter_map(|parameter| parameter.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.trim().to_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some(ContentLine {
+        name,
+        parameters,
+        value: value.to_string(),
+    })
+}
@@ -0,0 +1,204 @@
+use crate::exchange::format::Import;
+use crate::model::due::Due;
+use crate::model::priority::Priority;
+use crate::model::tags::extract_tags;
+use crate::model::task::Task;
+use clap::ValueEnum;
+use std::collections::BTreeSet;
+
+/// Headings the tasks of a Markdown checklist are grouped under
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum Grouping {
+    /// `## High`, `## Medium` and `## Low`, read back as the priority of their tasks
+    #[default]
+    Priority,
+    /// A heading per tag, the tasks being under their first tag
+    Tag,
+    /// A single checklist without headings
+    Flat,
+}
+
+/// Write the tasks as a GitHub checklist, `- [ ] task` or `- [x] task` for the completed
+/// ones, followed by their `+tags` and `(due <date>)`. Subtasks are nested under their
+/// parent, whose group they share.
+pub fn export(tasks: &[Task], grouping: Grouping) -> String {
+    let top_level = tasks
+        .iter()
+        .filter(|task| {
+            task.parent
+                .as_ref()
+                .is_none_or(|parent| !tasks.iter().any(|other| other.id == *parent))
+        })
+        .collect::<Vec<_>>();
+    let groups = match grouping {
+        Grouping::Flat => vec![(None, top_level)],
+        Grouping::Priority => [Priority::High, Priority::Medium, Priority::Low]
+            .into_iter()
+            .map(|priority| {
+                let group = top_level
+                    .iter()
+                    .filter(|task| task.priority == priority)
+                    .copied()
+                    .collect();
+                (Some(priority.to_string()), group)
+            })
+            .collect(),
+        Grouping::Tag => {
+            let first_tag = |task: &Task| task.tags.first().cloned();
+            let tags = top_level
+                .iter()
+                .filter_map(|task| first_tag(task))
+                .collect::<BTreeSet<_>>();
+            tags.into_iter()
+                .map(Some)
+                .chain([None])
+                .map(|tag| {
+                    let group = top_level
+                        .iter()
+                        .filter(|task| first_tag(task) == tag)
+                        .copied()
+                        .collect();
+                    (Some(tag.unwrap_or("No tag".to_string())), group)
+                })
+                .collect()
+        }
+    };
+
+    let mut lines = Vec::new();
+    for (heading, group) in groups.into_iter().filter(|(_, group)| !group.is_empty()) {
+        if let Some(heading) = heading {
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            lines.push(format!("## {}", heading));
+            lines.push(String::new());
+        }
+        for task in group {
+            write_item(&mut lines, tasks, task, 0);
+        }
+    }
+    lines.into_iter().map(|line| line + "\n").collect()
+}
+
+/// Read the tasks of the checklist items, `- [ ]`, `* [x]` or `1. [ ]`. Indented items
+/// are subtasks of the item above them. The items under a `High`, `Medium` or `Low`
+/// heading get its priority, the others are Low. Other lines are left out.
+pub fn import(text: &str) -> Import {
+    let mut import = Import::default();
+    let mut priority = Priority::Low;
+    // Indentation and ID of the items the next ones may be nested in
+    let mut ancestors: Vec<(usize, String)> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if let Some(heading) = parse_heading(line) {
+            priority = Priority::str_to_priority(heading).unwrap_or(Priority::Low);
+            ancestors.clear();
+            continue;
+        }
+        let Some((indent, completed, text)) = parse_item(line) else {
+            continue;
+        };
+        while ancestors.last().is_some_and(|(other, _)| *other >= indent) {
+            ancestors.pop();
+        }
+        let (text, due) = match text
+            .strip_suffix(')')
+            .and_then(|text| text.rsplit_once("(due "))
+        {
+            Some((rest, due)) => match Due::parse(due) {
+                Ok(due) => (rest.trim_end(), Some(due)),
+                Err(_) => {
+                    import.warnings.push(format!(
+                        "Line {}: invalid due date '{}', kept in the description",
+                        number, due
+                    ));
+                    (text, None)
+                }
+            },
+            None => (text, None),
+        };
+        let (description, tags) = extract_tags(text);
+        if description.is_empty() {
+            import
+                .warnings
+                .push(format!("Line {}: no description, skipped", number));
+            continue;
+        }
+        let id = format!("#{}", number);
+        import.tasks.push(Task {
+            id: id.clone(),
+            description,
+            priority,
+            completed,
+            due,
+            tags,
+            parent: ancestors.last().map(|(_, parent)| parent.clone()),
+            depends_on: BTreeSet::new(),
+            recurrence: None,
+            created_at: None,
+            updated_at: None,
+            completed_at: None,
+            uuid: None,
+        });
+        ancestors.push((indent, id));
+    }
+    import.resolve_references();
+    import
+}
+
+fn write_item(lines: &mut Vec<String>, tasks: &[Task], task: &Task, depth: usize) {
+    let mut words = vec![task.description.clone()];
+    words.extend(task.tags.iter().map(|tag| format!("+{}", tag)));
+    if let Some(due) = task.due {
+        words.push(format!("(due {})", due));
+    }
+    let state = if task.completed { 'x' } else { ' ' };
+    lines.push(format!(
+        "{}- [{}] {}",
+        "  ".repeat(depth),
+        state,
+        words.join(" ")
+    ));
+    for subtask in tasks
+        .iter()
+        .filter(|subtask| subtask.parent.as_ref() == Some(&task.id))
+    {
+        write_item(lines, tasks, subtask, depth + 1);
+    }
+}
+
+/// Text of a `#` to `######` heading
+fn parse_heading(line: &str) -> Option<&str> {
+    let text = line.trim_start();
+    let rest = text.trim_start_matches('#');
+    let level = text.len() - rest.len();
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end())
+}
+
+/// Indentation, state and text of a checklist item. Tabs count as 4 spaces.
+fn parse_item(line: &str) -> Option<(usize, bool, &str)> {
+    let text = line.trim_start();
+    let indent = line[..line.len() - text.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+    let number = text.trim_start_matches(|c: char| c.is_ascii_digit());
+    let rest = match text.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None if number.len() < text.len() => number.strip_prefix(['.', ')'])?,
+        None => return None,
+    };
+    let rest = rest.strip_prefix(' ')?.trim_start();
+    let (completed, rest) = match rest.get(..3) {
+        Some("[ ]") => (false, &rest[3..]),
+        Some("[x]" | "[X]") => (true, &rest[3..]),
+        _ => return None,
+    };
+    if !(rest.is_empty() || rest.starts_with(char::is_whitespace)) {
+        return None;
+    }
+    Some((indent, completed, rest.trim()))
+}
@@ -0,0 +1,289 @@
+use crate::error::TodoError;
+use crate::exchange::format::{Import, format_utc, parse_utc};
+use crate::model::due::Due;
+use crate::model::priority::Priority;
+use crate::model::tags::normalize_tag;
+use crate::model::task::Task;
+use chrono::NaiveTime;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// Prefix of the tags holding the Taskwarrior project, e.g. `project:home.garden`
+pub const PROJECT_TAG_PREFIX: &str = "project:";
+
+/// Attributes of a Taskwarrior task that have no task field but need not be reported:
+/// its working set number and its computed urgency
+const IGNORED_ATTRIBUTES: &[&str] = &["id", "urgency"];
+
+/// Task as written by `task export`
+#[derive(Debug, Serialize)]
+struct TaskwarriorTask {
+    uuid: String,
+    description: String,
+    /// `pending` or `completed`
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    /// `H`, `M` or `L`
+    priority: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// UUIDs of the tasks to complete first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends: Vec<String>,
+}
+
+/// Write the tasks as the JSON array of `task export`. The tasks imported from Taskwarrior
+/// keep their UUID, the others get one derived from their ID and creation time, so that
+/// exporting them again updates the same tasks. The `project:` tag is the project.
+pub fn export(tasks: &[Task]) -> Result<String, TodoError> {
+    let objects = tasks
+        .iter()
+        .map(|task| {
+            let mut tags = task.tags.iter().cloned().collect::<Vec<_>>();
+            let project = tags
+                .iter()
+                .position(|tag| tag.starts_with(PROJECT_TAG_PREFIX))
+                .map(|index| tags.remove(index)[PROJECT_TAG_PREFIX.len()..].to_string());
+            let depends = task
+                .depends_on
+                .iter()
+                .filter_map(|id| tasks.iter().find(|other| other.id == *id))
+                .map(uuid)
+                .collect();
+            let due = task.due.map(|due| {
+                // Due dates without time are due at the start of the day, as in Taskwarrior
+                format_utc(due.date.and_time(due.time.unwrap_or(NaiveTime::MIN)))
+            });
+            TaskwarriorTask {
+                uuid: uuid(task),
+                description: task.description.clone(),
+                status: if task.completed {
+                    "completed"
+                } else {
+                    "pending"
+                },
+                entry: task.created_at.map(format_utc),
+                modified: task.updated_at.map(format_utc),
+                end: task.completed_at.filter(|_| task.completed).map(format_utc),
+                due,
+                priority: match task.priority {
+                    Priority::High => "H",
+                    Priority::Medium => "M",
+                    Priority::Low => "L",
+                },
+                project,
+                tags,
+                depends,
+            }
+        })
+        .map(|task| serde_json::to_string(&task))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| TodoError::Serialization(format!("Failed to serialize the tasks: {}", e)))?;
+    Ok(format!("[\n{}\n]\n", objects.join(",\n")))
+}
+
+/// Read the tasks of `task export`, a JSON array or one JSON object per line. The
+/// deleted tasks and the templates of the recurring ones are skipped, and the attributes
+/// that have no task field are reported.
+pub fn import(text: &str) -> Result<Import, TodoError> {
+    let invalid =
+        |e: serde_json::Error| TodoError::Parse(format!("Invalid Taskwarrior export: {}", e));
+    let values = if text.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<Value>>(text).map_err(invalid)?
+    } else {
+        text.lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty())
+            .map(serde_json::from_str::<Value>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid)?
+    };
+
+    let mut import = Import::default();
+    for (index, value) in values.into_iter().enumerate() {
+        let number = index + 1;
+        let Value::Object(object) = value else {
+            import
+                .warnings
+                .push(format!("Task {}: not a JSON object, skipped", number));
+            continue;
+        };
+        match parse_task(&object, number, &mut import.warnings) {
+            Ok(mut task) => {
+                if import.tasks.iter().any(|other| other.id == task.id) {
+                    import.warnings.push(format!(
+                        "Task {}: duplicate UUID '{}', the task gets a new one",
+                        number, task.id
+                    ));
+                    task.id = format!("#{}", number);
+                    task.uuid = None;
+                }
+                import.tasks.push(task);
+            }
+            Err(reason) => import
+                .warnings
+                .push(format!("Task {}: {}, skipped", number, reason)),
+        }
+    }
+    import.resolve_references();
+    Ok(import)
+}
+
+/// Task of a Taskwarrior object, whose ID is its UUID or `#<position>`. Invalid values
+/// are reported and dropped, the tasks that cannot be imported give the reason.
+fn parse_task(
+    object: &Map<String, Value>,
+    number: usize,
+    warnings: &mut Vec<String>,
+) -> Result<Task, String> {
+    let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::trim);
+    let description = text("description")
+        .filter(|description| !description.is_empty())
+        .ok_or("no description")?;
+    let status = text("status").unwrap_or("pending");
+    let completed = match status {
+        "pending" | "waiting" => false,
+        "completed" => true,
+        "deleted" => return Err(format!("'{}' was deleted", description)),
+        "recurring" => {
+            return Err(format!(
+                "'{}' is the template of a recurring task",
+                description
+            ));
+        }
+        other => return Err(format!("unknown status '{}'", other)),
+    };
+    let uuid = text("uuid").map(str::to_string);
+    let mut task = Task {
+        id: uuid.clone().unwrap_or_else(|| format!("#{}", number)),
+        description: description.to_string(),
+        priority: Priority::Low,
+        completed,
+        due: None,
+        tags: BTreeSet::new(),
+        parent: None,
+        depends_on: BTreeSet::new(),
+        recurrence: None,
+        created_at: None,
+        updated_at: None,
+        completed_at: None,
+        uuid,
+    };
+
+    let mut unsupported = Vec::new();
+    for (key, value) in object {
+        let mut invalid = || {
+            warnings.push(format!(
+                "Task {}: invalid {} {}, dropped",
+                number, key, value
+            ));
+        };
+        match (key.as_str(), value) {
+            ("uuid" | "description" | "status", _) => {}
+            ("priority", Value::String(priority)) => match priority.as_str() {
+                "H" => task.priority = Priority::High,
+                "M" => task.priority = Priority::Medium,
+                "L" | "" => task.priority = Priority::Low,
+                _ => invalid(),
+            },
+            ("entry" | "modified" | "end", Value::String(timestamp)) => {
+                match parse_utc(timestamp) {
+                    Some(timestamp) if key == "entry" => task.created_at = Some(timestamp),
+                    Some(timestamp) if key == "modified" => task.updated_at = Some(timestamp),
+                    Some(timestamp) => {
+                        // Deleted tasks have an end too, only the completed ones are kept
+                        task.completed_at = Some(timestamp).filter(|_| completed)
+                    }
+                    None => invalid(),
+                }
+            }
+            ("due", Value::String(due)) => match parse_utc(due) {
+                // Taskwarrior dates without time are due at the start of the day
+                Some(due) if due.time() == NaiveTime::MIN => {
+                    task.due = Some(Due {
+                        date: due.date(),
+                        time: None,
+                    })
+                }
+                Some(due) => {
+                    task.due = Some(Due {
+                        date: due.date(),
+                        time: Some(due.time()),
+                    })
+                }
+                None => invalid(),
+            },
+            ("project", Value::String(project)) => {
+                match normalize_tag(&format!("{}{}", PROJECT_TAG_PREFIX, project)) {
+                    Ok(tag) => {
+                        task.tags.insert(tag);
+                    }
+                    Err(_) => invalid(),
+                }
+            }
+            ("tags", Value::Array(tags)) => {
+                for tag in tags {
+                    match tag.as_str().map(normalize_tag) {
+                        Some(Ok(tag)) => {
+                            task.tags.insert(tag);
+                        }
+                        _ => {
+                            warnings.push(format!("Task {}: invalid tag {}, dropped", number, tag))
+                        }
+                    }
+                }
+            }
+            // A comma separated string before Taskwarrior 2.6, an array since
+            ("depends", Value::String(depends)) => task.depends_on.extend(
+                depends
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|uuid| !uuid.is_empty())
+                    .map(str::to_string),
+            ),
+            ("depends", Value::Array(depends)) => {
+                for uuid in depends {
+                    match uuid.as_str() {
+                        Some(uuid) => {
+                            task.depends_on.insert(uuid.to_string());
+                        }
+                        None => invalid(),
+                    }
+                }
+            }
+            ("priority" | "entry" | "modified" | "end" | "due" | "project" | "tags", _)
+            | ("depends", _) => invalid(),
+            (key, _) if IGNORED_ATTRIBUTES.contains(&key) => {}
+            (key, _) => unsupported.push(key.to_string()),
+        }
+    }
+    if !unsupported.is_empty() {
+        warnings.push(format!(
+            "Task '{}': unsupported {} dropped",
+            task.description,
+            unsupported.join(", ")
+        ));
+    }
+    Ok(task)
+}
+
+/// UUID of a task in Taskwarrior: the one it was imported with, or one derived from it
+pub fn uuid(task: &Task) -> String {
+    task.uuid.clone().unwrap_or_else(|| {
+        // The same task always gets the same UUID
+        let created_at = task.created_at.map(format_utc).unwrap_or_default();
+        let name = format!("todo:{}:{}", task.id, created_at);
+        Uuid::new_v5(&Uuid::NAMESPACE_OID, name.as_bytes()).to_string()
+    })
+}
@@ -0,0 +1,253 @@
+use crate::exchange::format::Import;
+use crate::model::due::Due;
+use crate::model::priority::Priority;
+use crate::model::recurrence::Recurrence;
+use crate::model::tags::normalize_tag;
+use crate::model::task::Task;
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::collections::BTreeSet;
+
+/// Write the tasks in the todo.txt format, one line per task:
+/// `x <completed> (A) <created> description +tag due:<date> rec:<rule>`.
+/// High, Medium and Low priorities are `(A)`, `(B)` and `(C)`, kept as `pri:` for
+/// completed tasks. Subtasks and dependencies refer to the `id:` of their tasks with
+/// `parent:` and `dep:`.
+pub fn export(tasks: &[Task]) -> String {
+    let referenced = tasks
+        .iter()
+        .flat_map(|task| task.parent.iter().chain(task.depends_on.iter()))
+        .collect::<BTreeSet<_>>();
+    tasks
+        .iter()
+        .map(|task| {
+            let mut words = Vec::new();
+            if task.completed {
+                words.push("x".to_string());
+                if let Some(completed_at) = task.completed_at {
+                    words.push(completed_at.date().to_string());
+                }
+            } else {
+                words.push(format!("({})", priority_letter(task.priority)));
+            }
+            // A creation date alone after `x` would be read as the completion date
+            if let Some(created_at) = task.created_at
+                && (!task.completed || task.completed_at.is_some())
+            {
+                words.push(created_at.date().to_string());
+            }
+            words.extend(task.description.split_whitespace().map(str::to_string));
+            words.extend(task.tags.iter().map(|tag| format!("+{}", tag)));
+            if let Some(due) = task.due {
+                words.push(match due.time {
+                    Some(time) => format!("due:{}T{}", due.date, time.format("%H:%M")),
+                    None => format!("due:{}", due.date),
+                });
+            }
+            if let Some(recurrence) = &task.recurrence {
+                words.push(format!("rec:{}", recurrence_rule(recurrence)));
+            }
+            if referenced.contains(&task.id) {
+                words.push(format!("id:{}", task.id));
+            }
+            if let Some(parent) = &task.parent {
+                words.push(format!("parent:{}", parent));
+            }
+            if !task.depends_on.is_empty() {
+                let depends_on = task.depends_on.iter().cloned().collect::<Vec<_>>();
+                words.push(format!("dep:{}", depends_on.join(",")));
+            }
+            if task.completed {
+                words.push(format!("pri:{}", priority_letter(task.priority)));
+            }
+            words.join(" ")
+        })
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Read the tasks of a todo.txt file. `+project` words become tags, the `due:`, `rec:`,
+/// `id:`, `parent:`, `dep:` and `pri:` extras fill the matching fields, and everything
+/// else, `@context` words and other `key:value` extras included, stays in the description.
+pub fn import(text: &str) -> Import {
+    let mut import = Import::default();
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(line, number, &mut import.warnings) {
+            Some(mut task) => {
+                if import.tasks.iter().any(|other| other.id == task.id) {
+                    import.warnings.push(format!(
+                        "Line {}: duplicate id '{}', the task gets a new one",
+                        number, task.id
+                    ));
+                    task.id = format!("#{}", number);
+                }
+                import.tasks.push(task);
+            }
+            None => import
+                .warnings
+                .push(format!("Line {}: no description, skipped", number)),
+        }
+    }
+    import.resolve_references();
+    import
+}
+
+/// Task of a line, whose ID is its `id:` extra or `#<line number>`. None without description.
+fn parse_line(line: &str, number: usize, warnings: &mut Vec<String>) -> Option<Task> {
+    let mut words = line.split_whitespace().peekable();
+    let completed = words.next_if_eq(&"x").is_some();
+    let mut completed_at = None;
+    if completed {
+        completed_at = words
+            .next_if(|word| parse_date(word).is_some())
+            .and_then(parse_date);
+    }
+    let mut priority = None;
+    if let Some(word) = words.next_if(|word| parse_priority(word).is_some()) {
+        priority = parse_priority(word);
+    }
+    let created_at = words
+        .next_if(|word| parse_date(word).is_some())
+        .and_then(parse_date);
+
+    let mut task = Task {
+        id: format!("#{}", number),
+        description: String::new(),
+        priority: Priority::Low,
+        completed,
+        due: None,
+        tags: BTreeSet::new(),
+        parent: None,
+        depends_on: BTreeSet::new(),
+        recurrence: None,
+        created_at,
+        updated_at: None,
+        completed_at,
+        uuid: None,
+    };
+    let mut description = Vec::new();
+    for word in words {
+        if let Some(tag) = word.strip_prefix('+').filter(|tag| !tag.is_empty())
+            && let Ok(tag) = normalize_tag(tag)
+        {
+            task.tags.insert(tag);
+            continue;
+        }
+        let Some((key, value)) = word.split_once(':').filter(|(_, value)| !value.is_empty()) else {
+            description.push(word);
+            continue;
+        };
+        let valid = match key {
+            "due" => parse_due(value).map(|due| task.due = Some(due)).is_some(),
+            "rec" => parse_recurrence(value)
+                .map(|recurrence| task.recurrence = Some(recurrence))
+                .is_some(),
+            "id" => {
+                task.id = value.to_string();
+                true
+            }
+            "parent" => {
+                task.parent = Some(value.to_string());
+                true
+            }
+            "dep" => {
+                let ids = value.split(',').filter(|id| !id.is_empty());
+                task.depends_on.extend(ids.map(str::to_string));
+                true
+            }
+            "pri" => parse_priority(&format!("({})", value))
+                .map(|letter| priority = Some(letter))
+                .is_some(),
+            _ => {
+                description.push(word);
+                continue;
+            }
+        };
+        if !valid {
+            warnings.push(format!(
+                "Line {}: invalid value '{}', kept in the description",
+                number, word
+            ));
+            description.push(word);
+        }
+    }
+    if description.is_empty() {
+        return None;
+    }
+    task.description = description.join(" ");
+    match priority {
+        Some(Ok(priority)) => task.priority = priority,
+        Some(Err(letter)) => warnings.push(format!(
+            "Line {}: priority ({}) imported as Low",
+            number, letter
+        )),
+        None => {}
+    }
+    Some(task)
+}
+
+fn priority_letter(priority: Priority) -> char {
+    match priority {
+        Priority::High => 'A',
+        Priority::Medium => 'B',
+        Priority::Low => 'C',
+    }
+}
+
+/// `(A)` to `(C)` give a priority, the lower letters the letter itself
+fn parse_priority(word: &str) -> Option<Result<Priority, char>> {
+    let letter = word.strip_prefix('(')?.strip_suffix(')')?;
+    let mut chars = letter.chars();
+    let (Some(letter), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    match letter {
+        'A' => Some(Ok(Priority::High)),
+        'B' => Some(Ok(Priority::Medium)),
+        'C' => Some(Ok(Priority::Low)),
+        'D'..='Z' => Some(Err(letter)),
+        _ => None,
+    }
+}
+
+/// `YYYY-MM-DD` dates are read as midnight
+fn parse_date(word: &str) -> Option<NaiveDateTime> {
+    NaiveDate::parse_from_str(word, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_time(NaiveTime::MIN))
+}
+
+/// `YYYY-MM-DD`, with an optional `THH:MM` time
+fn parse_due(value: &str) -> Option<Due> {
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(NaiveTime::parse_from_str(time, "%H:%M").ok()?)),
+        None => (value, None),
+    };
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(Due { date, time })
+}
+
+/// Rule of the `rec:` extra. The usual todo.txt rules are `1d` (a day after completion),
+/// `+1d`, `+1w` and `+1m`, the others follow the syntax of `add --every` with `_` for spaces.
+fn recurrence_rule(recurrence: &Recurrence) -> String {
+    match recurrence {
+        Recurrence::Daily => "+1d".to_string(),
+        Recurrence::Weekly { weekdays } if weekdays.is_empty() => "+1w".to_string(),
+        Recurrence::Monthly { day: None } => "+1m".to_string(),
+        _ => recurrence.rule().replace(' ', "_"),
+    }
+}
+
+fn parse_recurrence(value: &str) -> Option<Recurrence> {
+    match value {
+        "+1d" => Some(Recurrence::Daily),
+        "+1w" => Some(Recurrence::Weekly {
+            weekdays: Vec::new(),
+        }),
+        "+1m" => Some(Recurrence::Monthly { day: None }),
+        _ => Recurrence::parse(&value.replace('_', " ")).ok(),
+    }
+}
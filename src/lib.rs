@@ -1,14 +1,46 @@
+pub mod error;
+
+pub mod exchange {
+    pub mod csv;
+    pub mod format;
+    pub mod ical;
+    pub mod markdown;
+    pub mod taskwarrior;
+    pub mod todo_txt;
+}
+
 pub mod model {
+    pub mod due;
+    pub mod merge;
     pub mod priority;
+    pub mod recurrence;
+    pub mod search;
+    pub mod selection;
+    pub mod sort;
+    pub mod tags;
     pub mod task;
+    pub mod timestamp;
     pub mod todo_list;
 }
 
 pub mod service {
     pub mod actions;
+    pub mod archive;
+    pub mod history;
+    pub mod lists;
     pub mod manager;
 }
 
+pub mod storage {
+    pub mod backend;
+    pub mod file;
+    pub mod json_file;
+    pub mod lock;
+    pub mod memory;
+    pub mod schema;
+    pub mod sqlite;
+}
+
 pub mod ui {
     pub mod console_ui {
         pub mod console_displayer;
@@ -18,6 +50,7 @@ pub mod ui {
     pub mod cli_argument_parser {
         pub mod cli_displayer;
         pub mod cli_parser;
+        pub mod output_format;
         pub mod trait_cli_displayer;
     }
     pub mod displayer;
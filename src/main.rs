@@ -1,23 +1,72 @@
 use clap::Parser;
+use to_do::error::TodoError;
+use to_do::service::archive::archive_on_complete;
+use to_do::service::history::history_limit;
 use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::backend::open_storage;
 use to_do::ui::cli_argument_parser::cli_displayer::CliDisplayer;
-use to_do::ui::cli_argument_parser::cli_parser::Cli;
+use to_do::ui::cli_argument_parser::cli_parser::{Cli, CliCommand};
 use to_do::ui::console_ui::console_displayer::ConsoleDisplayer;
 use to_do::ui::displayer::Displayer;
 
 fn main() {
     let cli = Cli::parse();
+    // The errors are already notified by the displayers, only the exit code is left to report
+    if let Err(e) = run(cli) {
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn run(cli: Cli) -> Result<(), TodoError> {
+    let mut cli_displayer = CliDisplayer::new();
+    // The named lists are managed without loading any of them
+    if let Some(command @ CliCommand::Lists { .. }) = &cli.command {
+        let mut manager = Manager::new(Box::new(CliDisplayer::quiet()));
+        return cli.evaluate_command(command.clone(), &mut manager, &mut cli_displayer);
+    }
+    // The storage backend is configured by TODO_BACKEND or the file extension
+    let storage = cli
+        .todo_file()
+        .and_then(|file| open_storage(&file))
+        .inspect_err(|e| cli.report_error(e, &mut cli_displayer))?;
+    let history_limit = history_limit().inspect_err(|e| cli.report_error(e, &mut cli_displayer))?;
+    let archive_on_complete =
+        archive_on_complete().inspect_err(|e| cli.report_error(e, &mut cli_displayer))?;
+
     match &cli.command {
         Some(command) => {
-            let mut cli_displayer = CliDisplayer::new();
-            let mut manager = Manager::new(Box::new(CliDisplayer::new()));
-            manager.run();
-            cli.evaluate_command(command.clone(), &mut manager, &mut cli_displayer);
+            // Only the results are written with a structured output or an export to the
+            // standard output, not the loading messages
+            let displayer = if cli.quiet() {
+                CliDisplayer::quiet()
+            } else {
+                CliDisplayer::new()
+            };
+            let mut manager = Manager::new(Box::new(displayer));
+            manager.todo_list.storage = storage;
+            manager.history_limit = history_limit;
+            manager.archive_on_complete = archive_on_complete;
+            // The backups are restored and the migrations checked without loading the todo
+            // list, which may be unreadable or not migrated yet
+            if !matches!(
+                command,
+                CliCommand::Restore { .. } | CliCommand::Migrate { .. }
+            ) && let Err(e) = manager.run()
+            {
+                if cli.quiet() {
+                    cli.report_error(&e, &mut cli_displayer);
+                }
+                return Err(e);
+            }
+            cli.evaluate_command(command.clone(), &mut manager, &mut cli_displayer)
         }
         None => {
             let displayer: Box<dyn Displayer> = Box::new(ConsoleDisplayer::new());
             let mut manager = Manager::new(displayer);
-            manager.run();
+            manager.todo_list.storage = storage;
+            manager.history_limit = history_limit;
+            manager.archive_on_complete = archive_on_complete;
+            manager.run()
         }
     }
 }
@@ -0,0 +1,104 @@
+use chrono::{Datelike, Days, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result};
+
+/// Deadline of a task: a calendar date with an optional time of the day
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Due {
+    pub date: NaiveDate,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<NaiveTime>,
+}
+
+impl Due {
+    /// Parse a due date relative to today's local date
+    pub fn parse(text: &str) -> core::result::Result<Self, String> {
+        Due::parse_relative_to(text, Local::now().date_naive())
+    }
+
+    /// Parse a due date with an optional trailing time (e.g. `next fri 17:00`).
+    /// Accepted dates: `YYYY-MM-DD`, `today`, `tomorrow`, `yesterday`, `+Nd`, `+Nw`, `+Nm`,
+    /// a weekday name (`fri`, `friday`) or `next <weekday>`.
+    pub fn parse_relative_to(text: &str, today: NaiveDate) -> core::result::Result<Self, String> {
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Err("Empty due date".to_string());
+        }
+
+        let (date_text, time) = match text.rsplit_once(' ') {
+            Some((date_text, last)) => match parse_time(last) {
+                Some(time) => (date_text.trim(), Some(time)),
+                None => (text.as_str(), None),
+            },
+            None => (text.as_str(), None),
+        };
+
+        let date = parse_date(date_text, today)
+            .ok_or_else(|| format!("Invalid due date value: {}", date_text))?;
+        Ok(Due { date, time })
+    }
+
+    /// Point in time after which the task is considered overdue
+    pub fn deadline(&self) -> NaiveDateTime {
+        match self.time {
+            Some(time) => self.date.and_time(time),
+            None => self
+                .date
+                .succ_opt()
+                .unwrap_or(self.date)
+                .and_time(NaiveTime::MIN),
+        }
+    }
+
+    pub fn is_past(&self, now: NaiveDateTime) -> bool {
+        self.deadline() <= now
+    }
+}
+
+impl Display for Due {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self.time {
+            Some(time) => write!(f, "{} {}", self.date, time.format("%H:%M")),
+            None => write!(f, "{}", self.date),
+        }
+    }
+}
+
+fn parse_time(text: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(text, "%H:%M").ok()
+}
+
+fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    match text {
+        "today" => return Some(today),
+        "tomorrow" => return today.checked_add_days(Days::new(1)),
+        "yesterday" => return today.checked_sub_days(Days::new(1)),
+        _ => {}
+    }
+
+    if let Some(offset) = text.strip_prefix('+') {
+        let unit = offset.chars().last()?;
+        let amount: u32 = offset[..offset.len() - unit.len_utf8()].parse().ok()?;
+        return match unit {
+            'd' => today.checked_add_days(Days::new(amount.into())),
+            'w' => today.checked_add_days(Days::new(u64::from(amount) * 7)),
+            'm' => today.checked_add_months(Months::new(amount)),
+            _ => None,
+        };
+    }
+
+    // Both "fri" and "next fri" refer to the first friday strictly after today
+    let weekday = text
+        .strip_prefix("next ")
+        .unwrap_or(text)
+        .trim()
+        .parse::<Weekday>()
+        .ok()?;
+    let days_ahead =
+        (weekday.num_days_from_monday() + 6 - today.weekday().num_days_from_monday()) % 7 + 1;
+    today.checked_add_days(Days::new(days_ahead.into()))
+}
@@ -0,0 +1,92 @@
+use crate::model::task::Task;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Result of merging the changes made to a todo list by two processes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Merge {
+    pub tasks: Vec<Task>,
+    /// New IDs of the tasks added on our side whose ID was taken by the other side
+    pub renamed: BTreeMap<String, String>,
+}
+
+/// Three-way merge of the tasks, matched by ID: `base` is the version both sides started
+/// from, `ours` the tasks about to be saved and `theirs` the tasks saved in the meantime.
+/// - A task changed on a single side keeps that change; changed on both sides, ours wins.
+/// - A task removed on either side stays removed.
+/// - Tasks added on both sides are all kept, ours being renumbered when their ID is taken.
+///
+/// The tasks keep the order of `theirs`, followed by the ones we added.
+/// Dependencies on removed tasks are dropped, and subtasks of removed tasks become top level tasks.
+pub fn merge_tasks(base: &[Task], ours: &[Task], theirs: &[Task]) -> Merge {
+    let find = |tasks: &[Task], id: &str| tasks.iter().position(|t| t.id == id);
+
+    // Each task with whether it comes from our side
+    let mut merged = Vec::new();
+    for task in theirs {
+        match (find(base, &task.id), find(ours, &task.id)) {
+            // Added by them
+            (None, _) => merged.push((task.clone(), false)),
+            // Removed by us
+            (Some(_), None) => {}
+            (Some(b), Some(o)) if ours[o] != base[b] => merged.push((ours[o].clone(), true)),
+            (Some(_), Some(_)) => merged.push((task.clone(), false)),
+        }
+    }
+
+    // Added by us, renumbered after the highest ID when theirs took it
+    let added = ours
+        .iter()
+        .filter(|task| find(base, &task.id).is_none())
+        .collect::<Vec<_>>();
+    let mut next_id = theirs
+        .iter()
+        .chain(ours)
+        .filter_map(|task| u32::from_str_radix(&task.id, 16).ok())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut renamed = BTreeMap::new();
+    for task in &added {
+        if find(theirs, &task.id).is_some() {
+            renamed.insert(task.id.clone(), format!("{:X}", next_id));
+            next_id += 1;
+        }
+    }
+    for task in added {
+        let mut task = task.clone();
+        if let Some(id) = renamed.get(&task.id) {
+            task.id = id.clone();
+        }
+        merged.push((task, true));
+    }
+
+    // Only our own tasks can refer to the renumbered ones
+    let mut tasks = merged
+        .into_iter()
+        .map(|(mut task, from_ours)| {
+            if from_ours {
+                let rename = |id: &String| renamed.get(id).unwrap_or(id).clone();
+                task.parent = task.parent.as_ref().map(rename);
+                task.depends_on = task.depends_on.iter().map(rename).collect();
+            }
+            task
+        })
+        .collect::<Vec<_>>();
+
+    let ids = tasks
+        .iter()
+        .map(|task| task.id.clone())
+        .collect::<BTreeSet<_>>();
+    for task in tasks.iter_mut() {
+        task.depends_on
+            .retain(|dependency| ids.contains(dependency));
+        if task
+            .parent
+            .as_ref()
+            .is_some_and(|parent| !ids.contains(parent))
+        {
+            task.parent = None;
+        }
+    }
+    Merge { tasks, renamed }
+}
@@ -2,7 +2,8 @@ use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+/// Priorities are ordered by importance: High > Medium > Low
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Priority {
     Low,
     Medium,
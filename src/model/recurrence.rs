@@ -0,0 +1,140 @@
+use crate::model::due::Due;
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result};
+
+/// Rule used to create the next occurrence of a task when it is completed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum Recurrence {
+    /// Every day after the previous due date
+    Daily,
+    /// On the given weekdays, or every 7 days when none is given
+    Weekly { weekdays: Vec<Weekday> },
+    /// On the given day of the month, or the same day as the previous due date
+    Monthly { day: Option<u32> },
+    /// A number of days after the task was completed
+    AfterCompletion { days: u32 },
+}
+
+impl Recurrence {
+    /// Parse a recurrence rule: `daily`, `weekly`, `weekly mon,thu`, `mon,thu`,
+    /// `monthly`, `monthly 15` or `3d` (every 3 days after completion).
+    pub fn parse(text: &str) -> core::result::Result<Self, String> {
+        let text = text.trim().to_lowercase();
+        let (rule, argument) = match text.split_once(' ') {
+            Some((rule, argument)) => (rule, Some(argument.trim())),
+            None => (text.as_str(), None),
+        };
+        let invalid = || format!("Invalid recurrence value: {}", text);
+
+        match (rule, argument) {
+            ("daily" | "day", None) => Ok(Recurrence::Daily),
+            ("weekly" | "week", None) => Ok(Recurrence::Weekly {
+                weekdays: Vec::new(),
+            }),
+            ("weekly" | "week", Some(weekdays)) => Ok(Recurrence::Weekly {
+                weekdays: parse_weekdays(weekdays).ok_or_else(invalid)?,
+            }),
+            ("monthly" | "month", None) => Ok(Recurrence::Monthly { day: None }),
+            ("monthly" | "month", Some(day)) => match day.parse::<u32>() {
+                Ok(day) if (1..=31).contains(&day) => Ok(Recurrence::Monthly { day: Some(day) }),
+                _ => Err(invalid()),
+            },
+            (rule, None) => {
+                if let Some(days) = rule.trim_start_matches('+').strip_suffix('d') {
+                    match days.parse::<u32>() {
+                        Ok(days) if days > 0 => Ok(Recurrence::AfterCompletion { days }),
+                        _ => Err(invalid()),
+                    }
+                } else {
+                    parse_weekdays(rule)
+                        .map(|weekdays| Recurrence::Weekly { weekdays })
+                        .ok_or_else(invalid)
+                }
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Rule in the syntax read by `parse`, e.g. `weekly mon,thu` or `3d`
+    pub fn rule(&self) -> String {
+        match self {
+            Recurrence::Daily => "daily".to_string(),
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => "weekly".to_string(),
+            Recurrence::Weekly { weekdays } => {
+                let weekdays = weekdays
+                    .iter()
+                    .map(|weekday| weekday.to_string().to_lowercase())
+                    .collect::<Vec<_>>();
+                format!("weekly {}", weekdays.join(","))
+            }
+            Recurrence::Monthly { day: None } => "monthly".to_string(),
+            Recurrence::Monthly { day: Some(day) } => format!("monthly {}", day),
+            Recurrence::AfterCompletion { days } => format!("{}d", days),
+        }
+    }
+
+    /// Compute the due date of the occurrence following the one due at `previous`
+    /// and completed on `completed_on`. The time of the day is kept.
+    pub fn next_due(&self, previous: Option<Due>, completed_on: NaiveDate) -> Due {
+        let base = previous.map_or(completed_on, |due| due.date);
+        let date = match self {
+            Recurrence::Daily => base.checked_add_days(Days::new(1)),
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => {
+                base.checked_add_days(Days::new(7))
+            }
+            Recurrence::Weekly { weekdays } => base
+                .iter_days()
+                .skip(1)
+                .take(7)
+                .find(|date| weekdays.contains(&date.weekday())),
+            Recurrence::Monthly { day } => {
+                let day = day.unwrap_or(base.day());
+                base.checked_add_months(Months::new(1)).map(|next_month| {
+                    // Days missing in shorter months fall back to the last day of the month
+                    (1..=day)
+                        .rev()
+                        .find_map(|day| next_month.with_day(day))
+                        .unwrap_or(next_month)
+                })
+            }
+            Recurrence::AfterCompletion { days } => {
+                completed_on.checked_add_days(Days::new((*days).into()))
+            }
+        };
+        Due {
+            date: date.unwrap_or(base),
+            time: previous.and_then(|due| due.time),
+        }
+    }
+}
+
+impl Display for Recurrence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly { weekdays } if weekdays.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly { weekdays } => {
+                let weekdays = weekdays.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+                write!(f, "weekly on {}", weekdays.join(", "))
+            }
+            Recurrence::Monthly { day: None } => write!(f, "monthly"),
+            Recurrence::Monthly { day: Some(day) } => write!(f, "monthly on day {}", day),
+            Recurrence::AfterCompletion { days } => {
+                write!(f, "every {} days after completion", days)
+            }
+        }
+    }
+}
+
+fn parse_weekdays(text: &str) -> Option<Vec<Weekday>> {
+    let mut weekdays = Vec::new();
+    for weekday in text.split(',') {
+        let weekday = weekday.trim().parse::<Weekday>().ok()?;
+        if !weekdays.contains(&weekday) {
+            weekdays.push(weekday);
+        }
+    }
+    weekdays.sort_by_key(|w| w.num_days_from_monday());
+    Some(weekdays)
+}
@@ -0,0 +1,64 @@
+use crate::model::task::Task;
+use regex::{Regex, RegexBuilder};
+
+const HIGHLIGHT_START: &str = "\x1b[1;33m";
+const HIGHLIGHT_END: &str = "\x1b[0m";
+
+/// How the search text is matched against the task descriptions
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SearchOptions {
+    pub ignore_case: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+/// Compiled search over task descriptions
+#[derive(Debug, Clone)]
+pub struct Search {
+    pattern: Regex,
+}
+
+impl Search {
+    /// Build a search for the given text, which is a plain substring unless `options.regex` is set
+    pub fn new(text: &str, options: SearchOptions) -> Result<Self, String> {
+        if text.is_empty() {
+            return Err("Empty search text".to_string());
+        }
+        let mut pattern = if options.regex {
+            text.to_string()
+        } else {
+            regex::escape(text)
+        };
+        if options.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        let pattern = RegexBuilder::new(&pattern)
+            .case_insensitive(options.ignore_case)
+            .build()
+            .map_err(|e| format!("Invalid search pattern '{}': {}", text, e))?;
+        Ok(Search { pattern })
+    }
+
+    pub fn is_match(&self, task: &Task) -> bool {
+        self.pattern.is_match(&task.description)
+    }
+
+    /// Wrap every match found in the text with terminal highlight codes
+    pub fn highlight(&self, text: &str) -> String {
+        self.pattern
+            .replace_all(text, |captures: &regex::Captures| {
+                format!("{}{}{}", HIGHLIGHT_START, &captures[0], HIGHLIGHT_END)
+            })
+            .into_owned()
+    }
+
+    /// Highlight the matches within the description part of a rendered task line
+    pub fn highlight_task_line(&self, line: &str, task: &Task) -> String {
+        let description = format!("Description: {}", task.description);
+        line.replacen(
+            &description,
+            &format!("Description: {}", self.highlight(&task.description)),
+            1,
+        )
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::TodoError;
+use crate::model::priority::Priority;
+use crate::model::tags::normalize_tag;
+use crate::model::task::Task;
+
+/// Task field compared by a `--where` condition
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Priority(Priority),
+    Completed(bool),
+    Tag(String),
+    /// `None` for the top level tasks
+    Parent(Option<String>),
+}
+
+/// `key=value` or `key!=value` condition on a task field
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: Field,
+    pub negated: bool,
+}
+
+/// Conditions of a `--where` expression, e.g. `priority=low,completed=true`.
+/// A task matches when it meets all of them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    pub conditions: Vec<Condition>,
+}
+
+impl Filter {
+    /// Parse comma separated conditions on `priority`, `completed`, `tag` and `parent`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let conditions = text
+            .split(',')
+            .map(str::trim)
+            .filter(|condition| !condition.is_empty())
+            .map(Condition::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        if conditions.is_empty() {
+            return Err("Empty filter, expected e.g. priority=low,completed=true".to_string());
+        }
+        Ok(Filter { conditions })
+    }
+
+    pub fn is_match(&self, task: &Task) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.is_match(task))
+    }
+}
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (key, value, negated) = if let Some((key, value)) = text.split_once("!=") {
+            (key, value, true)
+        } else if let Some((key, value)) = text.split_once('=') {
+            (key, value, false)
+        } else {
+            return Err(format!(
+                "Invalid condition '{}', expected key=value or key!=value",
+                text
+            ));
+        };
+        let value = value.trim();
+        let field = match key.trim().to_lowercase().as_str() {
+            "priority" => Field::Priority(Priority::str_to_priority(value)?),
+            "completed" => Field::Completed(value.parse().map_err(|_| {
+                format!(
+                    "Invalid completed value '{}', expected true or false",
+                    value
+                )
+            })?),
+            "tag" => Field::Tag(normalize_tag(value)?),
+            "parent" if value.eq_ignore_ascii_case("none") => Field::Parent(None),
+            "parent" => Field::Parent(Some(value.to_string())),
+            other => {
+                return Err(format!(
+                    "Unknown filter key '{}', expected priority, completed, tag or parent",
+                    other
+                ));
+            }
+        };
+        Ok(Condition { field, negated })
+    }
+
+    fn is_match(&self, task: &Task) -> bool {
+        let matched = match &self.field {
+            Field::Priority(priority) => task.priority == *priority,
+            Field::Completed(completed) => task.completed == *completed,
+            Field::Tag(tag) => task.tags.contains(tag),
+            Field::Parent(parent) => task.parent == *parent,
+        };
+        matched != self.negated
+    }
+}
+
+/// IDs of the tasks selected by `--id` values and an optional filter, in the order of the list.
+/// Each value is an ID or an inclusive range of IDs such as `3-9`. Explicit IDs must exist,
+/// the IDs of a range that match no task are skipped. Without any value, every task is
+/// a candidate for the filter.
+pub fn select_tasks(
+    tasks: &[Task],
+    values: &[String],
+    filter: Option<&Filter>,
+) -> Result<Vec<String>, TodoError> {
+    let invalid =
+        |value: &str| TodoError::Validation(format!("Invalid ID or ID range '{}'", value));
+    let mut ranges = Vec::new();
+    for value in values {
+        let value = value.trim();
+        match value.split_once('-') {
+            Some((start, end)) => {
+                let parse =
+                    |id: &str| u32::from_str_radix(id.trim(), 16).map_err(|_| invalid(value));
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(invalid(value));
+                }
+                ranges.push((start, end));
+            }
+            None => {
+                if !tasks.iter().any(|task| task.id == value) {
+                    return Err(TodoError::NotFound(value.to_string()));
+                }
+                let id = u32::from_str_radix(value, 16).map_err(|_| invalid(value))?;
+                ranges.push((id, id));
+            }
+        }
+    }
+    Ok(tasks
+        .iter()
+        .filter(|task| {
+            values.is_empty()
+                || u32::from_str_radix(&task.id, 16).is_ok_and(|id| {
+                    ranges
+                        .iter()
+                        .any(|(start, end)| (*start..=*end).contains(&id))
+                })
+        })
+        .filter(|task| filter.is_none_or(|filter| filter.is_match(task)))
+        .map(|task| task.id.clone())
+        .collect())
+}
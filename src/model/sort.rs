@@ -0,0 +1,70 @@
+use crate::model::task::Task;
+use clap::ValueEnum;
+use std::cmp::Ordering;
+
+/// Field used to order the tasks of a listing
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SortKey {
+    /// Highest priority first
+    Priority,
+    Id,
+    /// Pending tasks before completed ones
+    Status,
+    Description,
+    /// Earliest due date first, tasks without one last
+    Due,
+    Created,
+    Updated,
+    Completed,
+}
+
+impl SortKey {
+    /// Parse a comma separated list of sort keys (e.g. `priority,due`)
+    pub fn parse_list(text: &str) -> Result<Vec<SortKey>, String> {
+        text.split(',')
+            .map(|key| {
+                SortKey::from_str(key.trim(), true)
+                    .map_err(|_| format!("Invalid sort key: {}", key.trim()))
+            })
+            .collect()
+    }
+
+    pub fn compare(&self, a: &Task, b: &Task) -> Ordering {
+        match self {
+            SortKey::Priority => b.priority.cmp(&a.priority),
+            SortKey::Id => numeric_id(a).cmp(&numeric_id(b)),
+            SortKey::Status => a.completed.cmp(&b.completed),
+            SortKey::Description => a
+                .description
+                .to_lowercase()
+                .cmp(&b.description.to_lowercase()),
+            SortKey::Due => match (a.due, b.due) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+            SortKey::Created => a.created_at.cmp(&b.created_at),
+            SortKey::Updated => a.updated_at.cmp(&b.updated_at),
+            SortKey::Completed => a.completed_at.cmp(&b.completed_at),
+        }
+    }
+}
+
+/// Sort the tasks by each key in turn, the later keys breaking the ties of the former ones.
+/// The sort is stable, so tasks equal on every key keep their insertion order,
+/// and `reverse` flips the whole resulting order.
+pub fn sort_tasks(tasks: &mut [&Task], keys: &[SortKey], reverse: bool) {
+    tasks.sort_by(|a, b| {
+        keys.iter().fold(Ordering::Equal, |ordering, key| {
+            ordering.then_with(|| key.compare(a, b))
+        })
+    });
+    if reverse {
+        tasks.reverse();
+    }
+}
+
+fn numeric_id(task: &Task) -> Option<u32> {
+    u32::from_str_radix(&task.id, 16).ok()
+}
@@ -0,0 +1,33 @@
+use std::collections::BTreeSet;
+
+/// Normalize a tag given by the user: a single lowercase word, with an optional leading `+`
+pub fn normalize_tag(text: &str) -> Result<String, String> {
+    let tag = text.trim().trim_start_matches('+').to_lowercase();
+    if tag.is_empty() || tag.chars().any(char::is_whitespace) {
+        Err(format!("Invalid tag value: {}", text))
+    } else {
+        Ok(tag)
+    }
+}
+
+/// Split the `+tag` words out of a description.
+/// Returns the description without them and the set of tags found.
+/// A description made only of tags is kept as it is, so it never becomes empty.
+pub fn extract_tags(description: &str) -> (String, BTreeSet<String>) {
+    let mut tags = BTreeSet::new();
+    let mut words = Vec::new();
+    for word in description.split_whitespace() {
+        match word.strip_prefix('+').filter(|tag| !tag.is_empty()) {
+            Some(tag) => {
+                tags.insert(tag.to_lowercase());
+            }
+            None => words.push(word),
+        }
+    }
+
+    if tags.is_empty() || words.is_empty() {
+        (description.to_string(), tags)
+    } else {
+        (words.join(" "), tags)
+    }
+}
@@ -1,10 +1,120 @@
+use crate::model::due::Due;
 use crate::model::priority::Priority;
+use crate::model::recurrence::Recurrence;
+use crate::model::timestamp::{self, format_timestamp};
+use chrono::{Local, NaiveDateTime};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Task {
     pub id: String,
     pub description: String,
     pub priority: Priority,
     pub completed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<Due>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub depends_on: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<NaiveDateTime>,
+    /// UUID of the task in Taskwarrior, kept to export it back as the same task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+}
+
+/// Optional attributes of a task that can be given when adding or editing it
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TaskOptions {
+    #[serde(default)]
+    pub due: Option<Due>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub depends_on: BTreeSet<String>,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+}
+
+impl Task {
+    /// Return the optional attributes currently set on the task
+    pub fn options(&self) -> TaskOptions {
+        TaskOptions {
+            due: self.due,
+            tags: self.tags.clone(),
+            parent: self.parent.clone(),
+            depends_on: self.depends_on.clone(),
+            recurrence: self.recurrence.clone(),
+        }
+    }
+
+    /// A task is overdue when it is still pending and its deadline has passed
+    pub fn is_overdue(&self, now: NaiveDateTime) -> bool {
+        !self.completed && self.due.is_some_and(|due| due.is_past(now))
+    }
+
+    /// Change the completion status, recording when the task was completed
+    pub fn set_completed(&mut self, completed: bool) {
+        let now = timestamp::now();
+        self.completed = completed;
+        self.completed_at = completed.then_some(now);
+        self.updated_at = Some(now);
+    }
+
+    /// Describe when the task was created, last updated and completed.
+    /// Tasks saved before timestamps were recorded have none of them.
+    pub fn timestamps(&self) -> String {
+        let mut text = String::new();
+        for (label, timestamp) in [
+            ("Created", self.created_at),
+            ("Updated", self.updated_at),
+            ("Completed on", self.completed_at),
+        ] {
+            if let Some(timestamp) = timestamp {
+                text.push_str(&format!(", {}: {}", label, format_timestamp(timestamp)));
+            }
+        }
+        text
+    }
+}
+
+impl Display for Task {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "ID: {}, Description: {}, Priority: {}, Completed: {}",
+            self.id, self.description, self.priority, self.completed
+        )?;
+        if let Some(due) = self.due {
+            write!(f, ", Due: {}", due)?;
+            if self.is_overdue(Local::now().naive_local()) {
+                write!(f, " (OVERDUE)")?;
+            }
+        }
+        if !self.tags.is_empty() {
+            let tags = self.tags.iter().cloned().collect::<Vec<_>>();
+            write!(f, ", Tags: {}", tags.join(", "))?;
+        }
+        if !self.depends_on.is_empty() {
+            let depends_on = self.depends_on.iter().cloned().collect::<Vec<_>>();
+            write!(f, ", Depends on: {}", depends_on.join(", "))?;
+        }
+        if let Some(recurrence) = &self.recurrence {
+            write!(f, ", Repeats: {}", recurrence)?;
+        }
+        Ok(())
+    }
 }
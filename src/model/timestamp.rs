@@ -0,0 +1,38 @@
+use crate::model::due::Due;
+use chrono::{Local, NaiveDateTime, TimeDelta, Timelike};
+
+/// Current local time, truncated to whole seconds
+pub fn now() -> NaiveDateTime {
+    let now = Local::now().naive_local();
+    now.with_nanosecond(0).unwrap_or(now)
+}
+
+pub fn format_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// Parse the start of a time window: an age such as `7d`, `2w` or `12h`,
+/// or a date in any of the formats accepted for due dates (e.g. `2026-10-01`, `yesterday`).
+pub fn parse_since(text: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let text = text.trim().to_lowercase();
+    let invalid = || format!("Invalid time value: {}", text);
+
+    if let Some(unit) = text.chars().last()
+        && let Ok(amount) = text[..text.len() - unit.len_utf8()].parse::<i64>()
+    {
+        let age = match unit {
+            'h' => TimeDelta::try_hours(amount),
+            'd' => TimeDelta::try_days(amount),
+            'w' => TimeDelta::try_weeks(amount),
+            _ => None,
+        }
+        .ok_or_else(invalid)?;
+        return now.checked_sub_signed(age).ok_or_else(invalid);
+    }
+
+    let due = Due::parse_relative_to(&text, now.date()).map_err(|_| invalid())?;
+    Ok(match due.time {
+        Some(time) => due.date.and_time(time),
+        None => due.date.and_hms_opt(0, 0, 0).unwrap_or_default(),
+    })
+}
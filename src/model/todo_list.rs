@@ -1,42 +1,170 @@
+use crate::error::TodoError;
+use crate::model::merge::merge_tasks;
 use crate::model::priority::Priority;
-use crate::model::task::Task;
-use serde::{Deserialize, Serialize};
-use std::path::Path;
+use crate::model::task::{Task, TaskOptions};
+use crate::model::timestamp;
+use crate::storage::backend::{Storage, TodoData};
+use crate::storage::json_file::JsonFileStorage;
+use crate::storage::lock::FileLock;
+use chrono::{NaiveDate, NaiveDateTime};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Pairs (dependent, dependency) of the dependencies on tasks taken out of the todo list
+pub type Dependents = Vec<(String, String)>;
+
+#[derive(Debug)]
 pub struct TodoList {
     pub tasks: Vec<Task>,
     pub next_id: u32,
-    pub file_name: String,
+    pub storage: Box<dyn Storage>,
+    /// Tasks as last loaded from or saved to the storage, None when it was never loaded
+    pub stored: Option<Vec<Task>>,
+    /// When the todo list was first saved
+    pub created_at: Option<NaiveDateTime>,
+    /// Tasks moved to or from the archive since the last save
+    archive_changes: ArchiveChanges,
+    /// State of the todo list when each transaction in progress began, the innermost last
+    transactions: Vec<Snapshot>,
+    /// Lock of the storage, held from the beginning of a transaction until it ends
+    transaction_lock: Option<FileLock>,
+}
+
+/// Moves between the todo list and its archive, applied to the stored archive on the next save
+#[derive(Debug, Clone, Default)]
+struct ArchiveChanges {
+    archived: Vec<Task>,
+    /// IDs of the tasks taken out of the archive
+    unarchived: Vec<String>,
+}
+
+impl ArchiveChanges {
+    fn is_empty(&self) -> bool {
+        self.archived.is_empty() && self.unarchived.is_empty()
+    }
+
+    fn apply(&self, archive: &mut Vec<Task>) {
+        archive.retain(|task| {
+            !self.unarchived.contains(&task.id) && !self.archived.iter().any(|t| t.id == task.id)
+        });
+        archive.extend(self.archived.iter().cloned());
+    }
+}
+
+/// Tasks, next ID and archive moves as they were when a transaction began
+#[derive(Debug)]
+struct Snapshot {
+    tasks: Vec<Task>,
+    next_id: u32,
+    archive_changes: ArchiveChanges,
+}
+
+impl Default for TodoList {
+    fn default() -> Self {
+        TodoList::new()
+    }
 }
 
 impl TodoList {
-    /// Create a new empty todo list
+    /// Create a new empty todo list saved in `todo_list.json`
     pub fn new() -> Self {
+        TodoList::with_storage(Box::new(JsonFileStorage::new("todo_list.json")))
+    }
+
+    /// Create a new empty todo list saved in the given storage
+    pub fn with_storage(storage: Box<dyn Storage>) -> Self {
         TodoList {
             tasks: Vec::<Task>::new(),
             next_id: 1,
-            file_name: "todo_list.json".to_string(),
+            storage,
+            stored: None,
+            created_at: None,
+            archive_changes: ArchiveChanges::default(),
+            transactions: Vec::new(),
+            transaction_lock: None,
         }
     }
 
     /// Add a new task to the todo list
-    pub fn add_task(&mut self, description: String, priority: Priority) -> String {
+    pub fn add_task(
+        &mut self,
+        description: String,
+        priority: Priority,
+    ) -> Result<String, TodoError> {
+        self.add_task_with_options(description, priority, TaskOptions::default())
+    }
+
+    /// Add a new task to the todo list with its optional attributes already set
+    pub fn add_task_with_options(
+        &mut self,
+        description: String,
+        priority: Priority,
+        options: TaskOptions,
+    ) -> Result<String, TodoError> {
         let id_new = format!("{:X}", self.next_id);
+        let now = timestamp::now();
         self.tasks.push(Task {
             id: id_new.clone(),
             description,
             priority,
             completed: false,
+            due: options.due,
+            tags: options.tags,
+            parent: options.parent,
+            depends_on: options.depends_on,
+            recurrence: options.recurrence,
+            created_at: Some(now),
+            updated_at: Some(now),
+            completed_at: None,
+            uuid: None,
         });
         self.next_id += 1;
-        self.save();
-        id_new
+        // The ID may have been taken by another ToDo saving in the meantime
+        let renamed = self.save_merged()?;
+        Ok(renamed.get(&id_new).cloned().unwrap_or(id_new))
+    }
+
+    /// Add tasks read from another format, giving them fresh IDs. Their IDs only link them
+    /// together: their parents and dependencies follow the new IDs, or are kept when they
+    /// are tasks of the todo list (see `Import::skip_existing`), the others are dropped.
+    /// Returns the added tasks.
+    pub fn import_tasks(&mut self, tasks: Vec<Task>) -> Result<Vec<Task>, TodoError> {
+        // The references to tasks of the todo list are kept, unless an imported task has their ID
+        let mut ids = self
+            .tasks
+            .iter()
+            .map(|task| (task.id.clone(), task.id.clone()))
+            .collect::<BTreeMap<_, _>>();
+        for task in &tasks {
+            ids.insert(task.id.clone(), format!("{:X}", self.next_id));
+            self.next_id += 1;
+        }
+        let now = timestamp::now();
+        let mut added = Vec::new();
+        for mut task in tasks {
+            task.id = ids[&task.id].clone();
+            added.push(task.id.clone());
+            task.parent = task.parent.and_then(|parent| ids.get(&parent).cloned());
+            task.depends_on = task
+                .depends_on
+                .iter()
+                .filter_map(|dependency| ids.get(dependency).cloned())
+                .collect();
+            task.created_at = task.created_at.or(Some(now));
+            task.updated_at = Some(now);
+            self.tasks.push(task);
+        }
+        // The IDs may have been taken by another ToDo saving in the meantime
+        let renamed = self.save_merged()?;
+        Ok(added
+            .iter()
+            .map(|id| renamed.get(id).unwrap_or(id))
+            .filter_map(|id| self.tasks.iter().find(|t| t.id == *id).cloned())
+            .collect())
     }
 
-    pub fn push_task(&mut self, task: Task) {
+    pub fn push_task(&mut self, task: Task) -> Result<(), TodoError> {
         self.tasks.push(task);
-        self.save();
+        self.save()
     }
 
     /// Return the tasks in the todo list
@@ -48,104 +176,598 @@ impl TodoList {
         self.tasks.iter_mut().find(|t| t.id.eq(id))
     }
 
+    /// Replace the stored task having the same ID, timestamps included
+    pub fn replace_task(&mut self, task: Task) -> Result<(), TodoError> {
+        let stored = self
+            .get_task_mut(&task.id)
+            .ok_or_else(|| TodoError::NotFound(task.id.clone()))?;
+        *stored = task;
+        self.save()
+    }
+
     /// Mark a task as completed/uncompleted by ID
-    pub fn toggle_task_status(&mut self, id: String) {
+    pub fn toggle_task_status(&mut self, id: String) -> Result<(), TodoError> {
         // the if let with Option<T> is a way to match against the Some(T) variant
         if let Some(task) = self.tasks.iter_mut().find(|t| t.id == id) {
-            task.completed = !task.completed;
-            self.save();
+            task.set_completed(!task.completed);
+            self.save()
         } else {
-            panic!(
-                "IllegalArgument Error: Task with ID: {} not found when trying to toggle its state.",
-                id
-            );
+            Err(TodoError::NotFound(id))
         }
     }
 
-    /// Remove a task from the todo list by ID
-    pub fn remove_task(&mut self, id: String) {
-        if let Some(pos) = self.tasks.iter().position(|t| t.id == id) {
-            self.tasks.remove(pos);
-            self.save();
+    /// Remove a task and all its subtasks from the todo list by ID.
+    /// The dependencies of other tasks on the removed ones are dropped as well.
+    /// Returns the removed tasks, parents before their children.
+    pub fn remove_task(&mut self, id: String) -> Result<Vec<Task>, TodoError> {
+        if self.tasks.iter().any(|t| t.id == id) {
+            let mut ids = vec![id.clone()];
+            ids.extend(self.descendants(&id));
+            let removed = ids
+                .iter()
+                .filter_map(|id| self.tasks.iter().find(|t| t.id == *id).cloned())
+                .collect();
+            self.tasks.retain(|t| !ids.contains(&t.id));
+            for task in self.tasks.iter_mut() {
+                task.depends_on
+                    .retain(|dependency| !ids.contains(dependency));
+            }
+            self.save()?;
+            Ok(removed)
         } else {
-            panic!(
-                "IllegalArgument Error: Task with ID: {} not found when trying to remove.",
+            Err(TodoError::NotFound(id))
+        }
+    }
+
+    /// Archived tasks, including the moves not saved yet
+    pub fn archived_tasks(&self) -> Result<Vec<Task>, TodoError> {
+        let mut archive = self.storage.load_archive()?;
+        self.archive_changes.apply(&mut archive);
+        Ok(archive)
+    }
+
+    /// Move tasks and all their subtasks from the todo list to its archive by ID.
+    /// The dependencies of other tasks on the archived ones are dropped.
+    /// Returns the archived tasks in the order of the list, with the dropped (dependent, dependency) pairs.
+    pub fn archive_tasks(&mut self, ids: &[String]) -> Result<(Vec<Task>, Dependents), TodoError> {
+        let mut archived_ids = Vec::new();
+        for id in ids {
+            if !self.tasks.iter().any(|t| t.id == *id) {
+                return Err(TodoError::NotFound(id.clone()));
+            }
+            for id in std::iter::once(id.clone()).chain(self.descendants(id)) {
+                if !archived_ids.contains(&id) {
+                    archived_ids.push(id);
+                }
+            }
+        }
+        let archived = self
+            .tasks
+            .iter()
+            .filter(|t| archived_ids.contains(&t.id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let dependents = self.dependents_of(&archived_ids);
+        for task in self.tasks.iter_mut() {
+            task.depends_on
+                .retain(|dependency| !archived_ids.contains(dependency));
+        }
+        self.move_to_archive(archived.clone())?;
+        Ok((archived, dependents))
+    }
+
+    /// Put tasks in the archive as they are given, taking them out of the todo list
+    pub fn move_to_archive(&mut self, tasks: Vec<Task>) -> Result<(), TodoError> {
+        let ids = tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+        self.tasks.retain(|t| !ids.contains(&t.id));
+        self.archive_changes
+            .unarchived
+            .retain(|id| !ids.contains(id));
+        self.archive_changes
+            .archived
+            .retain(|t| !ids.contains(&t.id));
+        self.archive_changes.archived.extend(tasks);
+        self.save()
+    }
+
+    /// Move archived tasks and their archived subtasks back to the todo list by ID.
+    /// A task whose parent was removed in the meantime comes back as a top level task.
+    /// Returns the tasks as they were in the archive.
+    pub fn unarchive_tasks(&mut self, ids: &[String]) -> Result<Vec<Task>, TodoError> {
+        let archive = self.archived_tasks()?;
+        let mut unarchived_ids = Vec::new();
+        for id in ids {
+            let task = archive
+                .iter()
+                .find(|t| t.id == *id)
+                .ok_or_else(|| TodoError::NotFound(id.clone()))?;
+            if let Some(parent) = &task.parent
+                && !ids.contains(parent)
+                && archive.iter().any(|t| t.id == *parent)
+            {
+                return Err(TodoError::Validation(format!(
+                    "Task {} is a subtask of the archived task {}, unarchive that one instead",
+                    id, parent
+                )));
+            }
+            unarchived_ids.push(id.clone());
+        }
+        // The archived subtasks come back with their parent
+        let mut index = 0;
+        while index < unarchived_ids.len() {
+            for task in &archive {
+                if task.parent.as_ref() == Some(&unarchived_ids[index])
+                    && !unarchived_ids.contains(&task.id)
+                {
+                    unarchived_ids.push(task.id.clone());
+                }
+            }
+            index += 1;
+        }
+        if let Some(id) = unarchived_ids
+            .iter()
+            .find(|id| self.tasks.iter().any(|t| t.id == **id))
+        {
+            return Err(TodoError::Validation(format!(
+                "Task {} cannot be unarchived, another task has its ID",
                 id
-            );
+            )));
+        }
+
+        let unarchived = archive
+            .into_iter()
+            .filter(|t| unarchived_ids.contains(&t.id))
+            .collect::<Vec<_>>();
+        for task in &unarchived {
+            let mut task = task.clone();
+            if task.parent.as_ref().is_some_and(|parent| {
+                !unarchived_ids.contains(parent) && !self.tasks.iter().any(|t| t.id == *parent)
+            }) {
+                task.parent = None;
+            }
+            let id = u32::from_str_radix(&task.id, 16)
+                .map_err(|_| TodoError::Parse(format!("Failed to parse task ID: {}", task.id)))?;
+            self.next_id = self.next_id.max(id + 1);
+            self.tasks.push(task);
         }
+        self.archive_changes
+            .archived
+            .retain(|t| !unarchived_ids.contains(&t.id));
+        self.archive_changes.unarchived.extend(unarchived_ids);
+        self.save()?;
+        Ok(unarchived)
     }
 
     /// Edit a task fields
-    pub fn edit_task(&mut self, id: &str, new_fields: (&str, &Priority)) {
-        let task = self.get_task_mut(id).expect(&format!(
-            "IllegalState Error: Task not found for ID: {}",
-            id
-        ));
+    pub fn edit_task(&mut self, id: &str, new_fields: (&str, &Priority)) -> Result<(), TodoError> {
+        let task = self
+            .get_task_mut(id)
+            .ok_or_else(|| TodoError::NotFound(id.to_string()))?;
         if !task.description.eq(new_fields.0) {
-            task.description = new_fields.0.to_string()
+            task.description = new_fields.0.to_string();
+            task.updated_at = Some(timestamp::now());
         }
 
         if !task.priority.eq(new_fields.1) {
-            task.priority = *new_fields.1
+            task.priority = *new_fields.1;
+            task.updated_at = Some(timestamp::now());
         }
 
-        self.save();
+        self.save()
     }
 
-    /// Save the todo list into the default JSON file name stored
-    pub fn save(&self) {
-        self.save_to_file(&self.file_name)
-            .expect("Failed to save todo list to the default file");
+    /// Replace the optional attributes of a task
+    pub fn set_options(&mut self, id: &str, options: TaskOptions) -> Result<(), TodoError> {
+        let task = self
+            .get_task_mut(id)
+            .ok_or_else(|| TodoError::NotFound(id.to_string()))?;
+        if task.options() != options {
+            task.updated_at = Some(timestamp::now());
+        }
+        task.due = options.due;
+        task.tags = options.tags;
+        task.parent = options.parent;
+        task.depends_on = options.depends_on;
+        task.recurrence = options.recurrence;
+        self.save()
     }
 
-    /// Save the todo list into a JSON file where the file name is passed as a parameter
-    pub fn save_to_file(&self, file_name: &str) -> Result<(), String> {
-        let json_data = serde_json::to_string(&self.tasks)
-            .map_err(|e| format!("Failed to serialize tasks: {}", e))?;
-
-        //The ? operator is used to propagate the posible serialization errors and directly return Err(e)
+    /// Add the next occurrence of a recurring task completed on the given date.
+    /// Returns the ID of the new task, or None if the task does not repeat.
+    pub fn spawn_next_occurrence(
+        &mut self,
+        id: &str,
+        completed_on: NaiveDate,
+    ) -> Result<Option<String>, TodoError> {
+        let task = self
+            .tasks
+            .iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| TodoError::NotFound(id.to_string()))?;
+        let Some(recurrence) = task.recurrence.clone() else {
+            return Ok(None);
+        };
+        let options = TaskOptions {
+            due: Some(recurrence.next_due(task.due, completed_on)),
+            tags: task.tags.clone(),
+            parent: task.parent.clone(),
+            recurrence: Some(recurrence),
+            ..Default::default()
+        };
+        self.add_task_with_options(task.description.clone(), task.priority, options)
+            .map(Some)
+    }
 
-        std::fs::write(file_name, json_data)
-            .map_err(|e| format!("Failed to write to the file {} Err: {}", file_name, e))?;
+    /// Return the direct subtasks of a task
+    pub fn children(&self, id: &str) -> Vec<&Task> {
+        self.tasks
+            .iter()
+            .filter(|t| t.parent.as_deref() == Some(id))
+            .collect()
+    }
 
-        Ok(())
+    /// Return the IDs of every task below the given one in the hierarchy, parents before children
+    pub fn descendants(&self, id: &str) -> Vec<String> {
+        let mut descendants = Vec::new();
+        let mut pending = vec![id.to_string()];
+        while let Some(current) = pending.pop() {
+            for child in self.children(&current) {
+                if child.id != id && !descendants.contains(&child.id) {
+                    descendants.push(child.id.clone());
+                    pending.push(child.id.clone());
+                }
+            }
+        }
+        descendants
     }
 
-    pub fn try_load(&mut self) -> Result<(), ()> {
-        if !Path::new(&self.file_name).exists() {
-            Err(())
+    /// Return the number of completed and total direct subtasks of a task, if it has any
+    pub fn progress(&self, id: &str) -> Option<(usize, usize)> {
+        let children = self.children(id);
+        if children.is_empty() {
+            None
         } else {
-            self.load();
-            Ok(())
+            let done = children.iter().filter(|t| t.completed).count();
+            Some((done, children.len()))
         }
     }
 
-    pub fn load(&mut self) {
-        let file_name = self.file_name.clone();
-        if let Err(_) = self.load_from_file(&file_name) {
-            self.save();
+    /// Mark every open subtask of a task as completed, returning their IDs
+    pub fn complete_descendants(&mut self, id: &str) -> Result<Vec<String>, TodoError> {
+        let mut completed = Vec::new();
+        for descendant in self.descendants(id) {
+            if let Some(task) = self.get_task_mut(&descendant)
+                && !task.completed
+            {
+                task.set_completed(true);
+                completed.push(descendant);
+            }
+        }
+        if !completed.is_empty() {
+            self.save()?;
         }
+        Ok(completed)
     }
 
-    //Load the todo list from a JSON file
-    pub fn load_from_file(&mut self, file_name: &str) -> Result<(), String> {
-        let data = std::fs::read_to_string(file_name)
-            .map_err(|e| format!("Failed to read the file '{}'. Err: {}", file_name, e))?;
+    /// Check that a task can depend on another one: both must exist and no cycle can be created
+    pub fn check_dependency(&self, id: &str, dependency: &str) -> Result<(), TodoError> {
+        if id == dependency {
+            return Err(TodoError::Validation(format!(
+                "Task with ID: {} cannot depend on itself",
+                id
+            )));
+        }
+        if !self.tasks.iter().any(|t| t.id == dependency) {
+            return Err(TodoError::NotFound(dependency.to_string()));
+        }
 
-        let deserialized_tasks: Vec<Task> = serde_json::from_str(&data)
-            .map_err(|e| format!("Failed to deserialize tasks: {}", e))?;
+        let mut visited = Vec::new();
+        let mut pending = vec![dependency.to_string()];
+        while let Some(current) = pending.pop() {
+            if current == id {
+                return Err(TodoError::Validation(format!(
+                    "Task with ID: {} cannot depend on {} because it would create a cycle",
+                    id, dependency
+                )));
+            }
+            if let Some(task) = self.tasks.iter().find(|t| t.id == current) {
+                pending.extend(
+                    task.depends_on
+                        .iter()
+                        .filter(|d| !visited.contains(*d))
+                        .cloned(),
+                );
+            }
+            visited.push(current);
+        }
+        Ok(())
+    }
 
-        self.tasks = deserialized_tasks;
-        self.next_id = self
-            .tasks
+    /// Make a task depend on another one, without any validation
+    pub fn add_dependency(&mut self, id: &str, dependency: &str) -> Result<(), TodoError> {
+        let task = self
+            .get_task_mut(id)
+            .ok_or_else(|| TodoError::NotFound(id.to_string()))?;
+        task.depends_on.insert(dependency.to_string());
+        self.save()
+    }
+
+    /// Return the pairs (dependent, dependency) of the tasks depending on any of the given IDs
+    pub fn dependents_of(&self, ids: &[String]) -> Vec<(String, String)> {
+        self.tasks
             .iter()
-            .map(|task| {
-                u32::from_str_radix(&task.id, 16)
-                .expect(&format!("Failed to parse task ID: {}", task.id))
+            .filter(|task| !ids.contains(&task.id))
+            .flat_map(|task| {
+                task.depends_on
+                    .iter()
+                    .filter(|dependency| ids.contains(dependency))
+                    .map(|dependency| (task.id.clone(), dependency.clone()))
             })
-            .max()
-            .unwrap_or(0)
-            + 1;
+            .collect()
+    }
+
+    /// Return the dependencies of a task that are still open
+    pub fn open_dependencies(&self, id: &str) -> Vec<&Task> {
+        match self.tasks.iter().find(|t| t.id == id) {
+            Some(task) => self
+                .tasks
+                .iter()
+                .filter(|t| !t.completed && task.depends_on.contains(&t.id))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// A task is blocked while any of its dependencies is still open
+    pub fn is_blocked(&self, id: &str) -> bool {
+        !self.open_dependencies(id).is_empty()
+    }
+
+    /// Order the given tasks as a tree, each one followed by its subtasks and paired with its depth.
+    /// Tasks whose parent is not among the given ones are shown as roots.
+    pub fn as_tree<'a>(tasks: &[&'a Task]) -> Vec<(usize, &'a Task)> {
+        fn visit<'a>(
+            task: &'a Task,
+            depth: usize,
+            tasks: &[&'a Task],
+            tree: &mut Vec<(usize, &'a Task)>,
+        ) {
+            if tree.iter().any(|(_, t)| t.id == task.id) {
+                return;
+            }
+            tree.push((depth, task));
+            for child in tasks
+                .iter()
+                .filter(|t| t.parent.as_deref() == Some(task.id.as_str()))
+            {
+                visit(child, depth + 1, tasks, tree);
+            }
+        }
+
+        let mut tree = Vec::new();
+        for task in tasks.iter().filter(|task| {
+            task.parent
+                .as_ref()
+                .is_none_or(|parent| !tasks.iter().any(|t| t.id == *parent))
+        }) {
+            visit(task, 0, tasks, &mut tree);
+        }
+        tree
+    }
+
+    /// Render a task for the listings, indented by its depth and with the progress of its subtasks
+    pub fn render_task(&self, task: &Task, depth: usize) -> String {
+        let indent = if depth == 0 {
+            String::new()
+        } else {
+            format!("{}└─ ", "   ".repeat(depth - 1))
+        };
+        let mut line = format!("{}{}", indent, task);
+        if let Some((done, total)) = self.progress(&task.id) {
+            line.push_str(&format!(", Progress: {}/{} done", done, total));
+        }
+        if !task.completed && self.is_blocked(&task.id) {
+            line.push_str(" (BLOCKED)");
+        }
+        line
+    }
+
+    /// Count the open and closed tasks of every tag in the list
+    pub fn tag_counts(&self) -> BTreeMap<String, (usize, usize)> {
+        let mut counts = BTreeMap::<String, (usize, usize)>::new();
+        for task in &self.tasks {
+            for tag in &task.tags {
+                let (open, closed) = counts.entry(tag.clone()).or_default();
+                if task.completed {
+                    *closed += 1;
+                } else {
+                    *open += 1;
+                }
+            }
+        }
+        counts
+    }
+
+    /// Save the todo list into its storage. Within a transaction, the todo list is only
+    /// saved when the transaction is committed.
+    pub fn save(&mut self) -> Result<(), TodoError> {
+        self.save_merged().map(|_| ())
+    }
+
+    /// Start grouping changes: they are saved at once by `commit_transaction`, or reverted by
+    /// `rollback_transaction`. The storage stays locked until the outermost transaction ends,
+    /// so that no other ToDo saves in between. Transactions can be nested.
+    pub fn begin_transaction(&mut self) -> Result<(), TodoError> {
+        if self.transactions.is_empty() {
+            let lock = self.storage.lock()?;
+            // The changes saved by another ToDo since our last save are taken in first
+            self.merge_stored()?;
+            self.transaction_lock = lock;
+        }
+        self.transactions.push(Snapshot {
+            tasks: self.tasks.clone(),
+            next_id: self.next_id,
+            archive_changes: self.archive_changes.clone(),
+        });
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        !self.transactions.is_empty()
+    }
+
+    /// End the innermost transaction, saving the changes when it is the outermost one.
+    /// When the save fails, the changes of the transaction are reverted.
+    pub fn commit_transaction(&mut self) -> Result<(), TodoError> {
+        let Some(snapshot) = self.transactions.pop() else {
+            return Err(TodoError::Validation(
+                "No transaction to commit".to_string(),
+            ));
+        };
+        if !self.transactions.is_empty() {
+            return Ok(());
+        }
+        let result = self.save();
+        if result.is_err() {
+            self.restore_snapshot(snapshot);
+        }
+        self.transaction_lock = None;
+        result
+    }
+
+    /// End the innermost transaction, reverting its changes
+    pub fn rollback_transaction(&mut self) {
+        if let Some(snapshot) = self.transactions.pop() {
+            self.restore_snapshot(snapshot);
+        }
+        if self.transactions.is_empty() {
+            self.transaction_lock = None;
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        self.tasks = snapshot.tasks;
+        self.next_id = snapshot.next_id;
+        self.archive_changes = snapshot.archive_changes;
+    }
+
+    /// Save the todo list while holding the lock of its storage. When the stored tasks
+    /// changed since they were loaded, e.g. by another ToDo running at the same time,
+    /// they are read again and merged with ours first (see `merge_tasks`).
+    /// Returns the new IDs of the added tasks whose ID was taken in the meantime.
+    fn save_merged(&mut self) -> Result<BTreeMap<String, String>, TodoError> {
+        if !self.transactions.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+        // The lock is already held at the end of a transaction
+        let _lock = match self.transaction_lock {
+            Some(_) => None,
+            None => self.storage.lock()?,
+        };
+        let renamed = self.merge_stored()?;
+        // The archive is saved first, so that a failure leaves the moved tasks in both
+        // places rather than in none
+        if !self.archive_changes.is_empty() {
+            let mut archive = self.storage.load_archive()?;
+            self.archive_changes.apply(&mut archive);
+            self.storage.save_archive(&archive)?;
+            self.archive_changes = ArchiveChanges::default();
+        }
+        let data = self.data();
+        // The stored tasks are the ones of the storage, read again by `merge_stored` when
+        // another ToDo saved since
+        match &self.stored {
+            Some(stored) => self.storage.save_changes(&data, stored)?,
+            None => self.storage.save(&data)?,
+        }
+        self.created_at = data.created_at;
+        self.stored = Some(data.tasks);
+        Ok(renamed)
+    }
+
+    /// Merge the tasks saved by another ToDo since they were loaded into ours.
+    /// Returns the new IDs of our added tasks whose ID was taken.
+    fn merge_stored(&mut self) -> Result<BTreeMap<String, String>, TodoError> {
+        let mut renamed = BTreeMap::new();
+        if let Some(stored) = &self.stored
+            && self.storage.exists()
+        {
+            let current = self.storage.load()?;
+            if current.tasks != *stored {
+                let merge = merge_tasks(stored, &self.tasks, &current.tasks);
+                renamed = merge.renamed;
+                self.set_loaded_data(TodoData {
+                    next_id: self.next_id.max(current.next_id),
+                    created_at: current.created_at.or(self.created_at),
+                    updated_at: current.updated_at,
+                    tasks: merge.tasks,
+                })?;
+                self.stored = Some(current.tasks);
+            }
+        }
+        Ok(renamed)
+    }
+
+    /// Tasks and metadata to save, updated now
+    fn data(&self) -> TodoData {
+        let now = timestamp::now();
+        TodoData {
+            next_id: self.next_id,
+            created_at: self.created_at.or(Some(now)),
+            updated_at: Some(now),
+            tasks: self.tasks.clone(),
+        }
+    }
+
+    /// Save the todo list into a JSON file where the file name is passed as a parameter
+    pub fn save_to_file(&self, file_name: &str) -> Result<(), TodoError> {
+        JsonFileStorage::new(file_name).save(&self.data())
+    }
+
+    /// Load the todo list from its storage, first upgrading it in place when it was
+    /// saved with an older schema version.
+    /// Returns Ok(false) when nothing has been stored yet.
+    pub fn try_load(&mut self) -> Result<bool, TodoError> {
+        if !self.storage.exists() {
+            // Tasks saved by another ToDo before ours are merged with them
+            self.stored = Some(Vec::new());
+            Ok(false)
+        } else {
+            self.load()?;
+            Ok(true)
+        }
+    }
+
+    pub fn load(&mut self) -> Result<(), TodoError> {
+        self.storage.migrate(false)?;
+        let data = self.storage.load()?;
+        let tasks = data.tasks.clone();
+        self.set_loaded_data(data)?;
+        self.stored = Some(tasks);
+        Ok(())
+    }
+
+    //Load the todo list from a JSON file, upgrading it in place when it was saved
+    //with an older schema version
+    pub fn load_from_file(&mut self, file_name: &str) -> Result<(), TodoError> {
+        let mut storage = JsonFileStorage::new(file_name);
+        storage.migrate(false)?;
+        let data = storage.load()?;
+        self.set_loaded_data(data)
+    }
+
+    /// Replace the tasks by loaded ones. The next ID is the saved one, unless a task
+    /// already has it.
+    fn set_loaded_data(&mut self, data: TodoData) -> Result<(), TodoError> {
+        let mut max_id = 0;
+        for task in &data.tasks {
+            let id = u32::from_str_radix(&task.id, 16)
+                .map_err(|_| TodoError::Parse(format!("Failed to parse task ID: {}", task.id)))?;
+            max_id = max_id.max(id);
+        }
+        self.tasks = data.tasks;
+        self.next_id = data.next_id.max(max_id + 1);
+        self.created_at = data.created_at;
         Ok(())
     }
 }
@@ -1,27 +1,163 @@
+use crate::error::TodoError;
 use crate::model::priority::Priority;
-use crate::model::task::Task;
+use crate::model::task::{Task, TaskOptions};
 use crate::service::manager::{Manager, ManagerTrait};
+use chrono::Local;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
 /// UndoData enum represents the data needed to undo actions in the todo list.
-#[derive(Debug, Clone)]
+/// It is saved with the history, so that actions can be undone in later sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UndoRedoData {
-    AddTask { task: Task },
-    CompleteTask { id: String, previous_state: bool },
-    RemoveTask { task: Task },
-    EditTask { previous_task: Task },
+    AddTask {
+        task: Task,
+    },
+    CompleteTask {
+        id: String,
+        previous_state: bool,
+        completed_subtasks: Vec<String>,
+        next_occurrence: Option<Task>,
+    },
+    RemoveTask {
+        task: Task,
+        subtasks: Vec<Task>,
+        dependents: Vec<(String, String)>,
+    },
+    EditTask {
+        previous_task: Task,
+    },
+    /// Tasks moved to the archive, subtasks included, and the dropped dependencies on them
+    ArchiveTasks {
+        tasks: Vec<Task>,
+        dependents: Vec<(String, String)>,
+    },
+    /// Tasks taken out of the archive, as they were in it
+    UnarchiveTasks {
+        tasks: Vec<Task>,
+    },
+    /// Tasks added from another format, with the IDs they were given
+    ImportTasks {
+        tasks: Vec<Task>,
+    },
+    /// Undo data of each action of a transaction, in the order they were executed
+    Transaction {
+        undo_data: Vec<UndoRedoData>,
+    },
+}
+
+impl UndoRedoData {
+    /// ID of the task changed by the action, comma separated IDs for the actions
+    /// changing several tasks
+    pub fn task_id(&self) -> String {
+        let ids = |tasks: &[Task]| {
+            tasks
+                .iter()
+                .map(|task| task.id.clone())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            UndoRedoData::AddTask { task } => task.id.clone(),
+            UndoRedoData::CompleteTask { id, .. } => id.clone(),
+            UndoRedoData::RemoveTask { task, .. } => task.id.clone(),
+            UndoRedoData::EditTask { previous_task } => previous_task.id.clone(),
+            UndoRedoData::ArchiveTasks { tasks, .. }
+            | UndoRedoData::UnarchiveTasks { tasks }
+            | UndoRedoData::ImportTasks { tasks } => ids(tasks),
+            UndoRedoData::Transaction { undo_data } => undo_data
+                .iter()
+                .map(UndoRedoData::task_id)
+                .collect::<Vec<_>>()
+                .join(","),
+        }
+    }
+
+    /// IDs of every task changed by the action or referred to by the tasks it changed,
+    /// e.g. the parent of an added subtask
+    pub fn task_ids(&self) -> BTreeSet<String> {
+        let mut ids = BTreeSet::new();
+        let mut add_task = |task: &Task| {
+            ids.insert(task.id.clone());
+            ids.extend(task.parent.iter().cloned());
+            ids.extend(task.depends_on.iter().cloned());
+        };
+        match self {
+            UndoRedoData::AddTask { task }
+            | UndoRedoData::EditTask {
+                previous_task: task,
+            } => add_task(task),
+            UndoRedoData::CompleteTask {
+                id,
+                completed_subtasks,
+                next_occurrence,
+                ..
+            } => {
+                if let Some(next_occurrence) = next_occurrence {
+                    add_task(next_occurrence);
+                }
+                ids.insert(id.clone());
+                ids.extend(completed_subtasks.iter().cloned());
+            }
+            UndoRedoData::RemoveTask {
+                task,
+                subtasks,
+                dependents,
+            } => {
+                add_task(task);
+                subtasks.iter().for_each(&mut add_task);
+                ids.extend(dependents.iter().map(|(dependent, _)| dependent.clone()));
+            }
+            UndoRedoData::ArchiveTasks { tasks, dependents } => {
+                tasks.iter().for_each(&mut add_task);
+                ids.extend(dependents.iter().map(|(dependent, _)| dependent.clone()));
+            }
+            UndoRedoData::UnarchiveTasks { tasks } | UndoRedoData::ImportTasks { tasks } => {
+                tasks.iter().for_each(&mut add_task)
+            }
+            UndoRedoData::Transaction { undo_data } => {
+                ids.extend(undo_data.iter().flat_map(UndoRedoData::task_ids));
+            }
+        }
+        ids
+    }
+}
+
+impl Command {
+    /// Name of the subcommand performing the action. A transaction has the name of its actions
+    /// when they are all alike, `transaction` otherwise.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::AddTask { .. } => "add",
+            Command::RemoveTask { .. } => "remove",
+            Command::CompleteTask { .. } => "toggle-status",
+            Command::EditTask { .. } => "edit",
+            Command::ArchiveTasks { .. } => "archive",
+            Command::UnarchiveTasks { .. } => "unarchive",
+            Command::ImportTasks { .. } => "import",
+            Command::Transaction { commands } => {
+                let mut names = commands.iter().map(Command::name);
+                match names.next() {
+                    Some(name) if names.all(|other| other == name) => name,
+                    _ => "transaction",
+                }
+            }
+        }
+    }
 }
 
 /// Trait for actions that can be performed on the todo list.
 pub trait ActionTrait {
-    fn execute(&mut self, manager: &mut Manager) -> UndoRedoData;
+    fn execute(&mut self, manager: &mut Manager) -> Result<UndoRedoData, TodoError>;
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Command {
     AddTask {
         description: String,
         priority: Priority,
+        #[serde(default)]
+        options: TaskOptions,
     },
     RemoveTask {
         task: Task,
@@ -32,59 +168,123 @@ pub enum Command {
     EditTask {
         id: String,
         new_fields: (String, Priority),
+        #[serde(default)]
+        options: TaskOptions,
+    },
+    /// Move tasks to the archive with their subtasks
+    ArchiveTasks {
+        ids: Vec<String>,
+    },
+    /// Move archived tasks back to the todo list with their archived subtasks
+    UnarchiveTasks {
+        ids: Vec<String>,
+    },
+    /// Add tasks read from another format, see `TodoList::import_tasks`
+    ImportTasks {
+        tasks: Vec<Task>,
+    },
+    /// Actions executed, undone and redone as a whole, the todo list being saved once
+    /// at the end. When one of them fails, none is kept.
+    Transaction {
+        commands: Vec<Command>,
     },
 }
 
 impl ActionTrait for Command {
-    fn execute(&mut self, manager: &mut Manager) -> UndoRedoData {
+    fn execute(&mut self, manager: &mut Manager) -> Result<UndoRedoData, TodoError> {
         match self {
             Command::AddTask {
                 description,
                 priority,
+                options,
             } => {
-                let id = manager.todo_list.add_task(description.clone(), *priority);
-                UndoRedoData::AddTask {
-                    task: Task {
-                        id: id,
-                        description: description.clone(),
-                        priority: *priority,
-                        completed: false,
-                    },
-                }
+                let id = manager.todo_list.add_task_with_options(
+                    description.clone(),
+                    *priority,
+                    options.clone(),
+                )?;
+                let task = manager.get_task(&id).ok_or(TodoError::NotFound(id))?;
+                Ok(UndoRedoData::AddTask { task: task.clone() })
             }
             Command::RemoveTask { task } => {
-                manager.todo_list.remove_task(task.id.clone());
-                UndoRedoData::RemoveTask { task: task.clone() }
+                let mut ids = vec![task.id.clone()];
+                ids.extend(manager.todo_list.descendants(&task.id));
+                let dependents = manager.todo_list.dependents_of(&ids);
+
+                let mut removed = manager.todo_list.remove_task(task.id.clone())?;
+                let task = removed.remove(0);
+                Ok(UndoRedoData::RemoveTask {
+                    task,
+                    subtasks: removed,
+                    dependents,
+                })
             }
             Command::CompleteTask { id } => {
                 let previous_state = manager
-                    .todo_list
-                    .tasks
-                    .iter()
-                    .find(|task| task.id == *id)
-                    .map_or(false, |task| task.completed);
-                manager.todo_list.toggle_task_status(id.clone());
-                UndoRedoData::CompleteTask {
+                    .get_task(id)
+                    .ok_or_else(|| TodoError::NotFound(id.clone()))?
+                    .completed;
+                manager.todo_list.toggle_task_status(id.clone())?;
+                // Completing a parent task also completes all its open subtasks,
+                // and completing a recurring task adds its next occurrence
+                let (completed_subtasks, next_occurrence) = if previous_state {
+                    (Vec::new(), None)
+                } else {
+                    let completed_subtasks = manager.todo_list.complete_descendants(id)?;
+                    let next_occurrence = manager
+                        .todo_list
+                        .spawn_next_occurrence(id, Local::now().date_naive())?
+                        .and_then(|next_id| manager.get_task(&next_id).cloned());
+                    (completed_subtasks, next_occurrence)
+                };
+                Ok(UndoRedoData::CompleteTask {
                     id: id.clone(),
                     previous_state,
-                }
+                    completed_subtasks,
+                    next_occurrence,
+                })
             }
-            Command::EditTask { id, new_fields } => {
+            Command::EditTask {
+                id,
+                new_fields,
+                options,
+            } => {
                 let undo_data = UndoRedoData::EditTask {
                     previous_task: manager
                         .get_task(id)
-                        .expect(&format!(
-                            "IllegalState Error: Task not found for ID: {}",
-                            id
-                        ))
+                        .ok_or_else(|| TodoError::NotFound(id.clone()))?
                         .clone(),
                 };
 
                 manager
                     .todo_list
-                    .edit_task(id.as_ref(), (new_fields.0.as_ref(), &new_fields.1));
+                    .edit_task(id.as_ref(), (new_fields.0.as_ref(), &new_fields.1))?;
+                manager
+                    .todo_list
+                    .set_options(id.as_ref(), options.clone())?;
 
-                undo_data
+                Ok(undo_data)
+            }
+            Command::ArchiveTasks { ids } => {
+                let (tasks, dependents) = manager.todo_list.archive_tasks(ids)?;
+                Ok(UndoRedoData::ArchiveTasks { tasks, dependents })
+            }
+            Command::UnarchiveTasks { ids } => {
+                let tasks = manager.todo_list.unarchive_tasks(ids)?;
+                Ok(UndoRedoData::UnarchiveTasks { tasks })
+            }
+            Command::ImportTasks { tasks } => {
+                let tasks = manager.todo_list.import_tasks(tasks.clone())?;
+                Ok(UndoRedoData::ImportTasks { tasks })
+            }
+            Command::Transaction { commands } => {
+                let undo_data = manager.transaction(|manager| {
+                    commands
+                        .iter_mut()
+                        .map(|command| command.execute(manager))
+                        .collect::<Result<Vec<_>, _>>()
+                })?;
+                Ok(UndoRedoData::Transaction { undo_data })
             }
         }
     }
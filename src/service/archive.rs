@@ -0,0 +1,54 @@
+use crate::error::TodoError;
+use crate::model::task::Task;
+use crate::model::todo_list::TodoList;
+use chrono::NaiveDateTime;
+
+/// Environment variable moving the tasks to the archive as soon as they are completed when `true`
+pub const TODO_ARCHIVE_ON_COMPLETE_VARIABLE: &str = "TODO_ARCHIVE_ON_COMPLETE";
+
+/// Whether `TODO_ARCHIVE_ON_COMPLETE` asks to archive the tasks when they are completed
+pub fn archive_on_complete() -> Result<bool, TodoError> {
+    match std::env::var(TODO_ARCHIVE_ON_COMPLETE_VARIABLE) {
+        Ok(text) => match text.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "" | "0" | "false" | "no" | "off" => Ok(false),
+            _ => Err(TodoError::Validation(format!(
+                "Invalid {} value '{}', expected true or false",
+                TODO_ARCHIVE_ON_COMPLETE_VARIABLE, text
+            ))),
+        },
+        Err(_) => Ok(false),
+    }
+}
+
+/// IDs of the tasks that can be archived, in the order of the list: the completed ones,
+/// only those completed before the given time when there is one. A task is only archived
+/// along with all its subtasks, so one having a subtask to keep is kept as well.
+/// The tasks completed by older versions, without a completion time, are never old enough.
+pub fn archivable_tasks(
+    todo_list: &TodoList,
+    completed_before: Option<NaiveDateTime>,
+) -> Vec<String> {
+    let old_enough = |task: &Task| {
+        task.completed
+            && completed_before.is_none_or(|before| {
+                task.completed_at
+                    .is_some_and(|completed| completed < before)
+            })
+    };
+    todo_list
+        .get_tasks()
+        .iter()
+        .filter(|task| old_enough(task))
+        .filter(|task| {
+            todo_list.descendants(&task.id).iter().all(|id| {
+                todo_list
+                    .get_tasks()
+                    .iter()
+                    .find(|t| t.id == *id)
+                    .is_some_and(old_enough)
+            })
+        })
+        .map(|task| task.id.clone())
+        .collect()
+}
@@ -0,0 +1,271 @@
+use crate::error::TodoError;
+use crate::model::priority::Priority;
+use crate::model::task::{Task, TaskOptions};
+use crate::model::timestamp;
+use crate::service::actions::{Command, UndoRedoData};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Environment variable giving the number of actions kept in the undo history
+pub const TODO_HISTORY_LIMIT_VARIABLE: &str = "TODO_HISTORY_LIMIT";
+
+/// Number of actions kept in the undo history when `TODO_HISTORY_LIMIT` is not set
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Version of the saved history, to be able to change its format later on
+const HISTORY_VERSION: u32 = 1;
+
+/// Actions of the undo or redo stack, the most recent last
+pub type Stack = Vec<HistoryEntry>;
+
+/// Undo and redo stacks as saved alongside the todo list, the most recent actions last
+#[derive(Debug, Serialize, Deserialize)]
+pub struct History {
+    pub version: u32,
+    pub undo: Vec<HistoryEntry>,
+    pub redo: Vec<HistoryEntry>,
+}
+
+/// Action of the history with the data needed to undo and redo it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub command: Command,
+    pub undo_data: UndoRedoData,
+    /// When the action was performed, or last redone. Unknown for the actions saved by older versions.
+    #[serde(default)]
+    pub performed_at: Option<NaiveDateTime>,
+}
+
+impl HistoryEntry {
+    /// Action performed now
+    pub fn new(command: Command, undo_data: UndoRedoData) -> Self {
+        HistoryEntry {
+            command,
+            undo_data,
+            performed_at: Some(timestamp::now()),
+        }
+    }
+}
+
+/// Human readable description of the action, e.g. `Add task 3 "Buy milk" (High)`
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids = |tasks: &[String]| tasks.join(", ");
+        match (&self.command, &self.undo_data) {
+            (_, UndoRedoData::AddTask { task }) => {
+                write!(
+                    f,
+                    "Add task {} \"{}\" ({})",
+                    task.id, task.description, task.priority
+                )
+            }
+            (
+                _,
+                UndoRedoData::CompleteTask {
+                    id,
+                    previous_state: true,
+                    ..
+                },
+            ) => write!(f, "Reopen task {}", id),
+            (
+                _,
+                UndoRedoData::CompleteTask {
+                    id,
+                    completed_subtasks,
+                    next_occurrence,
+                    ..
+                },
+            ) => {
+                write!(f, "Complete task {}", id)?;
+                if !completed_subtasks.is_empty() {
+                    write!(f, " and its subtasks {}", ids(completed_subtasks))?;
+                }
+                if let Some(next_occurrence) = next_occurrence {
+                    write!(f, ", adding its next occurrence {}", next_occurrence.id)?;
+                }
+                Ok(())
+            }
+            (_, UndoRedoData::RemoveTask { task, subtasks, .. }) => {
+                write!(f, "Remove task {} \"{}\"", task.id, task.description)?;
+                if !subtasks.is_empty() {
+                    let subtasks = subtasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+                    write!(f, " and its subtasks {}", ids(&subtasks))?;
+                }
+                Ok(())
+            }
+            (
+                Command::EditTask {
+                    new_fields,
+                    options,
+                    ..
+                },
+                UndoRedoData::EditTask { previous_task },
+            ) => {
+                write!(f, "Edit task {}", previous_task.id)?;
+                let changes = edit_changes(previous_task, &new_fields.0, options, new_fields.1);
+                if changes.is_empty() {
+                    write!(f, " without changes")
+                } else {
+                    write!(f, ": {}", changes.join(", "))
+                }
+            }
+            (_, UndoRedoData::ArchiveTasks { tasks, .. }) => write_tasks(f, "Archive", tasks),
+            (_, UndoRedoData::UnarchiveTasks { tasks }) => write_tasks(f, "Unarchive", tasks),
+            (_, UndoRedoData::ImportTasks { tasks }) => write_tasks(f, "Import", tasks),
+            (Command::Transaction { commands }, UndoRedoData::Transaction { undo_data }) => {
+                let actions = commands
+                    .iter()
+                    .zip(undo_data)
+                    .map(|(command, undo_data)| {
+                        HistoryEntry {
+                            command: command.clone(),
+                            undo_data: undo_data.clone(),
+                            performed_at: None,
+                        }
+                        .to_string()
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "{} actions: {}", actions.len(), actions.join("; "))
+            }
+            (command, undo_data) => write!(f, "{} task {}", command.name(), undo_data.task_id()),
+        }
+    }
+}
+
+/// Action on several tasks, e.g. `Archive task 3 "Buy milk"` or `Archive tasks 3, 4`
+fn write_tasks(f: &mut fmt::Formatter<'_>, verb: &str, tasks: &[Task]) -> fmt::Result {
+    match tasks {
+        [task] => write!(f, "{} task {} \"{}\"", verb, task.id, task.description),
+        _ => {
+            let ids = tasks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+            write!(f, "{} tasks {}", verb, ids.join(", "))
+        }
+    }
+}
+
+/// Line of the history listing, e.g. `1: 2026-10-18 09:30 Add task 3 "Buy milk" (High)`
+pub fn history_line(position: usize, entry: &HistoryEntry) -> String {
+    match entry.performed_at {
+        Some(performed_at) => format!(
+            "{}: {} {}",
+            position,
+            timestamp::format_timestamp(performed_at),
+            entry
+        ),
+        None => format!("{}: {}", position, entry),
+    }
+}
+
+/// Changed fields of an edited task, with the previous and new values of the main ones
+fn edit_changes(
+    previous: &Task,
+    description: &str,
+    options: &TaskOptions,
+    priority: Priority,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    if previous.description != description {
+        changes.push(format!(
+            "description \"{}\" -> \"{}\"",
+            previous.description, description
+        ));
+    }
+    if previous.priority != priority {
+        changes.push(format!("priority {} -> {}", previous.priority, priority));
+    }
+    let previous_options = previous.options();
+    let fields = [
+        ("due date", previous_options.due != options.due),
+        ("tags", previous_options.tags != options.tags),
+        ("parent", previous_options.parent != options.parent),
+        (
+            "dependencies",
+            previous_options.depends_on != options.depends_on,
+        ),
+        (
+            "recurrence",
+            previous_options.recurrence != options.recurrence,
+        ),
+    ];
+    changes.extend(
+        fields
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(field, _)| field.to_string()),
+    );
+    changes
+}
+
+impl History {
+    pub fn new(undo: &[HistoryEntry], redo: &[HistoryEntry]) -> Self {
+        History {
+            version: HISTORY_VERSION,
+            undo: undo.to_vec(),
+            redo: redo.to_vec(),
+        }
+    }
+
+    pub fn from_value(value: Value) -> Result<Self, TodoError> {
+        let history: History = serde_json::from_value(value)
+            .map_err(|e| TodoError::Parse(format!("Failed to deserialize the history: {}", e)))?;
+        if history.version > HISTORY_VERSION {
+            return Err(TodoError::Parse(format!(
+                "The history was saved by a newer version of ToDo (version {})",
+                history.version
+            )));
+        }
+        Ok(history)
+    }
+
+    pub fn to_value(&self) -> Result<Value, TodoError> {
+        serde_json::to_value(self).map_err(|e| {
+            TodoError::Serialization(format!("Failed to serialize the history: {}", e))
+        })
+    }
+
+    /// The undo and redo stacks
+    pub fn into_stacks(self) -> (Stack, Stack) {
+        (self.undo, self.redo)
+    }
+}
+
+/// Undo and redo stacks to save when another ToDo may have saved its own history since
+/// `loaded`, the undo stack as we read or last saved it. The actions we undid are taken
+/// out of the `stored` undo stack and the ones we performed are appended to it, so that
+/// the actions of both ToDos are kept. The redo stacks cannot be merged: ours is kept,
+/// or the stored one when we neither performed nor undid any action.
+pub fn merge_history(
+    stored: (Stack, Stack),
+    loaded: &[HistoryEntry],
+    undo: &[HistoryEntry],
+    redo: &[HistoryEntry],
+) -> (Stack, Stack) {
+    let (stored_undo, stored_redo) = stored;
+    if stored_undo == loaded {
+        return (undo.to_vec(), redo.to_vec());
+    }
+    let redo = if undo == loaded {
+        stored_redo
+    } else {
+        redo.to_vec()
+    };
+    let mut merged = stored_undo
+        .into_iter()
+        .filter(|entry| !loaded.contains(entry) || undo.contains(entry))
+        .collect::<Stack>();
+    merged.extend(undo.iter().filter(|entry| !loaded.contains(entry)).cloned());
+    (merged, redo)
+}
+
+/// Number of actions kept in the undo history set by `TODO_HISTORY_LIMIT`, or the default one
+pub fn history_limit() -> Result<usize, TodoError> {
+    match std::env::var(TODO_HISTORY_LIMIT_VARIABLE) {
+        Ok(text) => text
+            .trim()
+            .parse()
+            .map_err(|_| TodoError::Validation(format!("Invalid history limit: {}", text))),
+        Err(_) => Ok(DEFAULT_HISTORY_LIMIT),
+    }
+}
@@ -0,0 +1,250 @@
+use crate::error::TodoError;
+use crate::model::timestamp;
+use crate::storage::backend::{Backend, DEFAULT_LOCK_TIMEOUT, TodoData};
+use crate::storage::file::{archive_path, backup_path, backup_paths, history_path, lock_path};
+use std::path::{Path, PathBuf};
+
+/// Name of the list used when none is selected
+pub const DEFAULT_LIST: &str = "default";
+
+/// Environment variable giving the file of the todo list to use
+pub const TODO_FILE_VARIABLE: &str = "TODO_FILE";
+
+/// Directory holding the named todo lists, one file per list
+pub struct ListDirectory {
+    pub directory: PathBuf,
+    pub backend: Backend,
+}
+
+impl ListDirectory {
+    /// Lists stored as JSON files in the given directory
+    pub fn new(directory: PathBuf) -> Self {
+        ListDirectory::with_backend(directory, Backend::JsonFile)
+    }
+
+    pub fn with_backend(directory: PathBuf, backend: Backend) -> Self {
+        ListDirectory { directory, backend }
+    }
+
+    /// Lists stored in the data directory of the user (e.g. `~/.local/share/todo`,
+    /// or `$XDG_DATA_HOME/todo` when the variable is set), with the backend set by
+    /// `TODO_BACKEND`
+    pub fn in_data_dir() -> Result<Self, TodoError> {
+        let backend = Backend::from_env()?.unwrap_or(Backend::JsonFile);
+        dirs::data_dir()
+            .map(|directory| ListDirectory::with_backend(directory.join("todo"), backend))
+            .ok_or_else(|| {
+                TodoError::Io(
+                    "Could not find the data directory, please use --file or TODO_FILE".to_string(),
+                )
+            })
+    }
+
+    /// File of the list with the given name, whether it exists or not
+    pub fn path(&self, name: &str) -> Result<PathBuf, TodoError> {
+        check_name(name)?;
+        Ok(self
+            .directory
+            .join(format!("{}.{}", name, self.backend.extension())))
+    }
+
+    /// File of a list that must already exist. The default list is the exception,
+    /// it is created on its first save as the todo list always was.
+    pub fn existing_path(&self, name: &str) -> Result<PathBuf, TodoError> {
+        let path = self.path(name)?;
+        if name != DEFAULT_LIST && !path.exists() {
+            return Err(TodoError::ListNotFound(name.to_string()));
+        }
+        Ok(path)
+    }
+
+    /// Names of the stored lists, sorted alphabetically
+    pub fn names(&self) -> Result<Vec<String>, TodoError> {
+        if !self.directory.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = std::fs::read_dir(&self.directory).map_err(|e| {
+            TodoError::Io(format!(
+                "Failed to read the directory '{}'. Err: {}",
+                self.directory.display(),
+                e
+            ))
+        })?;
+        let mut names = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == self.backend.extension())
+            })
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .filter(|name| check_name(name).is_ok())
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Number of tasks of a stored list
+    pub fn task_count(&self, name: &str) -> Result<usize, TodoError> {
+        let storage = self.backend.open(&self.existing_path(name)?);
+        if !storage.exists() {
+            return Ok(0);
+        }
+        Ok(storage.load()?.tasks.len())
+    }
+
+    /// Create a new empty list
+    pub fn create(&self, name: &str) -> Result<PathBuf, TodoError> {
+        let path = self.path(name)?;
+        if path.exists() {
+            return Err(TodoError::Validation(format!(
+                "List '{}' already exists",
+                name
+            )));
+        }
+        let now = timestamp::now();
+        self.backend.open(&path).save(&TodoData {
+            created_at: Some(now),
+            updated_at: Some(now),
+            ..Default::default()
+        })?;
+        Ok(path)
+    }
+
+    pub fn rename(&self, name: &str, new_name: &str) -> Result<PathBuf, TodoError> {
+        let path = self.stored_path(name)?;
+        let new_path = self.path(new_name)?;
+        if new_path.exists() {
+            return Err(TodoError::Validation(format!(
+                "List '{}' already exists",
+                new_name
+            )));
+        }
+        // The backups and the history and archive follow their list. The list itself is
+        // moved last, so that it keeps its name until all its files are moved.
+        let mut moves = backup_paths(&path)
+            .into_iter()
+            .map(|(number, backup)| (backup, backup_path(&new_path, number)))
+            .collect::<Vec<_>>();
+        for sidecar in [history_path, archive_path] {
+            if sidecar(&path).exists() {
+                moves.push((sidecar(&path), sidecar(&new_path)));
+            }
+        }
+        moves.push((path.clone(), new_path.clone()));
+
+        // No other ToDo may save the list while its files are moved
+        let lock = self
+            .backend
+            .open_with(&path, 0, DEFAULT_LOCK_TIMEOUT)
+            .lock()?;
+        for (done, (from, to)) in moves.iter().enumerate() {
+            if let Err(e) = std::fs::rename(from, to) {
+                // The files already moved go back, not to leave the list half renamed
+                for (from, to) in moves[..done].iter().rev() {
+                    let _ = std::fs::rename(to, from);
+                }
+                return Err(TodoError::Io(format!(
+                    "Failed to rename the file '{}'. Err: {}",
+                    from.display(),
+                    e
+                )));
+            }
+        }
+        drop(lock);
+        let lock_file = lock_path(&path);
+        if lock_file.exists() {
+            std::fs::remove_file(&lock_file).map_err(|e| {
+                TodoError::Io(format!(
+                    "Failed to delete the file '{}'. Err: {}",
+                    lock_file.display(),
+                    e
+                ))
+            })?;
+        }
+
+        // The list name saved with the tasks is updated, unless the list cannot be read
+        let mut storage = self.backend.open_with(&new_path, 0, DEFAULT_LOCK_TIMEOUT);
+        if let Ok(data) = storage.load() {
+            storage.save(&data)?;
+        }
+        Ok(new_path)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), TodoError> {
+        let path = self.stored_path(name)?;
+        let remove = |path: &Path| {
+            std::fs::remove_file(path).map_err(|e| {
+                TodoError::Io(format!(
+                    "Failed to delete the file '{}'. Err: {}",
+                    path.display(),
+                    e
+                ))
+            })
+        };
+        // No other ToDo may save the list while its files are deleted
+        let lock = self
+            .backend
+            .open_with(&path, 0, DEFAULT_LOCK_TIMEOUT)
+            .lock()?;
+        remove(&path)?;
+        // A list created again with the same name must not inherit the undo history,
+        // the archived tasks nor the backups
+        for sidecar in [history_path(&path), archive_path(&path)] {
+            if sidecar.exists() {
+                remove(&sidecar)?;
+            }
+        }
+        for (_, backup) in backup_paths(&path) {
+            remove(&backup)?;
+        }
+        drop(lock);
+        let lock_file = lock_path(&path);
+        if lock_file.exists() {
+            remove(&lock_file)?;
+        }
+        Ok(())
+    }
+
+    /// File of a list that has been saved at least once
+    fn stored_path(&self, name: &str) -> Result<PathBuf, TodoError> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(TodoError::ListNotFound(name.to_string()));
+        }
+        Ok(path)
+    }
+}
+
+/// Choose the file of the todo list to use: the `--file` argument first, then the
+/// `--list` argument, the `TODO_FILE` environment variable and finally the default list
+pub fn resolve_file(
+    file: Option<&Path>,
+    list: Option<&str>,
+    env_file: Option<PathBuf>,
+) -> Result<PathBuf, TodoError> {
+    if let Some(file) = file {
+        return Ok(file.to_path_buf());
+    }
+    if list.is_none()
+        && let Some(env_file) = env_file
+    {
+        return Ok(env_file);
+    }
+    ListDirectory::in_data_dir()?.existing_path(list.unwrap_or(DEFAULT_LIST))
+}
+
+/// List names are used as file names, so they are limited to letters, digits, `-` and `_`
+fn check_name(name: &str) -> Result<(), TodoError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(TodoError::Validation(format!(
+            "Invalid list name '{}': only letters, digits, '-' and '_' are allowed",
+            name
+        )))
+    }
+}
@@ -1,28 +1,344 @@
+use crate::error::TodoError;
 use crate::model::priority::Priority;
-use crate::model::task::Task;
+use crate::model::tags::extract_tags;
+use crate::model::task::{Task, TaskOptions};
 use crate::model::todo_list::TodoList;
 use crate::service::actions::{ActionTrait, Command, UndoRedoData};
+use crate::service::history::{DEFAULT_HISTORY_LIMIT, History, HistoryEntry, Stack, merge_history};
 use crate::ui::displayer::Displayer;
 
 pub struct Manager {
     pub todo_list: TodoList,
-    pub undo_stack: Vec<(Command, UndoRedoData)>,
-    pub redo_stack: Vec<(Command, UndoRedoData)>,
+    pub undo_stack: Stack,
+    pub redo_stack: Stack,
+    /// Maximum number of actions kept in the undo stack, the oldest ones being dropped
+    pub history_limit: usize,
+    /// Move the tasks to the archive as soon as they are completed
+    pub archive_on_complete: bool,
     pub displayer: Option<Box<dyn Displayer>>,
+    /// The history changed within a transaction, it is saved when the transaction ends
+    history_unsaved: bool,
+    /// Undo stack as last read from or written to the storage, to tell our actions apart
+    /// from the ones saved by another ToDo in the meantime
+    loaded_history: Stack,
 }
 
 pub trait ManagerTrait {
     fn new(displayer: Box<dyn Displayer>) -> Self;
-    fn run(&mut self);
-    fn add_task(&mut self, description: &str, priority: &Priority);
+    fn run(&mut self) -> Result<(), TodoError>;
+    fn add_task(&mut self, description: &str, priority: &Priority) -> Result<String, TodoError>;
+    fn add_task_with_options(
+        &mut self,
+        description: &str,
+        priority: &Priority,
+        options: TaskOptions,
+    ) -> Result<String, TodoError>;
     fn get_tasks(&self) -> &Vec<Task>;
     fn get_task(&self, id: &str) -> Option<&Task>;
     fn get_task_mut(&mut self, id: &str) -> Option<&mut Task>;
-    fn toggle_task_status(&mut self, task_id: &str) -> bool;
-    fn remove_task(&mut self, task_id: &str) -> bool;
-    fn edit_task(&mut self, task_id: &str, new_description: &str, new_priority: &Priority) -> bool;
-    fn undo(&mut self) -> Result<bool, String>;
-    fn redo(&mut self) -> Result<bool, String>;
+    fn toggle_task_status(&mut self, task_id: &str, force: bool) -> Result<(), TodoError>;
+    fn toggle_tasks_status(&mut self, task_ids: &[String], force: bool) -> Result<(), TodoError>;
+    fn remove_task(&mut self, task_id: &str) -> Result<(), TodoError>;
+    fn remove_tasks(&mut self, task_ids: &[String]) -> Result<(), TodoError>;
+    fn archive_tasks(&mut self, task_ids: &[String]) -> Result<(), TodoError>;
+    fn unarchive_tasks(&mut self, task_ids: &[String]) -> Result<(), TodoError>;
+    fn import_tasks(&mut self, tasks: Vec<Task>) -> Result<Vec<String>, TodoError>;
+    fn edit_task(
+        &mut self,
+        task_id: &str,
+        new_description: &str,
+        new_priority: &Priority,
+    ) -> Result<(), TodoError>;
+    fn edit_task_with_options(
+        &mut self,
+        task_id: &str,
+        new_description: &str,
+        new_priority: &Priority,
+        options: TaskOptions,
+    ) -> Result<(), TodoError>;
+    fn edit_tasks(
+        &mut self,
+        edits: Vec<(String, String, Priority, TaskOptions)>,
+    ) -> Result<(), TodoError>;
+    fn is_blocked(&self, task_id: &str) -> bool;
+    fn open_dependencies(&self, task_id: &str) -> Vec<&Task>;
+    fn blocked_completions(&self, task_ids: &[String]) -> Vec<(String, Vec<String>)>;
+    fn undo(&mut self) -> Result<bool, TodoError>;
+    fn undo_to(&mut self, position: usize) -> Result<usize, TodoError>;
+    fn undo_action(&mut self, position: usize) -> Result<(), TodoError>;
+    fn redo(&mut self) -> Result<bool, TodoError>;
+}
+
+impl Manager {
+    /// Execute a command and record it in the undo stack, clearing the redo stack.
+    fn execute(&mut self, mut command: Command) -> Result<(), TodoError> {
+        let undo_data = command.execute(self)?;
+        self.undo_stack.push(HistoryEntry::new(command, undo_data));
+        self.redo_stack.clear();
+        self.save_history()
+    }
+
+    /// Load the undo and redo stacks saved alongside the todo list by previous sessions
+    pub fn load_history(&mut self) -> Result<(), TodoError> {
+        let (undo_stack, redo_stack) = match self.todo_list.storage.load_history()? {
+            Some(value) => History::from_value(value)?.into_stacks(),
+            None => (Vec::new(), Vec::new()),
+        };
+        self.undo_stack = undo_stack;
+        self.redo_stack = redo_stack;
+        self.limit_history();
+        self.loaded_history = self.undo_stack.clone();
+        Ok(())
+    }
+
+    /// Save the undo and redo stacks alongside the todo list while holding the lock of its
+    /// storage. The history saved by another ToDo since we loaded ours is read again and
+    /// merged with ours (see `merge_history`). An unreadable saved history is replaced.
+    pub fn save_history(&mut self) -> Result<(), TodoError> {
+        self.write_history(true)
+    }
+
+    /// Save the undo and redo stacks, merged with the saved ones when `merge` is set
+    fn write_history(&mut self, merge: bool) -> Result<(), TodoError> {
+        if self.todo_list.in_transaction() {
+            self.history_unsaved = true;
+            return Ok(());
+        }
+        self.history_unsaved = false;
+        let _lock = self.todo_list.storage.lock()?;
+        let stored = match merge {
+            true => self.todo_list.storage.load_history().ok().flatten(),
+            false => None,
+        };
+        if let Some(stored) = stored.and_then(|value| History::from_value(value).ok()) {
+            (self.undo_stack, self.redo_stack) = merge_history(
+                stored.into_stacks(),
+                &self.loaded_history,
+                &self.undo_stack,
+                &self.redo_stack,
+            );
+        }
+        self.limit_history();
+        let history = History::new(&self.undo_stack, &self.redo_stack).to_value()?;
+        self.todo_list.storage.save_history(&history)?;
+        self.loaded_history = self.undo_stack.clone();
+        Ok(())
+    }
+
+    /// Forget every action, e.g. when the todo list is replaced by a backup, including
+    /// the ones saved by another ToDo in the meantime
+    pub fn clear_history(&mut self) -> Result<(), TodoError> {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.write_history(false)
+    }
+
+    /// Refuse to complete tasks whose dependencies stay open, unless forced
+    fn check_dependencies(&self, task_ids: &[String], force: bool) -> Result<(), TodoError> {
+        match self.blocked_completions(task_ids).first() {
+            Some((id, dependencies)) if !force => Err(TodoError::Validation(format!(
+                "Task with ID: {} depends on open tasks: {}",
+                id,
+                dependencies.join(", ")
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Positions of the undo stack start at 1 for the last action
+    fn check_position(&self, position: usize) -> Result<(), TodoError> {
+        if position == 0 || position > self.undo_stack.len() {
+            return Err(TodoError::Validation(format!(
+                "No action {} in the undo history, which has {} actions",
+                position,
+                self.undo_stack.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn limit_history(&mut self) {
+        for stack in [&mut self.undo_stack, &mut self.redo_stack] {
+            let excess = stack.len().saturating_sub(self.history_limit);
+            stack.drain(..excess);
+        }
+    }
+
+    /// Execute several commands as a single action, which is undone and redone as a whole.
+    /// The todo list is saved once, and left untouched when any of them fails.
+    pub fn execute_transaction(&mut self, mut commands: Vec<Command>) -> Result<(), TodoError> {
+        match commands.len() {
+            0 => Ok(()),
+            1 => self.execute(commands.remove(0)),
+            _ => self.execute(Command::Transaction { commands }),
+        }
+    }
+
+    /// Make changes to the todo list as a transaction: it is saved once at the end,
+    /// and every change, undo history included, is reverted when `changes` or the save fails.
+    /// The actions executed by `changes` are recorded separately in the undo history,
+    /// see `execute_transaction` to record them as a single one.
+    pub fn transaction<T>(
+        &mut self,
+        changes: impl FnOnce(&mut Manager) -> Result<T, TodoError>,
+    ) -> Result<T, TodoError> {
+        let stacks = (self.undo_stack.clone(), self.redo_stack.clone());
+        let history_unsaved = self.history_unsaved;
+        self.todo_list.begin_transaction()?;
+        let result = match changes(self) {
+            Ok(result) => self.todo_list.commit_transaction().map(|_| result),
+            Err(e) => {
+                self.todo_list.rollback_transaction();
+                Err(e)
+            }
+        };
+        if result.is_err() {
+            (self.undo_stack, self.redo_stack) = stacks;
+            self.history_unsaved = history_unsaved;
+        } else if self.history_unsaved {
+            self.save_history()?;
+        }
+        result
+    }
+
+    /// Revert the changes described by the undo data of a command
+    fn apply_undo(&mut self, undo_data: &UndoRedoData) -> Result<(), TodoError> {
+        match undo_data {
+            UndoRedoData::AddTask { task } => {
+                self.todo_list.remove_task(task.id.clone())?;
+            }
+            UndoRedoData::CompleteTask {
+                id,
+                completed_subtasks,
+                next_occurrence,
+                ..
+            } => {
+                self.todo_list.toggle_task_status(id.clone())?;
+                for subtask_id in completed_subtasks {
+                    self.todo_list.toggle_task_status(subtask_id.clone())?;
+                }
+                if let Some(next_occurrence) = next_occurrence {
+                    self.todo_list.remove_task(next_occurrence.id.clone())?;
+                }
+            }
+            UndoRedoData::RemoveTask {
+                task,
+                subtasks,
+                dependents,
+            } => {
+                self.todo_list.push_task(task.clone())?;
+                for subtask in subtasks {
+                    self.todo_list.push_task(subtask.clone())?;
+                }
+                for (dependent, dependency) in dependents {
+                    self.todo_list.add_dependency(dependent, dependency)?;
+                }
+            }
+            UndoRedoData::EditTask { previous_task } => {
+                self.todo_list.replace_task(previous_task.clone())?;
+            }
+            UndoRedoData::ArchiveTasks { tasks, dependents } => {
+                let ids = tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
+                self.todo_list.unarchive_tasks(&ids)?;
+                for (dependent, dependency) in dependents {
+                    self.todo_list.add_dependency(dependent, dependency)?;
+                }
+            }
+            UndoRedoData::UnarchiveTasks { tasks } => {
+                self.todo_list.move_to_archive(tasks.clone())?;
+            }
+            UndoRedoData::ImportTasks { tasks } => {
+                self.transaction(|manager| {
+                    tasks.iter().try_for_each(|task| {
+                        // Subtasks are removed with their parent
+                        match manager.get_task(&task.id) {
+                            Some(_) => manager.todo_list.remove_task(task.id.clone()).map(|_| ()),
+                            None => Ok(()),
+                        }
+                    })
+                })?;
+            }
+            UndoRedoData::Transaction { undo_data } => {
+                self.transaction(|manager| {
+                    undo_data
+                        .iter()
+                        .rev()
+                        .try_for_each(|undo_data| manager.apply_undo(undo_data))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply again the changes of a command that was undone
+    fn apply_redo(&mut self, command: &Command, undo_data: &UndoRedoData) -> Result<(), TodoError> {
+        match (command, undo_data) {
+            // The task is restored with its original ID so later commands still refer to it
+            (Command::AddTask { .. }, UndoRedoData::AddTask { task }) => {
+                self.todo_list.push_task(task.clone())?;
+            }
+            (
+                Command::CompleteTask { id },
+                UndoRedoData::CompleteTask {
+                    completed_subtasks,
+                    next_occurrence,
+                    ..
+                },
+            ) => {
+                self.todo_list.toggle_task_status(id.clone())?;
+                for subtask_id in completed_subtasks {
+                    self.todo_list.toggle_task_status(subtask_id.clone())?;
+                }
+                if let Some(next_occurrence) = next_occurrence {
+                    self.todo_list.push_task(next_occurrence.clone())?;
+                }
+            }
+            (Command::RemoveTask { task }, _) => {
+                self.todo_list.remove_task(task.id.clone())?;
+            }
+            (
+                Command::EditTask {
+                    id,
+                    new_fields,
+                    options,
+                },
+                _,
+            ) => {
+                self.todo_list
+                    .edit_task(id, (new_fields.0.as_ref(), &new_fields.1))?;
+                self.todo_list.set_options(id, options.clone())?;
+            }
+            (Command::ArchiveTasks { ids }, _) => {
+                self.todo_list.archive_tasks(ids)?;
+            }
+            (Command::UnarchiveTasks { ids }, _) => {
+                self.todo_list.unarchive_tasks(ids)?;
+            }
+            // The tasks are restored with the IDs they were given, as added tasks are
+            (Command::ImportTasks { .. }, UndoRedoData::ImportTasks { tasks }) => {
+                self.transaction(|manager| {
+                    tasks
+                        .iter()
+                        .try_for_each(|task| manager.todo_list.push_task(task.clone()))
+                })?;
+            }
+            (Command::Transaction { commands }, UndoRedoData::Transaction { undo_data }) => {
+                self.transaction(|manager| {
+                    commands
+                        .iter()
+                        .zip(undo_data)
+                        .try_for_each(|(command, undo_data)| manager.apply_redo(command, undo_data))
+                })?;
+            }
+            (command, undo_data) => {
+                return Err(TodoError::Validation(format!(
+                    "Mismatched redo data {:?} for command {:?}",
+                    undo_data, command
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ManagerTrait for Manager {
@@ -32,16 +348,52 @@ impl ManagerTrait for Manager {
             todo_list: TodoList::new(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            archive_on_complete: false,
             displayer: Some(displayer),
+            history_unsaved: false,
+            loaded_history: Vec::new(),
         }
     }
 
-    /// Runs the displayer, loading the todo list if it exists.
+    /// Runs the displayer, loading the todo list and its undo history if it exists.
     /// If the todo list does not exist, it notifies the user and creates a new one.
-    fn run(&mut self) {
-        if let Some(displayer) = self.displayer.as_mut() {
-            if let Err(_) = self.todo_list.try_load() {
-                let _ = displayer.notify("No previous todo list found... Created a new one🦀");
+    /// If it exists but cannot be loaded, the error is notified and returned without running the displayer,
+    /// with a hint to restore one of its backups when it has some.
+    /// An unreadable history is notified and started again.
+    fn run(&mut self) -> Result<(), TodoError> {
+        match self.todo_list.try_load() {
+            Ok(true) => {
+                if let Err(e) = self.load_history()
+                    && let Some(displayer) = self.displayer.as_mut()
+                {
+                    let _ = displayer.notify(&format!(
+                        "Error: {}. The undo history starts again from now.",
+                        e
+                    ));
+                }
+            }
+            Ok(false) => {
+                if let Some(displayer) = self.displayer.as_mut() {
+                    let _ = displayer.notify("No previous todo list found... Created a new one🦀");
+                }
+            }
+            Err(e) => {
+                if let Some(displayer) = self.displayer.as_mut() {
+                    let _ = displayer.notify(&format!("Error: {}", e));
+                    if matches!(e, TodoError::Parse(_))
+                        && self
+                            .todo_list
+                            .storage
+                            .backups()
+                            .is_ok_and(|backups| !backups.is_empty())
+                    {
+                        let _ = displayer.notify(
+                            "The todo list was left untouched. Run `ToDo restore` to list its backups.",
+                        );
+                    }
+                }
+                return Err(e);
             }
         }
 
@@ -49,18 +401,40 @@ impl ManagerTrait for Manager {
             displayer.run(self);
             self.displayer = Some(displayer);
         }
+        Ok(())
     }
 
     /// Adds a new task to the todo list and updates the undo stack.
     /// Clears the redo stack after adding a new task.
-    fn add_task(&mut self, description: &str, priority: &Priority) {
-        let mut command = Command::AddTask {
-            description: description.to_string(),
+    /// Returns the ID of the new task.
+    fn add_task(&mut self, description: &str, priority: &Priority) -> Result<String, TodoError> {
+        self.add_task_with_options(description, priority, TaskOptions::default())
+    }
+
+    /// Adds a new task with its optional attributes (e.g. due date) already set.
+    /// The `+tag` words of the description are moved into the task tags.
+    fn add_task_with_options(
+        &mut self,
+        description: &str,
+        priority: &Priority,
+        mut options: TaskOptions,
+    ) -> Result<String, TodoError> {
+        let (description, tags) = extract_tags(description);
+        options.tags.extend(tags);
+        self.execute(Command::AddTask {
+            description,
             priority: *priority,
-        };
-        let undo_data = command.execute(self);
-        self.undo_stack.push((command, undo_data));
-        self.redo_stack.clear();
+            options,
+        })?;
+        match self.undo_stack.last() {
+            Some(HistoryEntry {
+                undo_data: UndoRedoData::AddTask { task },
+                ..
+            }) => Ok(task.id.clone()),
+            _ => Err(TodoError::Validation(
+                "The added task was not recorded in the undo stack".to_string(),
+            )),
+        }
     }
 
     /// Returns the tasks in the todo list.
@@ -78,119 +452,291 @@ impl ManagerTrait for Manager {
         self.todo_list.get_task_mut(id)
     }
 
-    /// Complete/Uncomplete a task by ID
-    /// Returns true if the task was found and toggled, false otherwise.
-    fn toggle_task_status(&mut self, task_id: &str) -> bool {
-        if self.get_task(task_id).is_none() {
-            return false;
-        } else {
-            let mut command = Command::CompleteTask { id: task_id.into() };
-            let undo_data = command.execute(self);
-            self.undo_stack.push((command, undo_data));
-            self.redo_stack.clear();
-            return true;
+    /// Complete/Uncomplete a task by ID. With `archive_on_complete`, a completed task
+    /// is moved to the archive in the same action.
+    /// Completing a task whose dependencies are still open is refused unless `force` is set.
+    fn toggle_task_status(&mut self, task_id: &str, force: bool) -> Result<(), TodoError> {
+        let task = self
+            .get_task(task_id)
+            .ok_or_else(|| TodoError::NotFound(task_id.to_string()))?;
+        self.check_dependencies(&[task_id.to_string()], force)?;
+        let mut commands = vec![Command::CompleteTask { id: task_id.into() }];
+        if self.archive_on_complete && !task.completed {
+            commands.push(Command::ArchiveTasks {
+                ids: vec![task_id.into()],
+            });
         }
+        self.execute_transaction(commands)
     }
 
-    /// Remove a task from the todo list by ID
-    /// Returns true if the task was found and removed, false otherwise.
-    fn remove_task(&mut self, task_id: &str) -> bool {
-        let task_ = self.get_task(task_id);
+    /// Toggle the status of several tasks as a single action.
+    /// Open subtasks of a selected open task are left to it, as completing it completes them.
+    /// Completing tasks whose dependencies stay open is refused unless `force` is set.
+    fn toggle_tasks_status(&mut self, task_ids: &[String], force: bool) -> Result<(), TodoError> {
+        self.check_dependencies(task_ids, force)?;
+        let mut commands = Vec::new();
+        let mut completed = Vec::new();
+        for id in task_ids {
+            let task = self
+                .get_task(id)
+                .ok_or_else(|| TodoError::NotFound(id.clone()))?;
+            let completed_by_parent = !task.completed
+                && task_ids.iter().any(|other| {
+                    self.get_task(other).is_some_and(|other| !other.completed)
+                        && self.todo_list.descendants(other).contains(id)
+                });
+            if !completed_by_parent {
+                if !task.completed {
+                    completed.push(id.clone());
+                }
+                commands.push(Command::CompleteTask { id: id.clone() });
+            }
+        }
+        if self.archive_on_complete && !completed.is_empty() {
+            commands.push(Command::ArchiveTasks { ids: completed });
+        }
+        self.execute_transaction(commands)
+    }
 
-        if task_.is_none() {
-            return false;
-        } else {
-            let mut command = Command::RemoveTask {
-                task: task_
-                    .expect("Error when extracting the task during remove")
-                    .clone(),
-            };
-            let undo_data = command.execute(self);
-            self.undo_stack.push((command, undo_data));
-            self.redo_stack.clear();
-            return true;
+    /// Remove a task and its subtasks from the todo list by ID
+    fn remove_task(&mut self, task_id: &str) -> Result<(), TodoError> {
+        let task = self
+            .get_task(task_id)
+            .ok_or_else(|| TodoError::NotFound(task_id.to_string()))?;
+        self.execute(Command::RemoveTask { task: task.clone() })
+    }
+
+    /// Remove several tasks as a single action, with their subtasks
+    fn remove_tasks(&mut self, task_ids: &[String]) -> Result<(), TodoError> {
+        let mut commands = Vec::new();
+        for id in task_ids {
+            let task = self
+                .get_task(id)
+                .ok_or_else(|| TodoError::NotFound(id.clone()))?;
+            let removed_with_parent = task_ids
+                .iter()
+                .any(|other| self.todo_list.descendants(other).contains(id));
+            if !removed_with_parent {
+                commands.push(Command::RemoveTask { task: task.clone() });
+            }
+        }
+        self.execute_transaction(commands)
+    }
+
+    /// Move several tasks to the archive as a single action, with their subtasks
+    fn archive_tasks(&mut self, task_ids: &[String]) -> Result<(), TodoError> {
+        let mut ids = Vec::new();
+        for id in task_ids {
+            if self.get_task(id).is_none() {
+                return Err(TodoError::NotFound(id.clone()));
+            }
+            let archived_with_parent = task_ids
+                .iter()
+                .any(|other| self.todo_list.descendants(other).contains(id));
+            if !archived_with_parent {
+                ids.push(id.clone());
+            }
         }
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.execute(Command::ArchiveTasks { ids })
     }
 
-    fn edit_task(&mut self, task_id: &str, new_description: &str, new_priority: &Priority) -> bool {
-        let task = self.get_task_mut(task_id);
+    /// Move archived tasks back to the todo list as a single action, with their archived subtasks
+    fn unarchive_tasks(&mut self, task_ids: &[String]) -> Result<(), TodoError> {
+        if task_ids.is_empty() {
+            return Ok(());
+        }
+        self.execute(Command::UnarchiveTasks {
+            ids: task_ids.to_vec(),
+        })
+    }
 
-        if task.is_none() {
-            return false;
-        } else {
-            let mut command = Command::EditTask {
-                id: task_id.into(),
-                new_fields: (new_description.into(), *new_priority),
-            };
-            let undo_data = command.execute(self);
-            self.undo_stack.push((command, undo_data));
-            self.redo_stack.clear();
-            return true;
+    /// Add tasks read from another format as a single action, giving them fresh IDs.
+    /// Returns the IDs of the added tasks.
+    fn import_tasks(&mut self, tasks: Vec<Task>) -> Result<Vec<String>, TodoError> {
+        if tasks.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.execute(Command::ImportTasks { tasks })?;
+        match self.undo_stack.last().map(|entry| &entry.undo_data) {
+            Some(UndoRedoData::ImportTasks { tasks }) => {
+                Ok(tasks.iter().map(|task| task.id.clone()).collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Edit the description and priority of a task, keeping its optional attributes.
+    fn edit_task(
+        &mut self,
+        task_id: &str,
+        new_description: &str,
+        new_priority: &Priority,
+    ) -> Result<(), TodoError> {
+        let options = self
+            .get_task(task_id)
+            .ok_or_else(|| TodoError::NotFound(task_id.to_string()))?
+            .options();
+        self.edit_task_with_options(task_id, new_description, new_priority, options)
+    }
+
+    /// Edit every editable field of a task, replacing its optional attributes.
+    /// The `+tag` words of the new description are added to the task tags.
+    fn edit_task_with_options(
+        &mut self,
+        task_id: &str,
+        new_description: &str,
+        new_priority: &Priority,
+        mut options: TaskOptions,
+    ) -> Result<(), TodoError> {
+        if self.get_task(task_id).is_none() {
+            return Err(TodoError::NotFound(task_id.to_string()));
+        }
+        let (new_description, tags) = extract_tags(new_description);
+        options.tags.extend(tags);
+        self.execute(Command::EditTask {
+            id: task_id.into(),
+            new_fields: (new_description, *new_priority),
+            options,
+        })
+    }
+
+    /// Edit several tasks as a single action, each with its new description, priority and
+    /// optional attributes. The `+tag` words of the new descriptions are added to the tags.
+    fn edit_tasks(
+        &mut self,
+        edits: Vec<(String, String, Priority, TaskOptions)>,
+    ) -> Result<(), TodoError> {
+        let mut commands = Vec::new();
+        for (id, description, priority, mut options) in edits {
+            if self.get_task(&id).is_none() {
+                return Err(TodoError::NotFound(id));
+            }
+            let (description, tags) = extract_tags(&description);
+            options.tags.extend(tags);
+            commands.push(Command::EditTask {
+                id,
+                new_fields: (description, priority),
+                options,
+            });
         }
+        self.execute_transaction(commands)
+    }
+
+    /// A task is blocked while any of the tasks it depends on is still open,
+    /// otherwise it is ready to be done.
+    fn is_blocked(&self, task_id: &str) -> bool {
+        self.todo_list.is_blocked(task_id)
+    }
+
+    /// Returns the open tasks the given one depends on.
+    fn open_dependencies(&self, task_id: &str) -> Vec<&Task> {
+        self.todo_list.open_dependencies(task_id)
+    }
+
+    /// Returns the tasks that toggling the given ones would complete while some of their
+    /// dependencies stay open, with these dependencies. The dependencies completed by the
+    /// same toggle, being selected or subtasks of a selected task, are not counted.
+    fn blocked_completions(&self, task_ids: &[String]) -> Vec<(String, Vec<String>)> {
+        let completing = task_ids
+            .iter()
+            .filter(|id| self.get_task(id).is_some_and(|task| !task.completed))
+            .collect::<Vec<_>>();
+        let completed_together = completing
+            .iter()
+            .flat_map(|id| {
+                let mut ids = self.todo_list.descendants(id);
+                ids.push(id.to_string());
+                ids
+            })
+            .collect::<Vec<_>>();
+        completing
+            .into_iter()
+            .filter_map(|id| {
+                let dependencies = self
+                    .open_dependencies(id)
+                    .into_iter()
+                    .map(|task| task.id.clone())
+                    .filter(|dependency| !completed_together.contains(dependency))
+                    .collect::<Vec<_>>();
+                (!dependencies.is_empty()).then(|| (id.clone(), dependencies))
+            })
+            .collect()
     }
 
     /// Undo the last action performed on the todo list.
     /// Returns Ok(true) if the action was successfully undone, Ok(false) if there was nothing to undo.
-    /// Returns an error if the undo operation fails.
-    fn undo(&mut self) -> Result<bool, String> {
-        if let Some((command, undo_data)) = self.undo_stack.pop() {
-            let redo_undo_data_copy = undo_data.clone();
-            match &undo_data {
-                UndoRedoData::AddTask { task } => {
-                    self.todo_list.remove_task(task.id.clone());
-                }
-                UndoRedoData::CompleteTask { id, previous_state } => {
-                    if *previous_state {
-                        self.todo_list.toggle_task_status(id.clone());
-                    } else {
-                        self.todo_list.toggle_task_status(id.clone());
-                    }
-                }
-                UndoRedoData::RemoveTask { task } => {
-                    self.todo_list.push_task(task.clone());
-                }
-                UndoRedoData::EditTask { previous_task } => {
-                    self.todo_list.edit_task(
-                        previous_task.id.as_ref(),
-                        (previous_task.description.as_ref(), &previous_task.priority),
-                    );
-                }
+    /// Returns an error if the undo operation fails, keeping the action in the undo stack.
+    fn undo(&mut self) -> Result<bool, TodoError> {
+        if let Some(entry) = self.undo_stack.pop() {
+            if let Err(e) = self.apply_undo(&entry.undo_data) {
+                self.undo_stack.push(entry);
+                return Err(e);
             }
-            self.redo_stack.push((command, redo_undo_data_copy));
+            self.redo_stack.push(entry);
+            self.save_history()?;
             Ok(true)
         } else {
-            return Ok(false);
+            Ok(false)
         }
     }
 
+    /// Go back to the point before the action at the given position of the undo stack,
+    /// 1 being the last action, undoing it and every later action in a single transaction.
+    /// Returns the number of undone actions. When one of them cannot be undone, none is.
+    fn undo_to(&mut self, position: usize) -> Result<usize, TodoError> {
+        self.check_position(position)?;
+        self.transaction(|manager| {
+            for _ in 0..position {
+                manager.undo()?;
+            }
+            Ok(position)
+        })
+    }
+
+    /// Undo only the action at the given position of the undo stack, 1 being the last action.
+    /// It is refused when a later action changed one of its tasks, as they would conflict.
+    /// The undone action can be redone like the others.
+    fn undo_action(&mut self, position: usize) -> Result<(), TodoError> {
+        self.check_position(position)?;
+        let index = self.undo_stack.len() - position;
+        let ids = self.undo_stack[index].undo_data.task_ids();
+        for (later, entry) in self.undo_stack[index + 1..].iter().rev().enumerate() {
+            if let Some(id) = entry.undo_data.task_ids().intersection(&ids).next() {
+                return Err(TodoError::Validation(format!(
+                    "Action {} cannot be undone on its own, task {} was also changed by action {}. \
+                     Use `undo --to {}` to undo both",
+                    position,
+                    id,
+                    later + 1,
+                    position
+                )));
+            }
+        }
+        let entry = self.undo_stack[index].clone();
+        self.apply_undo(&entry.undo_data)?;
+        self.undo_stack.remove(index);
+        self.redo_stack.push(entry);
+        self.save_history()
+    }
+
     /// Redo the last action performed on the todo list.
     /// Returns Ok(true) if the action was successfully redone, Ok(false) if there was nothing to redo.
-    /// Returns an error if the redo operation fails.
-    fn redo(&mut self) -> Result<bool, String> {
-        if let Some((command, undo_data)) = self.redo_stack.pop() {
-            match &command {
-                Command::AddTask {
-                    description,
-                    priority,
-                } => {
-                    self.todo_list
-                        .add_task(description.clone(), priority.clone().clone());
-                }
-                Command::CompleteTask { id } => {
-                    self.todo_list.toggle_task_status(id.clone());
-                }
-                Command::RemoveTask { task } => {
-                    self.todo_list.remove_task(task.id.clone());
-                }
-                Command::EditTask { id, new_fields } => {
-                    self.todo_list
-                        .edit_task(id, (new_fields.0.as_ref(), &new_fields.1));
-                }
+    /// Returns an error if the redo operation fails, keeping the action in the redo stack.
+    fn redo(&mut self) -> Result<bool, TodoError> {
+        if let Some(entry) = self.redo_stack.pop() {
+            if let Err(e) = self.apply_redo(&entry.command, &entry.undo_data) {
+                self.redo_stack.push(entry);
+                return Err(e);
             }
-            self.undo_stack.push((command, undo_data));
+            self.undo_stack
+                .push(HistoryEntry::new(entry.command, entry.undo_data));
+            self.save_history()?;
             Ok(true)
         } else {
-            return Ok(false);
+            Ok(false)
         }
     }
 }
@@ -198,8 +744,18 @@ impl ManagerTrait for Manager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::due::Due;
+    use crate::model::recurrence::Recurrence;
+    use crate::storage::memory::MemoryStorage;
     use crate::ui::console_ui::mock_displayer::MockDisplayer;
 
+    /// Manager keeping its tasks in memory, so that the tests do not share a file
+    fn memory_manager(displayer: Box<dyn Displayer>) -> Manager {
+        let mut manager = Manager::new(displayer);
+        manager.todo_list.storage = Box::new(MemoryStorage::new());
+        manager
+    }
+
     #[test]
     fn test_new_manager() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
@@ -213,8 +769,10 @@ mod tests {
     #[test]
     fn test_add_task() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
-        manager.add_task("Test task".as_ref(), &Priority::High);
+        let mut manager = memory_manager(displayer);
+        manager
+            .add_task("Test task".as_ref(), &Priority::High)
+            .unwrap();
         assert_eq!(manager.todo_list.tasks.len(), 1);
         assert_eq!(manager.undo_stack.len(), 1);
         assert_eq!(manager.redo_stack.len(), 0);
@@ -225,13 +783,14 @@ mod tests {
     #[test]
     fn test_complete_task() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
+        let mut manager = memory_manager(displayer);
         let id = manager
             .todo_list
-            .add_task("Test task".to_string(), Priority::Medium);
-        let result = manager.toggle_task_status(id.as_ref());
-        assert!(result);
-        assert_eq!(manager.todo_list.tasks[0].completed, true);
+            .add_task("Test task".to_string(), Priority::Medium)
+            .unwrap();
+        let result = manager.toggle_task_status(id.as_ref(), false);
+        assert!(result.is_ok());
+        assert!(manager.todo_list.tasks[0].completed);
         assert_eq!(manager.undo_stack.len(), 1);
         assert_eq!(manager.redo_stack.len(), 0);
     }
@@ -239,21 +798,22 @@ mod tests {
     #[test]
     fn test_complete_task_not_found() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
-        let result = manager.toggle_task_status("notfound".as_ref());
-        assert!(!result);
+        let mut manager = memory_manager(displayer);
+        let result = manager.toggle_task_status("notfound".as_ref(), false);
+        assert_eq!(result, Err(TodoError::NotFound("notfound".to_string())));
         assert_eq!(manager.undo_stack.len(), 0);
     }
 
     #[test]
     fn test_remove_task() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
+        let mut manager = memory_manager(displayer);
         let id = manager
             .todo_list
-            .add_task("Test task".to_string(), Priority::Low);
+            .add_task("Test task".to_string(), Priority::Low)
+            .unwrap();
         let result = manager.remove_task(id.as_ref());
-        assert!(result);
+        assert!(result.is_ok());
         assert_eq!(manager.todo_list.tasks.len(), 0);
         assert_eq!(manager.undo_stack.len(), 1);
         assert_eq!(manager.redo_stack.len(), 0);
@@ -262,8 +822,10 @@ mod tests {
     #[test]
     fn test_undo_add_task() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
-        manager.add_task("Test task".as_ref(), &Priority::High);
+        let mut manager = memory_manager(displayer);
+        manager
+            .add_task("Test task".as_ref(), &Priority::High)
+            .unwrap();
         let result = manager.undo().expect("Undo failed");
         assert!(result);
         assert_eq!(manager.todo_list.tasks.len(), 0);
@@ -274,8 +836,10 @@ mod tests {
     #[test]
     fn test_redo_add_task() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
-        manager.add_task("Test task".as_ref(), &Priority::High);
+        let mut manager = memory_manager(displayer);
+        manager
+            .add_task("Test task".as_ref(), &Priority::High)
+            .unwrap();
         manager.undo().expect("Undo failed");
         let result = manager.redo().expect("Redo failed");
         assert!(result);
@@ -287,7 +851,7 @@ mod tests {
     #[test]
     fn test_undo_empty() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
+        let mut manager = memory_manager(displayer);
         let result = manager.undo().expect("Undo failed");
         assert!(!result);
     }
@@ -295,7 +859,7 @@ mod tests {
     #[test]
     fn test_undo_empty_stack() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
+        let mut manager = memory_manager(displayer);
         let result = manager.undo();
         assert_eq!(result, Ok(false));
     }
@@ -303,8 +867,295 @@ mod tests {
     #[test]
     fn test_redo_empty_stack() {
         let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
-        let mut manager = Manager::new(displayer);
+        let mut manager = memory_manager(displayer);
         let result = manager.redo();
         assert_eq!(result, Ok(false));
     }
+
+    fn create_manager_with_subtasks() -> Manager {
+        let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
+        let mut manager = memory_manager(displayer);
+        manager
+            .add_task("Parent".as_ref(), &Priority::High)
+            .unwrap();
+        let subtask = TaskOptions {
+            parent: Some("1".to_string()),
+            ..Default::default()
+        };
+        manager
+            .add_task_with_options("Child 1".as_ref(), &Priority::Low, subtask.clone())
+            .unwrap();
+        manager
+            .add_task_with_options("Child 2".as_ref(), &Priority::Low, subtask)
+            .unwrap();
+        manager
+    }
+
+    #[test]
+    fn test_undo_redo_remove_subtree() {
+        let mut manager = create_manager_with_subtasks();
+        assert!(manager.remove_task("1".as_ref()).is_ok());
+        assert_eq!(manager.todo_list.tasks.len(), 0);
+
+        manager.undo().expect("Undo failed");
+        assert_eq!(manager.todo_list.tasks.len(), 3);
+        assert_eq!(manager.todo_list.descendants("1"), vec!["2", "3"]);
+
+        manager.redo().expect("Redo failed");
+        assert_eq!(manager.todo_list.tasks.len(), 0);
+    }
+
+    #[test]
+    fn test_complete_parent_cascades_to_subtasks() {
+        let mut manager = create_manager_with_subtasks();
+        manager.toggle_task_status("2".as_ref(), false).unwrap();
+        manager.toggle_task_status("1".as_ref(), false).unwrap();
+        assert!(manager.todo_list.tasks.iter().all(|task| task.completed));
+
+        manager.undo().expect("Undo failed");
+        assert!(!manager.get_task("1").unwrap().completed);
+        assert!(manager.get_task("2").unwrap().completed);
+        assert!(!manager.get_task("3").unwrap().completed);
+
+        manager.redo().expect("Redo failed");
+        assert!(manager.todo_list.tasks.iter().all(|task| task.completed));
+    }
+
+    #[test]
+    fn test_redo_add_keeps_task_id() {
+        let mut manager = create_manager_with_subtasks();
+        manager.undo().expect("Undo failed");
+        manager.undo().expect("Undo failed");
+        manager.undo().expect("Undo failed");
+        for _ in 0..3 {
+            manager.redo().expect("Redo failed");
+        }
+        let ids = manager
+            .todo_list
+            .tasks
+            .iter()
+            .map(|task| task.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1", "2", "3"]);
+        assert_eq!(manager.todo_list.progress("1"), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_undo_remove_restores_dependencies() {
+        let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
+        let mut manager = memory_manager(displayer);
+        manager.add_task("First".as_ref(), &Priority::High).unwrap();
+        let options = TaskOptions {
+            depends_on: ["1".to_string()].into(),
+            ..Default::default()
+        };
+        manager
+            .add_task_with_options("Second".as_ref(), &Priority::High, options)
+            .unwrap();
+        assert!(manager.is_blocked("2"));
+
+        manager.remove_task("1".as_ref()).unwrap();
+        assert!(manager.get_task("2").unwrap().depends_on.is_empty());
+
+        manager.undo().expect("Undo failed");
+        assert!(manager.get_task("2").unwrap().depends_on.contains("1"));
+        assert!(manager.is_blocked("2"));
+    }
+
+    #[test]
+    fn test_complete_blocked_task_needs_force() {
+        let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
+        let mut manager = memory_manager(displayer);
+        manager.add_task("First".as_ref(), &Priority::High).unwrap();
+        let options = TaskOptions {
+            depends_on: ["1".to_string()].into(),
+            ..Default::default()
+        };
+        manager
+            .add_task_with_options("Second".as_ref(), &Priority::High, options)
+            .unwrap();
+
+        let result = manager.toggle_task_status("2".as_ref(), false);
+        assert_eq!(
+            result,
+            Err(TodoError::Validation(
+                "Task with ID: 2 depends on open tasks: 1".to_string()
+            ))
+        );
+        assert!(!manager.get_task("2").unwrap().completed);
+        assert!(
+            manager
+                .toggle_tasks_status(&["2".to_string()], false)
+                .is_err()
+        );
+
+        // Its dependency is completed in the same batch
+        let ids = ["2".to_string(), "1".to_string()];
+        assert!(manager.blocked_completions(&ids).is_empty());
+        manager.toggle_tasks_status(&ids, false).unwrap();
+        assert!(manager.get_tasks().iter().all(|task| task.completed));
+
+        manager.undo().unwrap();
+        manager.toggle_task_status("2".as_ref(), true).unwrap();
+        assert!(manager.get_task("2").unwrap().completed);
+    }
+
+    #[test]
+    fn test_complete_recurring_task_spawns_next_occurrence() {
+        let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
+        let mut manager = memory_manager(displayer);
+        let options = TaskOptions {
+            due: Some(Due::parse("2026-10-19").unwrap()),
+            recurrence: Some(Recurrence::Daily),
+            ..Default::default()
+        };
+        manager
+            .add_task_with_options("Water plants +home".as_ref(), &Priority::Medium, options)
+            .unwrap();
+
+        manager.toggle_task_status("1".as_ref(), false).unwrap();
+        let next = manager.get_task("2").expect("Next occurrence not spawned");
+        assert!(!next.completed);
+        assert_eq!(next.description, "Water plants");
+        assert_eq!(next.due, Some(Due::parse("2026-10-20").unwrap()));
+        assert_eq!(next.recurrence, Some(Recurrence::Daily));
+        assert!(next.tags.contains("home"));
+
+        manager.undo().expect("Undo failed");
+        assert!(!manager.get_task("1").unwrap().completed);
+        assert!(manager.get_task("2").is_none());
+
+        manager.redo().expect("Redo failed");
+        assert!(manager.get_task("1").unwrap().completed);
+        assert_eq!(
+            manager.get_task("2").unwrap().due,
+            Some(Due::parse("2026-10-20").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_action() {
+        let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
+        let mut manager = memory_manager(displayer);
+        let id = manager
+            .add_task("Test task".as_ref(), &Priority::High)
+            .unwrap();
+        manager.todo_list.tasks.clear();
+
+        assert_eq!(manager.undo(), Err(TodoError::NotFound(id)));
+        assert_eq!(manager.undo_stack.len(), 1);
+        assert_eq!(manager.redo_stack.len(), 0);
+    }
+
+    #[test]
+    fn test_changes_are_saved_to_storage() {
+        let displayer: Box<dyn Displayer> = Box::new(MockDisplayer::new());
+        let mut manager = Manager::new(displayer);
+        let storage = MemoryStorage::new();
+        manager.todo_list.storage = Box::new(storage.clone());
+
+        let id = manager
+            .add_task("Test task".as_ref(), &Priority::High)
+            .unwrap();
+        manager.toggle_task_status(&id, false).unwrap();
+        assert!(storage.tasks().unwrap()[0].completed);
+
+        manager.undo().expect("Undo failed");
+        manager.undo().expect("Undo failed");
+        assert!(storage.tasks().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_is_kept_across_sessions() {
+        let storage = MemoryStorage::new();
+        let mut manager = Manager::new(Box::new(MockDisplayer::new()));
+        manager.todo_list.storage = Box::new(storage.clone());
+        manager
+            .add_task("Test task".as_ref(), &Priority::High)
+            .unwrap();
+        manager.add_task("Other".as_ref(), &Priority::Low).unwrap();
+        manager.undo().expect("Undo failed");
+
+        let mut manager = Manager::new(Box::new(MockDisplayer::new()));
+        manager.todo_list.storage = Box::new(storage.clone());
+        manager.todo_list.load().unwrap();
+        manager.load_history().unwrap();
+        assert_eq!(manager.undo_stack.len(), 1);
+        assert_eq!(manager.redo_stack.len(), 1);
+
+        manager.redo().expect("Redo failed");
+        assert_eq!(manager.get_task("2").unwrap().description, "Other");
+        manager.undo().expect("Undo failed");
+        manager.undo().expect("Undo failed");
+        assert!(storage.tasks().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_history_limit_drops_oldest_actions() {
+        let mut manager = memory_manager(Box::new(MockDisplayer::new()));
+        manager.history_limit = 2;
+        for description in ["First", "Second", "Third"] {
+            manager
+                .add_task(description.as_ref(), &Priority::Low)
+                .unwrap();
+        }
+        assert_eq!(manager.undo_stack.len(), 2);
+        assert!(manager.undo().unwrap());
+        assert!(manager.undo().unwrap());
+        assert!(!manager.undo().unwrap());
+        assert_eq!(manager.todo_list.tasks.len(), 1);
+        assert_eq!(manager.todo_list.tasks[0].description, "First");
+    }
+
+    #[test]
+    fn test_bulk_actions_are_undone_as_a_whole() {
+        let mut manager = create_manager_with_subtasks();
+        manager.add_task("Other".as_ref(), &Priority::High).unwrap();
+        let ids = ["1", "2", "4"].map(String::from);
+
+        manager.toggle_tasks_status(&ids, false).unwrap();
+        assert!(manager.todo_list.tasks.iter().all(|task| task.completed));
+        assert_eq!(manager.undo_stack.len(), 5);
+
+        manager.undo().expect("Undo failed");
+        assert!(manager.todo_list.tasks.iter().all(|task| !task.completed));
+        manager.redo().expect("Redo failed");
+        assert!(manager.todo_list.tasks.iter().all(|task| task.completed));
+
+        manager.remove_tasks(&ids).unwrap();
+        assert!(manager.todo_list.tasks.is_empty());
+        manager.undo().expect("Undo failed");
+        assert_eq!(manager.todo_list.tasks.len(), 4);
+    }
+
+    #[test]
+    fn test_failed_bulk_edit_changes_nothing() {
+        let mut manager = create_manager_with_subtasks();
+        let edit = |id: &str| {
+            (
+                id.to_string(),
+                "Edited".to_string(),
+                Priority::High,
+                TaskOptions::default(),
+            )
+        };
+        let result = manager.edit_tasks(vec![edit("2"), edit("9")]);
+        assert_eq!(result, Err(TodoError::NotFound("9".to_string())));
+        assert_eq!(manager.get_task("2").unwrap().description, "Child 1");
+
+        let commands = vec![
+            Command::EditTask {
+                id: "2".to_string(),
+                new_fields: ("Edited".to_string(), Priority::High),
+                options: TaskOptions::default(),
+            },
+            Command::CompleteTask {
+                id: "9".to_string(),
+            },
+        ];
+        // The edit of task 2 is reverted when the completion of task 9 fails
+        assert!(manager.execute_transaction(commands).is_err());
+        assert_eq!(manager.get_task("2").unwrap().description, "Child 1");
+        assert_eq!(manager.undo_stack.len(), 3);
+    }
 }
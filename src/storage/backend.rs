@@ -0,0 +1,270 @@
+use crate::error::TodoError;
+use crate::model::task::Task;
+use crate::storage::file::backup_paths;
+use crate::storage::json_file::JsonFileStorage;
+use crate::storage::lock::FileLock;
+use crate::storage::memory::MemoryStorage;
+use crate::storage::schema::MigrationPlan;
+use crate::storage::sqlite::SqliteStorage;
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde_json::Value;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable choosing the storage backend: `json`, `sqlite` or `memory`
+pub const TODO_BACKEND_VARIABLE: &str = "TODO_BACKEND";
+
+/// Environment variable giving the number of backups kept by the file backends
+pub const TODO_BACKUPS_VARIABLE: &str = "TODO_BACKUPS";
+
+/// Number of backups kept when `TODO_BACKUPS` is not set
+pub const DEFAULT_BACKUPS: usize = 3;
+
+/// Environment variable giving how many seconds to wait for the lock of a todo list
+pub const TODO_LOCK_TIMEOUT_VARIABLE: &str = "TODO_LOCK_TIMEOUT";
+
+/// Time waited for the lock of a todo list when `TODO_LOCK_TIMEOUT` is not set
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Place where the tasks of a todo list are persisted
+pub trait Storage: Debug {
+    /// Whether tasks have already been stored
+    fn exists(&self) -> bool;
+    /// Read the stored tasks, upgrading them in memory when they were saved with an older schema
+    fn load(&self) -> Result<TodoData, TodoError>;
+    /// Replace the stored tasks
+    fn save(&mut self, data: &TodoData) -> Result<(), TodoError>;
+    /// Replace the stored tasks, knowing the ones stored until now, so that the storages
+    /// able to do so only write the changed tasks. Called while holding the lock of the
+    /// storage. By default the tasks are all written again.
+    fn save_changes(&mut self, data: &TodoData, _stored: &[Task]) -> Result<(), TodoError> {
+        self.save(data)
+    }
+    /// Where the tasks are stored, for messages
+    fn location(&self) -> String;
+
+    /// Take the lock keeping other processes from saving at the same time,
+    /// None for the storages that are not shared between processes
+    fn lock(&self) -> Result<Option<FileLock>, TodoError> {
+        Ok(None)
+    }
+
+    /// Read the undo history saved alongside the tasks, None when there is none
+    fn load_history(&self) -> Result<Option<Value>, TodoError> {
+        Ok(None)
+    }
+
+    /// Replace the saved undo history. Called while holding the lock of the storage.
+    fn save_history(&mut self, _history: &Value) -> Result<(), TodoError> {
+        Ok(())
+    }
+
+    /// Read the archived tasks, kept apart from the todo list. Empty when none was archived.
+    fn load_archive(&self) -> Result<Vec<Task>, TodoError> {
+        Ok(Vec::new())
+    }
+
+    /// Replace the archived tasks. Called while holding the lock of the storage.
+    fn save_archive(&mut self, _tasks: &[Task]) -> Result<(), TodoError> {
+        Err(TodoError::Validation(format!(
+            "The tasks of {} cannot be archived",
+            self.location()
+        )))
+    }
+
+    /// Upgrade the stored tasks to the current schema version in place, or only tell how
+    /// they would be upgraded with `dry_run`. Returns None when they are already up to date.
+    fn migrate(&mut self, _dry_run: bool) -> Result<Option<MigrationPlan>, TodoError> {
+        Ok(None)
+    }
+
+    /// Previous versions of the stored tasks, the most recent first
+    fn backups(&self) -> Result<Vec<Backup>, TodoError> {
+        Ok(Vec::new())
+    }
+
+    /// Replace the stored tasks by the ones of a backup
+    fn restore(&mut self, number: usize) -> Result<(), TodoError> {
+        Err(TodoError::Validation(format!(
+            "Backup {} of {} not found",
+            number,
+            self.location()
+        )))
+    }
+}
+
+/// Tasks of a todo list with the metadata stored along with them
+#[derive(Debug, Clone, PartialEq)]
+pub struct TodoData {
+    /// ID given to the next added task, so that the IDs of removed tasks are not reused
+    pub next_id: u32,
+    pub created_at: Option<NaiveDateTime>,
+    pub updated_at: Option<NaiveDateTime>,
+    pub tasks: Vec<Task>,
+}
+
+impl TodoData {
+    /// Data of tasks saved without metadata: the next ID follows the highest one, and the
+    /// timestamps of the list are the earliest creation and latest update of its tasks
+    pub fn from_tasks(tasks: Vec<Task>) -> Self {
+        TodoData {
+            next_id: next_free_id(&tasks),
+            created_at: tasks.iter().filter_map(|task| task.created_at).min(),
+            updated_at: tasks.iter().filter_map(|task| task.updated_at).max(),
+            tasks,
+        }
+    }
+}
+
+impl Default for TodoData {
+    fn default() -> Self {
+        TodoData::from_tasks(Vec::new())
+    }
+}
+
+/// ID following the highest hexadecimal task ID
+pub fn next_free_id(tasks: &[Task]) -> u32 {
+    tasks
+        .iter()
+        .filter_map(|task| u32::from_str_radix(&task.id, 16).ok())
+        .max()
+        .unwrap_or(0)
+        + 1
+}
+
+/// Copy of the stored tasks kept by a previous save
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    /// 1 for the most recent backup
+    pub number: usize,
+    pub path: PathBuf,
+    pub saved_at: Option<NaiveDateTime>,
+    /// Number of tasks, None when the backup cannot be read
+    pub tasks: Option<usize>,
+}
+
+/// Kind of storage used by a todo list
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    /// A JSON file holding the array of tasks
+    JsonFile,
+    /// An embedded SQLite database
+    Sqlite,
+    /// Tasks kept in memory, lost when the program exits
+    Memory,
+}
+
+impl Backend {
+    pub fn parse(text: &str) -> Result<Self, TodoError> {
+        match text.trim().to_lowercase().as_str() {
+            "json" => Ok(Backend::JsonFile),
+            "sqlite" => Ok(Backend::Sqlite),
+            "memory" => Ok(Backend::Memory),
+            _ => Err(TodoError::Validation(format!(
+                "Invalid storage backend: {}",
+                text
+            ))),
+        }
+    }
+
+    /// Backend chosen by the `TODO_BACKEND` environment variable, if set
+    pub fn from_env() -> Result<Option<Self>, TodoError> {
+        std::env::var(TODO_BACKEND_VARIABLE)
+            .ok()
+            .map(|text| Backend::parse(&text))
+            .transpose()
+    }
+
+    /// Backend matching the extension of a file: `.db`, `.sqlite` and `.sqlite3`
+    /// files are SQLite databases, any other file is a JSON file
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("db" | "sqlite" | "sqlite3") => Backend::Sqlite,
+            _ => Backend::JsonFile,
+        }
+    }
+
+    /// Extension of the files of the named lists
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Backend::Sqlite => "db",
+            Backend::JsonFile | Backend::Memory => "json",
+        }
+    }
+
+    pub fn open(&self, path: &Path) -> Box<dyn Storage> {
+        self.open_with(path, DEFAULT_BACKUPS, DEFAULT_LOCK_TIMEOUT)
+    }
+
+    pub fn open_with(
+        &self,
+        path: &Path,
+        backups: usize,
+        lock_timeout: Duration,
+    ) -> Box<dyn Storage> {
+        match self {
+            Backend::JsonFile => Box::new(
+                JsonFileStorage::new(path)
+                    .with_backups(backups)
+                    .with_lock_timeout(lock_timeout),
+            ),
+            Backend::Sqlite => Box::new(
+                SqliteStorage::new(path)
+                    .with_backups(backups)
+                    .with_lock_timeout(lock_timeout),
+            ),
+            Backend::Memory => Box::new(MemoryStorage::new()),
+        }
+    }
+}
+
+/// Number of backups set by `TODO_BACKUPS`, or the default one
+pub fn backup_count() -> Result<usize, TodoError> {
+    match std::env::var(TODO_BACKUPS_VARIABLE) {
+        Ok(text) => text
+            .trim()
+            .parse()
+            .map_err(|_| TodoError::Validation(format!("Invalid number of backups: {}", text))),
+        Err(_) => Ok(DEFAULT_BACKUPS),
+    }
+}
+
+/// Time to wait for the lock of a todo list set by `TODO_LOCK_TIMEOUT`, or the default one
+pub fn lock_timeout() -> Result<Duration, TodoError> {
+    match std::env::var(TODO_LOCK_TIMEOUT_VARIABLE) {
+        Ok(text) => text
+            .trim()
+            .parse()
+            .ok()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| TodoError::Validation(format!("Invalid lock timeout: {}", text))),
+        Err(_) => Ok(DEFAULT_LOCK_TIMEOUT),
+    }
+}
+
+/// Open the storage of a todo list file with the backend set by `TODO_BACKEND`,
+/// or the one matching the file extension
+pub fn open_storage(path: &Path) -> Result<Box<dyn Storage>, TodoError> {
+    let backend = Backend::from_env()?.unwrap_or_else(|| Backend::for_path(path));
+    Ok(backend.open_with(path, backup_count()?, lock_timeout()?))
+}
+
+/// Describe the backups of a file, reading them with `load` to count their tasks
+pub(crate) fn list_backups(
+    path: &Path,
+    load: impl Fn(&Path) -> Result<TodoData, TodoError>,
+) -> Vec<Backup> {
+    backup_paths(path)
+        .into_iter()
+        .map(|(number, path)| Backup {
+            number,
+            saved_at: std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(|modified| DateTime::<Local>::from(modified).naive_local()),
+            tasks: load(&path).ok().map(|data| data.tasks.len()),
+            path,
+        })
+        .collect()
+}
@@ -0,0 +1,149 @@
+use crate::error::TodoError;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Replace the content of a file without ever leaving it half written: the data is
+/// written and flushed to disk in a temporary file, which is then renamed over the file
+pub fn write_atomically(path: &Path, data: &[u8]) -> Result<(), TodoError> {
+    let directory = create_parent_directory(path)?;
+    let temp_path = sibling(path, ".tmp");
+    let io_error = |e: std::io::Error| {
+        TodoError::Io(format!(
+            "Failed to write to the file {} Err: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let written = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(data)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(io_error(e));
+    }
+    sync_directory(&directory);
+    Ok(())
+}
+
+/// Create the directory of a file if needed, returning it
+pub fn create_parent_directory(path: &Path) -> Result<PathBuf, TodoError> {
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    std::fs::create_dir_all(&directory).map_err(|e| {
+        TodoError::Io(format!(
+            "Failed to create the directory {} Err: {}",
+            directory.display(),
+            e
+        ))
+    })?;
+    Ok(directory)
+}
+
+/// File of the backup with the given number, 1 being the most recent: `todo.json.1.bak`
+pub fn backup_path(path: &Path, number: usize) -> PathBuf {
+    sibling(path, &format!(".{}.bak", number))
+}
+
+/// Existing backups of a file, the most recent first
+pub fn backup_paths(path: &Path) -> Vec<(usize, PathBuf)> {
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    let directory = match path.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let prefix = format!("{}.", file_name);
+    let mut backups = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let number = name.strip_prefix(&prefix)?.strip_suffix(".bak")?;
+            let number = number.parse::<usize>().ok().filter(|n| *n > 0)?;
+            Some((number, backup_path(path, number)))
+        })
+        .collect::<Vec<_>>();
+    backups.sort();
+    backups
+}
+
+/// Keep a copy of the current file as the most recent backup, shifting the older ones
+/// and dropping those beyond `count`
+pub fn rotate_backups(path: &Path, count: usize) -> Result<(), TodoError> {
+    if count == 0 || !path.exists() {
+        return Ok(());
+    }
+    let io_error = |e: std::io::Error| {
+        TodoError::Io(format!(
+            "Failed to back up the file {} Err: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    for (number, backup) in backup_paths(path).into_iter().rev() {
+        if number >= count {
+            std::fs::remove_file(&backup).map_err(io_error)?;
+        } else {
+            std::fs::rename(&backup, backup_path(path, number + 1)).map_err(io_error)?;
+        }
+    }
+    std::fs::copy(path, backup_path(path, 1)).map_err(io_error)?;
+    Ok(())
+}
+
+/// Replace a file by one of its backups. The replaced file becomes the most recent backup,
+/// so that a restore can be reverted by restoring backup 1.
+pub fn restore_backup(path: &Path, number: usize, count: usize) -> Result<(), TodoError> {
+    let backup = backup_path(path, number);
+    let data = std::fs::read(&backup).map_err(|e| {
+        TodoError::Io(format!(
+            "Failed to read the backup {} Err: {}",
+            backup.display(),
+            e
+        ))
+    })?;
+    rotate_backups(path, count.max(1))?;
+    write_atomically(path, &data)
+}
+
+/// File of the undo history saved alongside a todo list: `todo.json.history`
+pub fn history_path(path: &Path) -> PathBuf {
+    sibling(path, ".history")
+}
+
+/// File of the archived tasks of a JSON todo list: `todo.json.archive`
+pub fn archive_path(path: &Path) -> PathBuf {
+    sibling(path, ".archive")
+}
+
+/// File locked by the ToDo saving a todo list: `todo.json.lock`
+pub fn lock_path(path: &Path) -> PathBuf {
+    sibling(path, ".lock")
+}
+
+/// `todo.json` + `.tmp` gives `todo.json.tmp` in the same directory
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Make the rename itself durable. Directories cannot be opened as files on every
+/// platform, so failures are ignored.
+fn sync_directory(directory: &Path) {
+    if let Ok(directory) = File::open(directory) {
+        let _ = directory.sync_all();
+    }
+}
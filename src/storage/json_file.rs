@@ -0,0 +1,218 @@
+use crate::error::TodoError;
+use crate::model::task::Task;
+use crate::storage::backend::{
+    Backup, DEFAULT_BACKUPS, DEFAULT_LOCK_TIMEOUT, Storage, TodoData, list_backups,
+};
+use crate::storage::file::{
+    archive_path, backup_path, history_path, restore_backup, rotate_backups, write_atomically,
+};
+use crate::storage::lock::FileLock;
+use crate::storage::schema::{
+    ArchiveEnvelope, Envelope, MigrationPlan, SCHEMA_VERSION, migrate_json,
+};
+use serde_json::Value;
+use std::cell::Cell;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Stores the tasks in a JSON file, wrapped in an `Envelope` with the schema version and
+/// the metadata of the list. Saves replace the file atomically and keep its previous
+/// versions as rotating backups. The undo history is saved in a `<file>.history` file,
+/// and the archived tasks in a `<file>.archive` file.
+#[derive(Debug, Clone)]
+pub struct JsonFileStorage {
+    pub path: PathBuf,
+    /// Number of backups kept, 0 to keep none
+    pub backups: usize,
+    /// Time waited for the lock of the file
+    pub lock_timeout: Duration,
+    /// Set when the file could not be parsed, so that it is never overwritten
+    unreadable: Cell<bool>,
+}
+
+impl JsonFileStorage {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        JsonFileStorage {
+            path: path.as_ref().to_path_buf(),
+            backups: DEFAULT_BACKUPS,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            unreadable: Cell::new(false),
+        }
+    }
+
+    pub fn with_backups(mut self, backups: usize) -> Self {
+        self.backups = backups;
+        self
+    }
+
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
+    /// Read a todo list file of any schema version, returning it upgraded to the current
+    /// one with the migrations that were needed
+    fn read(path: &Path) -> Result<(Envelope, Option<MigrationPlan>), TodoError> {
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            TodoError::Io(format!(
+                "Failed to read the file '{}'. Err: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let parse_error =
+            |e: serde_json::Error| TodoError::Parse(format!("Failed to deserialize tasks: {}", e));
+
+        let value = serde_json::from_str(&data).map_err(parse_error)?;
+        let list = path.file_stem().and_then(|stem| stem.to_str());
+        let (value, plan) = migrate_json(value, list)?;
+        Ok((serde_json::from_value(value).map_err(parse_error)?, plan))
+    }
+
+    /// Read the file, remembering whether it could be parsed
+    fn read_checked(&self) -> Result<(Envelope, Option<MigrationPlan>), TodoError> {
+        let result = JsonFileStorage::read(&self.path);
+        self.unreadable
+            .set(matches!(result, Err(TodoError::Parse(_))));
+        result
+    }
+
+    /// Name of the list saved in the file, which is the name of the file
+    fn list(&self) -> Option<String> {
+        Some(self.path.file_stem()?.to_str()?.to_string())
+    }
+}
+
+impl Storage for JsonFileStorage {
+    fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    fn load(&self) -> Result<TodoData, TodoError> {
+        self.read_checked()
+            .map(|(envelope, _)| envelope.into_data())
+    }
+
+    fn save(&mut self, data: &TodoData) -> Result<(), TodoError> {
+        if self.unreadable.get() {
+            return Err(TodoError::Validation(format!(
+                "The file {} could not be read, it is left untouched instead of being overwritten",
+                self.path.display()
+            )));
+        }
+        let json_data = serde_json::to_string(&Envelope::new(data, self.list()))
+            .map_err(|e| TodoError::Serialization(format!("Failed to serialize tasks: {}", e)))?;
+
+        rotate_backups(&self.path, self.backups)?;
+        write_atomically(&self.path, json_data.as_bytes())
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn lock(&self) -> Result<Option<FileLock>, TodoError> {
+        FileLock::acquire(&self.path, self.lock_timeout).map(Some)
+    }
+
+    fn load_history(&self) -> Result<Option<Value>, TodoError> {
+        let path = history_path(&self.path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read_to_string(&path).map_err(|e| {
+            TodoError::Io(format!(
+                "Failed to read the file '{}'. Err: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| TodoError::Parse(format!("Failed to deserialize the history: {}", e)))
+    }
+
+    fn save_history(&mut self, history: &Value) -> Result<(), TodoError> {
+        write_atomically(&history_path(&self.path), history.to_string().as_bytes())
+    }
+
+    fn load_archive(&self) -> Result<Vec<Task>, TodoError> {
+        let path = archive_path(&self.path);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        let data = std::fs::read_to_string(&path).map_err(|e| {
+            TodoError::Io(format!(
+                "Failed to read the file '{}'. Err: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let archive: ArchiveEnvelope = serde_json::from_str(&data)
+            .map_err(|e| TodoError::Parse(format!("Failed to deserialize the archive: {}", e)))?;
+        if archive.version > SCHEMA_VERSION {
+            return Err(TodoError::Parse(format!(
+                "The archive was saved by a newer version of ToDo (schema version {})",
+                archive.version
+            )));
+        }
+        Ok(archive.tasks)
+    }
+
+    fn save_archive(&mut self, tasks: &[Task]) -> Result<(), TodoError> {
+        let archive = ArchiveEnvelope {
+            version: SCHEMA_VERSION,
+            tasks: tasks.to_vec(),
+        };
+        let json_data = serde_json::to_string(&archive).map_err(|e| {
+            TodoError::Serialization(format!("Failed to serialize the archive: {}", e))
+        })?;
+        write_atomically(&archive_path(&self.path), json_data.as_bytes())
+    }
+
+    fn migrate(&mut self, dry_run: bool) -> Result<Option<MigrationPlan>, TodoError> {
+        if !self.exists() {
+            return Ok(None);
+        }
+        let (_, plan) = self.read_checked()?;
+        if plan.is_none() || dry_run {
+            return Ok(plan);
+        }
+
+        // Read again under the lock, another ToDo may have migrated the file in the meantime.
+        // The file in its former version is kept as the most recent backup.
+        let _lock = self.lock()?;
+        let (envelope, plan) = self.read_checked()?;
+        if plan.is_some() {
+            let json_data = serde_json::to_string(&envelope).map_err(|e| {
+                TodoError::Serialization(format!("Failed to serialize tasks: {}", e))
+            })?;
+            rotate_backups(&self.path, self.backups)?;
+            write_atomically(&self.path, json_data.as_bytes())?;
+        }
+        Ok(plan)
+    }
+
+    fn backups(&self) -> Result<Vec<Backup>, TodoError> {
+        Ok(list_backups(&self.path, |path| {
+            JsonFileStorage::read(path).map(|(envelope, _)| envelope.into_data())
+        }))
+    }
+
+    fn restore(&mut self, number: usize) -> Result<(), TodoError> {
+        // Only a readable backup replaces the current file
+        let backup = backup_path(&self.path, number);
+        if !backup.exists() {
+            return Err(TodoError::Validation(format!(
+                "Backup {} of {} not found",
+                number,
+                self.location()
+            )));
+        }
+        JsonFileStorage::read(&backup)?;
+        let _lock = self.lock()?;
+        restore_backup(&self.path, number, self.backups)?;
+        self.unreadable.set(false);
+        Ok(())
+    }
+}
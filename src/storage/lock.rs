@@ -0,0 +1,67 @@
+use crate::error::TodoError;
+use crate::storage::file::{create_parent_directory, lock_path};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Time between two attempts to take a lock held by another process
+const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Advisory lock on a todo list, held by one ToDo at a time while it saves.
+/// The lock is taken on a `<file>.lock` file next to the todo list, as the todo list
+/// itself is replaced by each save. It is released when dropped, or when the process exits.
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    pub path: PathBuf,
+}
+
+impl FileLock {
+    /// Take the lock of a todo list file, waiting up to `timeout` for another process to release it
+    pub fn acquire(path: &Path, timeout: Duration) -> Result<FileLock, TodoError> {
+        create_parent_directory(path)?;
+        let lock_path = lock_path(path);
+        let io_error = |e: std::io::Error| {
+            TodoError::Io(format!(
+                "Failed to lock the file {} Err: {}",
+                lock_path.display(),
+                e
+            ))
+        };
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(io_error)?;
+
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => {
+                    return Ok(FileLock {
+                        file,
+                        path: lock_path,
+                    });
+                }
+                Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                    std::thread::sleep(RETRY_DELAY);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(TodoError::Locked(format!(
+                        "The todo list {} is locked by another ToDo, gave up after {} seconds",
+                        path.display(),
+                        timeout.as_secs_f32()
+                    )));
+                }
+                Err(TryLockError::Error(e)) => return Err(io_error(e)),
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
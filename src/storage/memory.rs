@@ -0,0 +1,77 @@
+use crate::error::TodoError;
+use crate::model::task::Task;
+use crate::storage::backend::{Storage, TodoData};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Keeps the tasks in memory. Clones share the same tasks, so a test can keep
+/// one to look at what a todo list saved.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    data: Rc<RefCell<Option<TodoData>>>,
+    history: Rc<RefCell<Option<Value>>>,
+    archive: Rc<RefCell<Vec<Task>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        MemoryStorage::default()
+    }
+
+    /// Storage already holding the given tasks
+    pub fn with_tasks(tasks: Vec<Task>) -> Self {
+        MemoryStorage {
+            data: Rc::new(RefCell::new(Some(TodoData::from_tasks(tasks)))),
+            ..Default::default()
+        }
+    }
+
+    /// The last saved tasks, if any
+    pub fn tasks(&self) -> Option<Vec<Task>> {
+        self.data().map(|data| data.tasks)
+    }
+
+    /// The last saved tasks with their metadata, if any
+    pub fn data(&self) -> Option<TodoData> {
+        self.data.borrow().clone()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn exists(&self) -> bool {
+        self.data.borrow().is_some()
+    }
+
+    fn load(&self) -> Result<TodoData, TodoError> {
+        self.data()
+            .ok_or_else(|| TodoError::Io("No tasks have been saved in memory".to_string()))
+    }
+
+    fn save(&mut self, data: &TodoData) -> Result<(), TodoError> {
+        *self.data.borrow_mut() = Some(data.clone());
+        Ok(())
+    }
+
+    fn load_history(&self) -> Result<Option<Value>, TodoError> {
+        Ok(self.history.borrow().clone())
+    }
+
+    fn save_history(&mut self, history: &Value) -> Result<(), TodoError> {
+        *self.history.borrow_mut() = Some(history.clone());
+        Ok(())
+    }
+
+    fn load_archive(&self) -> Result<Vec<Task>, TodoError> {
+        Ok(self.archive.borrow().clone())
+    }
+
+    fn save_archive(&mut self, tasks: &[Task]) -> Result<(), TodoError> {
+        *self.archive.borrow_mut() = tasks.to_vec();
+        Ok(())
+    }
+
+    fn location(&self) -> String {
+        "memory".to_string()
+    }
+}
@@ -0,0 +1,156 @@
+use crate::error::TodoError;
+use crate::model::task::Task;
+use crate::storage::backend::TodoData;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the schema written by this version of ToDo
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Migrations bringing stored tasks from an older schema version to the current one
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationPlan {
+    pub from: u32,
+    pub to: u32,
+    /// What each migration does, in the order they are applied
+    pub steps: Vec<String>,
+}
+
+/// Content of a JSON todo list file since version 2: the tasks with their metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub version: u32,
+    /// Name of the list when it was last saved, i.e. the name of its file
+    #[serde(default)]
+    pub list: Option<String>,
+    pub next_id: u32,
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    pub tasks: Vec<Task>,
+}
+
+impl Envelope {
+    pub fn new(data: &TodoData, list: Option<String>) -> Self {
+        Envelope {
+            version: SCHEMA_VERSION,
+            list,
+            next_id: data.next_id,
+            created_at: data.created_at,
+            updated_at: data.updated_at,
+            tasks: data.tasks.clone(),
+        }
+    }
+
+    pub fn into_data(self) -> TodoData {
+        TodoData {
+            next_id: self.next_id,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            tasks: self.tasks,
+        }
+    }
+}
+
+/// Content of the archive file of a JSON todo list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveEnvelope {
+    pub version: u32,
+    pub tasks: Vec<Task>,
+}
+
+/// Step upgrading JSON data from a schema version to the next one
+struct JsonMigration {
+    from: u32,
+    description: &'static str,
+    apply: fn(Value, Option<&str>) -> Result<Value, TodoError>,
+}
+
+/// Migrations of the JSON files, one per schema version
+const JSON_MIGRATIONS: &[JsonMigration] = &[JsonMigration {
+    from: 1,
+    description: "Wrap the array of tasks in an envelope with the schema version, \
+                  the next task ID, the list name and timestamps",
+    apply: wrap_tasks,
+}];
+
+/// Schema version of JSON data. The first version was a bare array of tasks.
+pub fn json_version(value: &Value) -> Result<u32, TodoError> {
+    match value {
+        Value::Array(_) => Ok(1),
+        Value::Object(object) => object
+            .get("version")
+            .and_then(Value::as_u64)
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| TodoError::Parse("Missing or invalid schema version".to_string())),
+        _ => Err(TodoError::Parse(
+            "Expected an array of tasks or a versioned object".to_string(),
+        )),
+    }
+}
+
+/// Upgrade JSON data to the current schema version, applying every migration from its version.
+/// Returns the upgraded data with the applied migrations, None when it was already up to date.
+pub fn migrate_json(
+    value: Value,
+    list: Option<&str>,
+) -> Result<(Value, Option<MigrationPlan>), TodoError> {
+    let from = json_version(&value)?;
+    let plan = plan(from, |version| {
+        JSON_MIGRATIONS
+            .iter()
+            .find(|migration| migration.from == version)
+            .map(|migration| migration.description)
+    })?;
+    let mut value = value;
+    for version in from..SCHEMA_VERSION {
+        if let Some(migration) = JSON_MIGRATIONS.iter().find(|m| m.from == version) {
+            value = (migration.apply)(value, list)?;
+        }
+    }
+    Ok((value, plan))
+}
+
+/// Describe the migrations from a schema version, None when it is the current one.
+/// Data saved by a newer version of ToDo cannot be read.
+pub fn plan(
+    from: u32,
+    description: impl Fn(u32) -> Option<&'static str>,
+) -> Result<Option<MigrationPlan>, TodoError> {
+    if from > SCHEMA_VERSION {
+        return Err(TodoError::Parse(format!(
+            "The todo list was saved by a newer version of ToDo (schema version {}, \
+             this version supports up to {})",
+            from, SCHEMA_VERSION
+        )));
+    }
+    if from == SCHEMA_VERSION {
+        return Ok(None);
+    }
+    let steps = (from..SCHEMA_VERSION)
+        .map(|version| {
+            description(version)
+                .map(str::to_string)
+                .ok_or_else(|| TodoError::Parse(format!("Unknown schema version: {}", version)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(MigrationPlan {
+        from,
+        to: SCHEMA_VERSION,
+        steps,
+    }))
+}
+
+/// Version 1 to 2, the metadata being deduced from the tasks
+fn wrap_tasks(value: Value, list: Option<&str>) -> Result<Value, TodoError> {
+    let tasks: Vec<Task> = serde_json::from_value(value)
+        .map_err(|e| TodoError::Parse(format!("Failed to deserialize tasks: {}", e)))?;
+    let envelope = Envelope {
+        version: 2,
+        ..Envelope::new(&TodoData::from_tasks(tasks), list.map(str::to_string))
+    };
+    serde_json::to_value(envelope)
+        .map_err(|e| TodoError::Serialization(format!("Failed to serialize tasks: {}", e)))
+}
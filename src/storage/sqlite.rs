@@ -0,0 +1,482 @@
+use crate::error::TodoError;
+use crate::model::task::Task;
+use crate::storage::backend::{
+    Backup, DEFAULT_BACKUPS, DEFAULT_LOCK_TIMEOUT, Storage, TodoData, list_backups, next_free_id,
+};
+use crate::storage::file::{backup_path, create_parent_directory, restore_backup, rotate_backups};
+use crate::storage::lock::FileLock;
+use crate::storage::schema::{MigrationPlan, SCHEMA_VERSION, plan};
+use chrono::NaiveDateTime;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Transaction, params};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time between two backups of a database made by its saves
+pub const DEFAULT_BACKUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Table of the metadata of the list, added by the schema version 2. The values are JSON.
+const CREATE_METADATA: &str = "CREATE TABLE IF NOT EXISTS metadata (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);";
+
+/// Table of the undo history, a single row holding it as JSON
+const CREATE_HISTORY: &str = "CREATE TABLE IF NOT EXISTS history (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    data TEXT NOT NULL
+);";
+
+/// Table of the archived tasks, in the same format as the `tasks` table
+const CREATE_ARCHIVE: &str = "CREATE TABLE IF NOT EXISTS archive (
+    position INTEGER PRIMARY KEY,
+    id TEXT NOT NULL UNIQUE,
+    data TEXT NOT NULL
+);";
+
+/// Stores the tasks in an embedded SQLite database, one row per task in the `tasks`
+/// table with its position in the list, its ID and its JSON representation.
+/// The `metadata` table holds the next task ID, the list name and timestamps, and the
+/// schema version is the `user_version` of the database. The undo history is kept in
+/// the `history` table and the archived tasks in the `archive` table, both created on
+/// their first save.
+/// Saves are transactions writing only the changed tasks. Copies of the database are kept
+/// as rotating backups, made by the saves at most once per `backup_interval` as the whole
+/// database is copied, and before each migration and restore.
+#[derive(Debug, Clone)]
+pub struct SqliteStorage {
+    pub path: PathBuf,
+    /// Number of backups kept, 0 to keep none
+    pub backups: usize,
+    /// Time waited for the lock of the file
+    pub lock_timeout: Duration,
+    /// Minimum time between two backups made by the saves
+    pub backup_interval: Duration,
+}
+
+impl SqliteStorage {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        SqliteStorage {
+            path: path.as_ref().to_path_buf(),
+            backups: DEFAULT_BACKUPS,
+            lock_timeout: DEFAULT_LOCK_TIMEOUT,
+            backup_interval: DEFAULT_BACKUP_INTERVAL,
+        }
+    }
+
+    pub fn with_backups(mut self, backups: usize) -> Self {
+        self.backups = backups;
+        self
+    }
+
+    pub fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
+    pub fn with_backup_interval(mut self, backup_interval: Duration) -> Self {
+        self.backup_interval = backup_interval;
+        self
+    }
+
+    /// Whether the most recent backup is older than the backup interval, or missing
+    fn backup_due(&self) -> bool {
+        std::fs::metadata(backup_path(&self.path, 1))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|elapsed| elapsed >= self.backup_interval)
+    }
+
+    fn open(&self, flags: OpenFlags) -> Result<Connection, TodoError> {
+        Connection::open_with_flags(&self.path, flags).map_err(|e| self.error(e))
+    }
+
+    fn error(&self, error: rusqlite::Error) -> TodoError {
+        TodoError::Io(format!(
+            "Failed to access the database {} Err: {}",
+            self.path.display(),
+            error
+        ))
+    }
+
+    fn has_table(&self, connection: &Connection, name: &str) -> bool {
+        connection
+            .query_row(
+                "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [name],
+                |row| row.get::<_, i64>(0),
+            )
+            .is_ok_and(|count| count > 0)
+    }
+
+    /// Schema version of the database. The first version did not set it.
+    fn version(&self, connection: &Connection) -> Result<u32, TodoError> {
+        let version = connection
+            .query_row("PRAGMA user_version", [], |row| row.get::<_, u32>(0))
+            .map_err(|e| self.error(e))?;
+        Ok(version.max(1))
+    }
+
+    /// Save the tasks in a single transaction, so that a failure leaves them untouched.
+    /// Only the changes are written when the stored tasks are known and kept their order,
+    /// otherwise the whole table is written again.
+    fn write(&mut self, data: &TodoData, stored: Option<&[Task]>) -> Result<(), TodoError> {
+        create_parent_directory(&self.path)?;
+        if self.backup_due() {
+            rotate_backups(&self.path, self.backups)?;
+        }
+        let mut connection = self.open(OpenFlags::default())?;
+        let transaction = connection.transaction().map_err(|e| self.error(e))?;
+        transaction
+            .execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS tasks (
+                    position INTEGER PRIMARY KEY,
+                    id TEXT NOT NULL UNIQUE,
+                    data TEXT NOT NULL
+                );
+                {}
+                PRAGMA user_version = {};",
+                CREATE_METADATA, SCHEMA_VERSION
+            ))
+            .map_err(|e| self.error(e))?;
+
+        let changes = stored.and_then(|stored| TaskChanges::between(stored, &data.tasks));
+        let (removed, updated, appended) = match changes {
+            Some(changes) => (changes.removed, changes.updated, changes.appended),
+            None => {
+                transaction
+                    .execute("DELETE FROM tasks", [])
+                    .map_err(|e| self.error(e))?;
+                (Vec::new(), Vec::new(), data.tasks.iter().collect())
+            }
+        };
+        for id in removed {
+            transaction
+                .execute("DELETE FROM tasks WHERE id = ?1", [id])
+                .map_err(|e| self.error(e))?;
+        }
+        for task in updated {
+            let json_data = serde_json::to_string(task).map_err(serialization_error)?;
+            transaction
+                .execute(
+                    "UPDATE tasks SET data = ?2 WHERE id = ?1",
+                    params![task.id, json_data],
+                )
+                .map_err(|e| self.error(e))?;
+        }
+        if !appended.is_empty() {
+            let last_position = transaction
+                .query_row("SELECT max(position) FROM tasks", [], |row| {
+                    row.get::<_, Option<i64>>(0)
+                })
+                .map_err(|e| self.error(e))?;
+            let mut statement = transaction
+                .prepare("INSERT INTO tasks (position, id, data) VALUES (?1, ?2, ?3)")
+                .map_err(|e| self.error(e))?;
+            for (position, task) in (last_position.map_or(0, |last| last + 1)..).zip(appended) {
+                let json_data = serde_json::to_string(task).map_err(serialization_error)?;
+                statement
+                    .execute(params![position, task.id, json_data])
+                    .map_err(|e| self.error(e))?;
+            }
+        }
+        self.write_metadata(&transaction, data)?;
+        transaction.commit().map_err(|e| self.error(e))
+    }
+
+    fn write_metadata(&self, transaction: &Transaction, data: &TodoData) -> Result<(), TodoError> {
+        let list = self
+            .path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .map(str::to_string);
+        let values = [
+            ("next_id", serde_json::to_string(&data.next_id)),
+            ("list", serde_json::to_string(&list)),
+            ("created_at", serde_json::to_string(&data.created_at)),
+            ("updated_at", serde_json::to_string(&data.updated_at)),
+        ];
+        transaction
+            .execute("DELETE FROM metadata", [])
+            .map_err(|e| self.error(e))?;
+        for (key, value) in values {
+            transaction
+                .execute(
+                    "INSERT INTO metadata (key, value) VALUES (?1, ?2)",
+                    params![key, value.map_err(serialization_error)?],
+                )
+                .map_err(|e| self.error(e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn exists(&self) -> bool {
+        self.open(OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .is_ok_and(|connection| self.has_table(&connection, "tasks"))
+    }
+
+    fn load(&self) -> Result<TodoData, TodoError> {
+        let connection = self.open(OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let version = self.version(&connection)?;
+        plan(version, sqlite_migration)?;
+        let mut statement = connection
+            .prepare("SELECT data FROM tasks ORDER BY position")
+            .map_err(|e| self.error(e))?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| self.error(e))?;
+
+        let mut tasks = Vec::new();
+        for data in rows {
+            let data = data.map_err(|e| self.error(e))?;
+            let task = serde_json::from_str(&data).map_err(parse_error)?;
+            tasks.push(task);
+        }
+        if version < 2 {
+            return Ok(TodoData::from_tasks(tasks));
+        }
+
+        let metadata = |key: &str| -> Result<Option<String>, TodoError> {
+            connection
+                .query_row("SELECT value FROM metadata WHERE key = ?1", [key], |row| {
+                    row.get(0)
+                })
+                .optional()
+                .map_err(|e| self.error(e))
+        };
+        let next_id = match metadata("next_id")? {
+            Some(value) => serde_json::from_str(&value).map_err(parse_error)?,
+            None => next_free_id(&tasks),
+        };
+        let timestamp = |key: &str| -> Result<Option<NaiveDateTime>, TodoError> {
+            match metadata(key)? {
+                Some(value) => serde_json::from_str(&value).map_err(parse_error),
+                None => Ok(None),
+            }
+        };
+        Ok(TodoData {
+            next_id,
+            created_at: timestamp("created_at")?,
+            updated_at: timestamp("updated_at")?,
+            tasks,
+        })
+    }
+
+    fn save(&mut self, data: &TodoData) -> Result<(), TodoError> {
+        self.write(data, None)
+    }
+
+    fn save_changes(&mut self, data: &TodoData, stored: &[Task]) -> Result<(), TodoError> {
+        self.write(data, Some(stored))
+    }
+
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn lock(&self) -> Result<Option<FileLock>, TodoError> {
+        FileLock::acquire(&self.path, self.lock_timeout).map(Some)
+    }
+
+    fn load_history(&self) -> Result<Option<Value>, TodoError> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let connection = self.open(OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        if !self.has_table(&connection, "history") {
+            return Ok(None);
+        }
+        let data = connection
+            .query_row("SELECT data FROM history WHERE id = 1", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()
+            .map_err(|e| self.error(e))?;
+        data.map(|data| {
+            serde_json::from_str(&data)
+                .map_err(|e| TodoError::Parse(format!("Failed to deserialize the history: {}", e)))
+        })
+        .transpose()
+    }
+
+    fn save_history(&mut self, history: &Value) -> Result<(), TodoError> {
+        let connection = self.open(OpenFlags::default())?;
+        connection
+            .execute_batch(CREATE_HISTORY)
+            .map_err(|e| self.error(e))?;
+        connection
+            .execute(
+                "INSERT OR REPLACE INTO history (id, data) VALUES (1, ?1)",
+                [history.to_string()],
+            )
+            .map_err(|e| self.error(e))?;
+        Ok(())
+    }
+
+    fn load_archive(&self) -> Result<Vec<Task>, TodoError> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let connection = self.open(OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        if !self.has_table(&connection, "archive") {
+            return Ok(Vec::new());
+        }
+        let mut statement = connection
+            .prepare("SELECT data FROM archive ORDER BY position")
+            .map_err(|e| self.error(e))?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| self.error(e))?;
+        let mut tasks = Vec::new();
+        for data in rows {
+            let data = data.map_err(|e| self.error(e))?;
+            tasks.push(serde_json::from_str(&data).map_err(parse_error)?);
+        }
+        Ok(tasks)
+    }
+
+    fn save_archive(&mut self, tasks: &[Task]) -> Result<(), TodoError> {
+        create_parent_directory(&self.path)?;
+        let mut connection = self.open(OpenFlags::default())?;
+        let transaction = connection.transaction().map_err(|e| self.error(e))?;
+        transaction
+            .execute_batch(&format!("{}\nDELETE FROM archive;", CREATE_ARCHIVE))
+            .map_err(|e| self.error(e))?;
+        {
+            let mut statement = transaction
+                .prepare("INSERT INTO archive (position, id, data) VALUES (?1, ?2, ?3)")
+                .map_err(|e| self.error(e))?;
+            for (position, task) in tasks.iter().enumerate() {
+                let json_data = serde_json::to_string(task).map_err(serialization_error)?;
+                statement
+                    .execute(params![position as i64, task.id, json_data])
+                    .map_err(|e| self.error(e))?;
+            }
+        }
+        transaction.commit().map_err(|e| self.error(e))
+    }
+
+    fn migrate(&mut self, dry_run: bool) -> Result<Option<MigrationPlan>, TodoError> {
+        if !self.exists() {
+            return Ok(None);
+        }
+        let version = self.version(&self.open(OpenFlags::SQLITE_OPEN_READ_ONLY)?)?;
+        let migrations = plan(version, sqlite_migration)?;
+        if migrations.is_none() || dry_run {
+            return Ok(migrations);
+        }
+
+        // Check again under the lock, another ToDo may have migrated the database in the
+        // meantime. The database in its former version is kept as the most recent backup.
+        let _lock = self.lock()?;
+        let data = self.load()?;
+        let version = self.version(&self.open(OpenFlags::SQLITE_OPEN_READ_ONLY)?)?;
+        let migrations = plan(version, sqlite_migration)?;
+        if migrations.is_none() {
+            return Ok(None);
+        }
+        rotate_backups(&self.path, self.backups)?;
+        let mut connection = self.open(OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+        let transaction = connection.transaction().map_err(|e| self.error(e))?;
+        if version < 2 {
+            transaction
+                .execute_batch(CREATE_METADATA)
+                .map_err(|e| self.error(e))?;
+            self.write_metadata(&transaction, &data)?;
+        }
+        transaction
+            .execute_batch(&format!("PRAGMA user_version = {};", SCHEMA_VERSION))
+            .map_err(|e| self.error(e))?;
+        transaction.commit().map_err(|e| self.error(e))?;
+        Ok(migrations)
+    }
+
+    fn backups(&self) -> Result<Vec<Backup>, TodoError> {
+        Ok(list_backups(&self.path, |path| {
+            SqliteStorage::new(path).load()
+        }))
+    }
+
+    fn restore(&mut self, number: usize) -> Result<(), TodoError> {
+        // Only a readable backup replaces the current database
+        let backup = backup_path(&self.path, number);
+        if !backup.exists() {
+            return Err(TodoError::Validation(format!(
+                "Backup {} of {} not found",
+                number,
+                self.location()
+            )));
+        }
+        SqliteStorage::new(&backup).load()?;
+        let _lock = self.lock()?;
+        restore_backup(&self.path, number, self.backups)
+    }
+}
+
+/// Rows of the `tasks` table to write to turn the stored tasks into the saved ones
+struct TaskChanges<'a> {
+    removed: Vec<&'a str>,
+    updated: Vec<&'a Task>,
+    /// Tasks added after the stored ones
+    appended: Vec<&'a Task>,
+}
+
+impl<'a> TaskChanges<'a> {
+    /// None when the kept tasks changed order or tasks were added before them, as the
+    /// positions of the rows would have to change
+    fn between(stored: &'a [Task], tasks: &'a [Task]) -> Option<Self> {
+        let stored_by_id = stored
+            .iter()
+            .map(|task| (task.id.as_str(), task))
+            .collect::<BTreeMap<_, _>>();
+        let ids = tasks
+            .iter()
+            .map(|task| task.id.as_str())
+            .collect::<BTreeSet<_>>();
+        let kept = tasks
+            .iter()
+            .take_while(|task| stored_by_id.contains_key(task.id.as_str()))
+            .collect::<Vec<_>>();
+        let appended = tasks[kept.len()..].iter().collect::<Vec<_>>();
+        let in_order = stored
+            .iter()
+            .filter(|task| ids.contains(task.id.as_str()))
+            .map(|task| task.id.as_str())
+            .eq(kept.iter().map(|task| task.id.as_str()));
+        if !in_order {
+            return None;
+        }
+        Some(TaskChanges {
+            removed: stored
+                .iter()
+                .filter(|task| !ids.contains(task.id.as_str()))
+                .map(|task| task.id.as_str())
+                .collect(),
+            updated: kept
+                .into_iter()
+                .filter(|task| stored_by_id[task.id.as_str()] != *task)
+                .collect(),
+            appended,
+        })
+    }
+}
+
+/// Migrations of the databases, one per schema version
+fn sqlite_migration(from: u32) -> Option<&'static str> {
+    match from {
+        1 => Some("Add the metadata table with the next task ID, the list name and timestamps"),
+        _ => None,
+    }
+}
+
+fn parse_error(error: serde_json::Error) -> TodoError {
+    TodoError::Parse(format!("Failed to deserialize tasks: {}", error))
+}
+
+fn serialization_error(error: serde_json::Error) -> TodoError {
+    TodoError::Serialization(format!("Failed to serialize tasks: {}", error))
+}
@@ -1,14 +1,17 @@
 use rustyline::Editor;
 use rustyline::history::DefaultHistory;
 
-use crate::{service::manager::Manager, ui::cli_argument_parser::trait_cli_displayer::TraitCliDisplayer};
 use crate::ui::console_ui::generic_console_displayer::GenericConsoleDisplayer;
 use crate::ui::displayer::Displayer;
 use crate::ui::menu_option::MenuOption;
-use std::io::{BufReader, Stdin, Stdout};
+use crate::{
+    service::manager::Manager, ui::cli_argument_parser::trait_cli_displayer::TraitCliDisplayer,
+};
+use std::io::{BufReader, IsTerminal, Stdin, Stdout, Write};
 
 pub struct CliDisplayer {
     inner: GenericConsoleDisplayer<BufReader<Stdin>, Stdout, Editor<(), DefaultHistory>>,
+    quiet: bool,
 }
 
 impl CliDisplayer {
@@ -19,24 +22,40 @@ impl CliDisplayer {
                 std::io::stdout(),
                 Editor::<(), DefaultHistory>::new().expect("Failed to create editor"),
             ),
+            quiet: false,
+        }
+    }
+
+    /// Displayer dropping every notification, to keep structured output free of messages
+    pub fn quiet() -> Self {
+        CliDisplayer {
+            quiet: true,
+            ..CliDisplayer::new()
         }
     }
 }
 
+impl Default for CliDisplayer {
+    fn default() -> Self {
+        CliDisplayer::new()
+    }
+}
+
 impl Displayer for CliDisplayer {
     fn new() -> Self {
         CliDisplayer::new()
     }
 
-    fn run(&mut self, _manager: &mut Manager) {
-        ()
-    }
+    fn run(&mut self, _manager: &mut Manager) {}
 
     fn display(&mut self) -> Result<MenuOption, String> {
         Ok(MenuOption::Exit)
     }
 
     fn notify(&mut self, message: &str) -> Result<(), String> {
+        if self.quiet {
+            return Ok(());
+        }
         self.inner.notify(message)
     }
 
@@ -47,10 +66,26 @@ impl Displayer for CliDisplayer {
 
 impl TraitCliDisplayer for CliDisplayer {
     fn handle_add_task(&mut self, manager: &mut Manager) {
-        let _ = self.inner.handle_add_task(manager);
+        if let Err(e) = self.inner.handle_add_task(manager) {
+            let _ = self.inner.notify(&format!("Error: {}", e));
+        }
     }
 
     fn handle_edit_task(&mut self, manager: &mut Manager) {
-        let _ = self.inner.handle_edit_task(manager);
+        if let Err(e) = self.inner.handle_edit_task(manager) {
+            let _ = self.inner.notify(&format!("Error: {}", e));
+        }
+    }
+
+    fn write_output(&mut self, output: &str) -> Result<(), String> {
+        let mut stdout = std::io::stdout().lock();
+        writeln!(stdout, "{}", output).map_err(|e| format!("Failed to write: {}", e))?;
+        stdout
+            .flush()
+            .map_err(|e| format!("Failed to flush: {}", e))
+    }
+
+    fn is_terminal(&self) -> bool {
+        std::io::stdout().is_terminal()
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,34 @@
+use crate::error::TodoError;
+use crate::exchange::csv::{self, ColumnMap};
+use crate::exchange::format::ExchangeFormat;
+use crate::exchange::ical;
+use crate::exchange::markdown::{self, Grouping};
+use crate::exchange::taskwarrior;
+use crate::exchange::todo_txt;
+use crate::model::due::Due;
 use crate::model::priority::Priority;
+use crate::model::recurrence::Recurrence;
+use crate::model::search::{Search, SearchOptions};
+use crate::model::selection::{Filter, select_tasks};
+use crate::model::sort::{SortKey, sort_tasks};
+use crate::model::tags::normalize_tag;
+use crate::model::task::{Task, TaskOptions};
+use crate::model::timestamp::{format_timestamp, parse_since};
+use crate::model::todo_list::TodoList;
+use crate::service::archive::archivable_tasks;
+use crate::service::history::history_line;
+use crate::service::lists::{ListDirectory, TODO_FILE_VARIABLE, resolve_file};
 use crate::service::manager::{Manager, ManagerTrait};
+use crate::storage::schema::SCHEMA_VERSION;
+use crate::ui::cli_argument_parser::output_format::{
+    ActionRecord, BackupRecord, ErrorRecord, HistoryRecord, ListRecord, MigrationRecord,
+    OutputFormat, Record, TagRecord, TaskRecord,
+};
 use crate::ui::cli_argument_parser::trait_cli_displayer::TraitCliDisplayer;
+use chrono::{Local, NaiveDateTime};
 use clap::{Parser, Subcommand};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "ToDo", version = "1.0")]
@@ -10,6 +37,30 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<CliCommand>,
+
+    #[arg(
+        long = "output",
+        value_enum,
+        global = true,
+        default_value_t = OutputFormat::Text,
+        help = "Format of the command results"
+    )]
+    pub output: OutputFormat,
+
+    #[arg(
+        long = "file",
+        global = true,
+        help = "File of the todo list to use, instead of TODO_FILE or the named lists"
+    )]
+    pub file: Option<PathBuf>,
+
+    #[arg(
+        long = "list",
+        global = true,
+        conflicts_with = "file",
+        help = "Name of the todo list to use, stored in the data directory"
+    )]
+    pub list: Option<String>,
 }
 
 #[derive(Subcommand, Clone, Debug, PartialEq)]
@@ -21,6 +72,27 @@ pub enum CliCommand {
 
         #[arg(short = 'p', long = "pri", value_enum, default_value_t = Priority::Low, help = "Priority of the new task")]
         priority: Priority,
+
+        #[arg(
+            long = "due",
+            help = "Due date of the new task (e.g. 2026-11-03, tomorrow, +3d, next fri 17:00)"
+        )]
+        due: Option<String>,
+
+        #[arg(long = "parent", help = "ID of the task the new one is a subtask of")]
+        parent: Option<String>,
+
+        #[arg(
+            long = "depends-on",
+            help = "ID of a task that must be completed before the new one (repeatable)"
+        )]
+        depends_on: Vec<String>,
+
+        #[arg(
+            long = "every",
+            help = "Repeat the task when completed (daily, weekly, mon,thu, monthly 15, 3d)"
+        )]
+        every: Option<String>,
     },
 
     #[command(about = "List all existing tasks")]
@@ -41,24 +113,195 @@ pub enum CliCommand {
             help = "Filter the list of tasks by completed status"
         )]
         completed: Option<bool>,
+
+        #[arg(
+            long = "overdue",
+            help = "Show only the pending tasks past their due date"
+        )]
+        overdue: bool,
+
+        #[arg(
+            long = "due-before",
+            help = "Show only the tasks due before the given date"
+        )]
+        due_before: Option<String>,
+
+        #[arg(
+            long = "due-after",
+            help = "Show only the tasks due after the given date"
+        )]
+        due_after: Option<String>,
+
+        #[arg(
+            short = 't',
+            long = "tag",
+            help = "Show only the tasks with the given tag (repeatable)"
+        )]
+        tags: Vec<String>,
+
+        #[arg(
+            long = "not-tag",
+            help = "Hide the tasks with the given tag (repeatable)"
+        )]
+        not_tags: Vec<String>,
+
+        #[arg(
+            long = "any-tag",
+            help = "Show the tasks having any of the --tag values instead of all of them"
+        )]
+        any_tag: bool,
+
+        #[arg(
+            long = "ready",
+            conflicts_with = "blocked",
+            help = "Show only the pending tasks whose dependencies are all completed"
+        )]
+        ready: bool,
+
+        #[arg(
+            long = "blocked",
+            help = "Show only the pending tasks waiting on an open dependency"
+        )]
+        blocked: bool,
+
+        #[arg(
+            long = "created-since",
+            help = "Show only the tasks created since the given age or date (e.g. 7d, 12h, 2026-10-01)"
+        )]
+        created_since: Option<String>,
+
+        #[arg(
+            long = "updated-since",
+            help = "Show only the tasks updated since the given age or date"
+        )]
+        updated_since: Option<String>,
+
+        #[arg(
+            long = "completed-since",
+            help = "Show only the tasks completed since the given age or date"
+        )]
+        completed_since: Option<String>,
+
+        #[arg(
+            long = "sort",
+            value_enum,
+            value_delimiter = ',',
+            help = "Order the tasks by the given keys, e.g. priority,due (ties broken by the next key)"
+        )]
+        sort: Vec<SortKey>,
+
+        #[arg(long = "reverse", help = "Reverse the order of the listed tasks")]
+        reverse: bool,
+
+        #[arg(
+            short = 'v',
+            long = "verbose",
+            help = "Show when each task was created, updated and completed"
+        )]
+        verbose: bool,
+
+        #[arg(long = "archived", help = "List the archived tasks instead")]
+        archived: bool,
     },
 
-    #[command(alias = "toggle", aliases = ["check"], about = "Change the completed/uncompleted status of a task")]
+    #[command(alias = "toggle", aliases = ["check"], about = "Change the completed/uncompleted status of tasks")]
     ToggleStatus {
-        #[arg(short = 'i', long = "id")]
-        id: String,
+        #[arg(
+            short = 'i',
+            long = "id",
+            value_delimiter = ',',
+            required_unless_present = "filter",
+            help = "ID or range of IDs (e.g. 3-9) of the tasks to toggle (repeatable)"
+        )]
+        ids: Vec<String>,
+
+        #[arg(
+            long = "where",
+            value_name = "CONDITIONS",
+            help = "Toggle the tasks matching all the conditions (e.g. priority=low,completed=true)"
+        )]
+        filter: Option<String>,
+
+        #[arg(
+            short = 'f',
+            long = "force",
+            help = "Complete the tasks even if their dependencies are still open"
+        )]
+        force: bool,
+
+        #[arg(long = "dry-run", help = "Only show the tasks that would be toggled")]
+        dry_run: bool,
     },
 
-    #[command(about = "Remove an existing task")]
+    #[command(about = "Remove existing tasks")]
     Remove {
-        #[arg(short = 'i', long = "id")]
-        id: String,
+        #[arg(
+            short = 'i',
+            long = "id",
+            value_delimiter = ',',
+            required_unless_present = "filter",
+            help = "ID or range of IDs (e.g. 3-9) of the tasks to remove (repeatable)"
+        )]
+        ids: Vec<String>,
+
+        #[arg(
+            long = "where",
+            value_name = "CONDITIONS",
+            help = "Remove the tasks matching all the conditions (e.g. priority=low,completed=true)"
+        )]
+        filter: Option<String>,
+
+        #[arg(long = "dry-run", help = "Only show the tasks that would be removed")]
+        dry_run: bool,
+    },
+
+    #[command(about = "Move the completed tasks to the archive")]
+    Archive {
+        #[arg(
+            long = "older-than",
+            value_name = "AGE",
+            help = "Archive only the tasks completed before the given age or date (e.g. 30 for 30 days, 2w, 2026-10-01)"
+        )]
+        older_than: Option<String>,
+
+        #[arg(long = "dry-run", help = "Only show the tasks that would be archived")]
+        dry_run: bool,
     },
 
-    #[command(about = "Edit an existing task")]
+    #[command(about = "Move archived tasks back to the todo list")]
+    Unarchive {
+        #[arg(
+            short = 'i',
+            long = "id",
+            value_delimiter = ',',
+            required = true,
+            help = "ID or range of IDs of the archived tasks to restore with their subtasks (repeatable)"
+        )]
+        ids: Vec<String>,
+    },
+
+    #[command(about = "Edit existing tasks")]
     Edit {
-        #[arg(short = 'i', long = "id", help = "ID of the TODO item to edit")]
-        id: Option<String>,
+        #[arg(
+            short = 'i',
+            long = "id",
+            value_delimiter = ',',
+            help = "ID or range of IDs (e.g. 3-9) of the TODO items to edit (repeatable)"
+        )]
+        ids: Vec<String>,
+
+        #[arg(
+            long = "where",
+            value_name = "CONDITIONS",
+            help = "Edit the TODO items matching all the conditions (e.g. priority=low,completed=true)"
+        )]
+        filter: Option<String>,
+
+        #[arg(
+            long = "dry-run",
+            help = "Only show the changes, without editing the TODO items"
+        )]
+        dry_run: bool,
 
         #[arg(long = "pat", help = "Pattern to search in the TODO description")]
         pattern: Option<String>,
@@ -72,192 +315,1409 @@ pub enum CliCommand {
             help = "Priority of the TODO item (Low, Medium, High)"
         )]
         priority: Option<Priority>,
+
+        #[arg(
+            long = "due",
+            help = "New due date of the TODO item (e.g. 2026-11-03, tomorrow, +3d, next fri 17:00)"
+        )]
+        due: Option<String>,
+
+        #[arg(
+            long = "no-due",
+            conflicts_with = "due",
+            help = "Remove the due date of the TODO item"
+        )]
+        no_due: bool,
+
+        #[arg(
+            short = 't',
+            long = "tag",
+            help = "Add a tag to the TODO item (repeatable)"
+        )]
+        tags: Vec<String>,
+
+        #[arg(long = "untag", help = "Remove a tag from the TODO item (repeatable)")]
+        untags: Vec<String>,
+
+        #[arg(
+            long = "depends-on",
+            help = "Add a dependency on the task with the given ID (repeatable)"
+        )]
+        depends_on: Vec<String>,
+
+        #[arg(
+            long = "no-depends-on",
+            help = "Remove the dependency on the task with the given ID (repeatable)"
+        )]
+        no_depends_on: Vec<String>,
+
+        #[arg(
+            long = "every",
+            help = "Repeat the TODO item when completed (daily, weekly, mon,thu, monthly 15, 3d)"
+        )]
+        every: Option<String>,
+
+        #[arg(
+            long = "no-every",
+            conflicts_with = "every",
+            help = "Stop repeating the TODO item"
+        )]
+        no_every: bool,
+    },
+
+    #[command(about = "List every tag with its number of open and closed tasks")]
+    Tags,
+
+    #[command(about = "Search the tasks whose description matches a text")]
+    Search {
+        #[arg(help = "Text to search in the task descriptions")]
+        text: String,
+
+        #[arg(
+            long = "ignore-case",
+            help = "Ignore upper and lower case when matching"
+        )]
+        ignore_case: bool,
+
+        #[arg(short = 'w', long = "word", help = "Match whole words only")]
+        whole_word: bool,
+
+        #[arg(
+            short = 'r',
+            long = "regex",
+            help = "Interpret the text as a regular expression"
+        )]
+        regex: bool,
+
+        #[arg(
+            short = 'p',
+            long = "pri",
+            value_enum,
+            help = "Filter the found tasks by priority"
+        )]
+        priority: Option<Priority>,
+
+        #[arg(
+            short = 'c',
+            long = "com",
+            help = "Filter the found tasks by completed status"
+        )]
+        completed: Option<bool>,
+
+        #[arg(
+            long = "ids-only",
+            help = "Print only the comma separated IDs of the found tasks, to reuse them in other commands"
+        )]
+        ids_only: bool,
+
+        #[arg(long = "no-highlight", help = "Do not highlight the matches")]
+        no_highlight: bool,
+    },
+
+    #[command(about = "Undo the last action, even one done by a previous command")]
+    Undo {
+        /// Go back to the point before the action at this position of `history`,
+        /// undoing it and every later action
+        #[arg(long, value_name = "N", conflicts_with = "action")]
+        to: Option<usize>,
+
+        /// Undo only the action at this position of `history`,
+        /// provided no later action changed its tasks
+        #[arg(long, value_name = "N")]
+        action: Option<usize>,
+    },
+
+    #[command(about = "Redo the last undone action")]
+    Redo,
+
+    #[command(about = "Show the actions that can be undone and redone")]
+    History,
+
+    #[command(about = "List the backups of the todo list, or restore one of them")]
+    Restore {
+        #[arg(help = "Number of the backup to restore, 1 being the most recent")]
+        backup: Option<usize>,
+    },
+
+    #[command(about = "Upgrade the todo list to the current schema version")]
+    Migrate {
+        #[arg(
+            long = "dry-run",
+            help = "Only tell which migrations would be applied, without changing the todo list"
+        )]
+        dry_run: bool,
+    },
+
+    #[command(about = "Enumerate the named todo lists, or create, rename and delete them")]
+    Lists {
+        #[command(subcommand)]
+        action: Option<ListsCommand>,
+    },
+
+    #[command(about = "Write the tasks to a file of another format, e.g. todo.txt")]
+    Export {
+        #[arg(
+            long = "format",
+            value_enum,
+            help = "Format of the file, guessed from its extension when not given"
+        )]
+        format: Option<ExchangeFormat>,
+
+        #[arg(
+            value_name = "FILE",
+            help = "File to write, the standard output when not given or `-`"
+        )]
+        path: Option<PathBuf>,
+
+        #[arg(
+            long = "map",
+            help = "CSV columns of the task fields, e.g. description=Title,priority=Prio"
+        )]
+        map: Option<String>,
+
+        #[arg(
+            long = "group-by",
+            value_enum,
+            help = "Headings of the Markdown checklist, by priority when not given"
+        )]
+        group_by: Option<Grouping>,
+    },
+
+    #[command(about = "Add the tasks of a file of another format, e.g. todo.txt")]
+    Import {
+        #[arg(
+            long = "format",
+            value_enum,
+            help = "Format of the file, guessed from its extension when not given"
+        )]
+        format: Option<ExchangeFormat>,
+
+        #[arg(value_name = "FILE", help = "File to read, `-` for the standard input")]
+        path: PathBuf,
+
+        #[arg(
+            long = "map",
+            help = "CSV columns of the task fields, by name or number from 1, e.g. description=Title,priority=2"
+        )]
+        map: Option<String>,
+
+        #[arg(long = "dry-run", help = "Only show the tasks that would be imported")]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug, PartialEq)]
+pub enum ListsCommand {
+    #[command(about = "Create a new empty list")]
+    Create {
+        #[arg(help = "Name of the new list")]
+        name: String,
+    },
+
+    #[command(about = "Rename an existing list")]
+    Rename {
+        #[arg(help = "Current name of the list")]
+        name: String,
+
+        #[arg(help = "New name of the list")]
+        new_name: String,
+    },
+
+    #[command(about = "Delete an existing list")]
+    Delete {
+        #[arg(help = "Name of the list to delete")]
+        name: String,
+
+        #[arg(
+            short = 'f',
+            long = "force",
+            help = "Delete the list even if it still has tasks"
+        )]
+        force: bool,
     },
 }
 
 impl Cli {
+    /// Execute a command, notifying the error it fails with, if any
     pub fn evaluate_command(
         &self,
         command: CliCommand,
         manager: &mut Manager,
         displayer: &mut dyn TraitCliDisplayer,
-    ) {
+    ) -> Result<(), TodoError> {
+        let result = self.execute_command(command, manager, displayer);
+        if let Err(e) = &result {
+            self.report_error(e, displayer);
+        }
+        result
+    }
+
+    /// Manage the named lists stored in the given directory, notifying the error
+    /// it fails with, if any
+    pub fn evaluate_lists(
+        &self,
+        action: Option<ListsCommand>,
+        lists: &ListDirectory,
+        displayer: &mut dyn TraitCliDisplayer,
+    ) -> Result<(), TodoError> {
+        let result = self.execute_lists(action, lists, displayer);
+        if let Err(e) = &result {
+            self.report_error(e, displayer);
+        }
+        result
+    }
+
+    /// File of the todo list selected by the `--file` and `--list` arguments
+    /// or the `TODO_FILE` environment variable
+    pub fn todo_file(&self) -> Result<PathBuf, TodoError> {
+        let env_file = std::env::var_os(TODO_FILE_VARIABLE).map(PathBuf::from);
+        resolve_file(self.file.as_deref(), self.list.as_deref(), env_file)
+    }
+
+    /// Notify an error in the selected output format
+    pub fn report_error(&self, error: &TodoError, displayer: &mut dyn TraitCliDisplayer) {
+        let _ = match self.output.render_one(&ErrorRecord::from(error)) {
+            Ok(Some(output)) => displayer.write_output(&output),
+            _ => displayer.notify(&format!("Error: {}", error)),
+        };
+    }
+
+    fn execute_command(
+        &self,
+        command: CliCommand,
+        manager: &mut Manager,
+        displayer: &mut dyn TraitCliDisplayer,
+    ) -> Result<(), TodoError> {
         match command {
             CliCommand::Add {
                 description,
                 priority,
+                due,
+                parent,
+                depends_on,
+                every,
             } => match description {
-                Some(desc) => {
-                    manager.add_task(desc.as_ref(), &priority);
-                    displayer
-                        .notify("Task added successfully.")
-                        .expect("Failed to notify addition of a task.");
+                None if self.output != OutputFormat::Text => {
+                    return Err(Cli::interactive_only("add", "--desc"));
                 }
-                None => {
-                    displayer.handle_add_task(manager);
+                Some(desc) => {
+                    let due = Cli::parse_due(due)?;
+                    let recurrence = Cli::parse_recurrence(every)?;
+                    if let Some(parent) = &parent {
+                        Cli::check_task(parent, manager)?;
+                    }
+                    for dependency in &depends_on {
+                        Cli::check_task(dependency, manager)?;
+                    }
+                    let id = manager.add_task_with_options(
+                        desc.as_ref(),
+                        &priority,
+                        TaskOptions {
+                            due,
+                            parent,
+                            depends_on: depends_on.into_iter().collect(),
+                            recurrence,
+                            ..Default::default()
+                        },
+                    )?;
+                    self.notify_text(
+                        displayer,
+                        &format!("Task added successfully with ID: {}.", id),
+                    )?;
+                    self.write_action(displayer, "add", &id)?;
                 }
+                None => displayer.handle_add_task(manager),
             },
             CliCommand::List {
                 priority,
                 completed,
+                overdue,
+                due_before,
+                due_after,
+                tags,
+                not_tags,
+                any_tag,
+                ready,
+                blocked,
+                created_since,
+                updated_since,
+                completed_since,
+                sort,
+                reverse,
+                verbose,
+                archived,
             } => {
-                let tasks = manager.get_tasks();
-                let mut filtered_tasks = match priority {
-                    None => tasks.iter().collect::<Vec<_>>(),
-                    Some(p) => tasks
-                        .iter()
-                        .filter(|task| task.priority == p)
-                        .collect::<Vec<_>>(),
+                let due_before = Cli::parse_due(due_before)?;
+                let due_after = Cli::parse_due(due_after)?;
+                let tags = Cli::parse_tags(tags)?;
+                let not_tags = Cli::parse_tags(not_tags)?;
+                let now = Local::now().naive_local();
+                let created_since = Cli::parse_since(created_since, now)?;
+                let updated_since = Cli::parse_since(updated_since, now)?;
+                let completed_since = Cli::parse_since(completed_since, now)?;
+
+                let archived_tasks = if archived {
+                    manager.todo_list.archived_tasks()?
+                } else {
+                    Vec::new()
                 };
-                if let Some(completed) = completed {
-                    filtered_tasks = filtered_tasks
-                        .into_iter()
-                        .filter(|task| task.completed == completed)
-                        .collect();
+                let tasks = if archived {
+                    &archived_tasks
+                } else {
+                    manager.get_tasks()
+                };
+                let mut filtered_tasks = tasks
+                    .iter()
+                    .filter(|task| priority.is_none_or(|p| task.priority == p))
+                    .filter(|task| completed.is_none_or(|c| task.completed == c))
+                    .filter(|task| !overdue || task.is_overdue(now))
+                    .filter(|task| {
+                        due_before
+                            .is_none_or(|before| task.due.is_some_and(|due| due.date < before.date))
+                    })
+                    .filter(|task| {
+                        due_after
+                            .is_none_or(|after| task.due.is_some_and(|due| due.date > after.date))
+                    })
+                    .filter(|task| {
+                        tags.is_empty()
+                            || if any_tag {
+                                tags.iter().any(|tag| task.tags.contains(tag))
+                            } else {
+                                tags.is_subset(&task.tags)
+                            }
+                    })
+                    .filter(|task| task.tags.is_disjoint(&not_tags))
+                    .filter(|task| {
+                        !(ready || blocked)
+                            || (!task.completed && manager.is_blocked(&task.id) == blocked)
+                    })
+                    .filter(|task| {
+                        created_since
+                            .is_none_or(|since| task.created_at.is_some_and(|t| t >= since))
+                    })
+                    .filter(|task| {
+                        updated_since
+                            .is_none_or(|since| task.updated_at.is_some_and(|t| t >= since))
+                    })
+                    .filter(|task| {
+                        completed_since
+                            .is_none_or(|since| task.completed_at.is_some_and(|t| t >= since))
+                    })
+                    .collect::<Vec<_>>();
+                if !sort.is_empty() || reverse {
+                    sort_tasks(&mut filtered_tasks, &sort, reverse);
+                }
+                if self.output != OutputFormat::Text {
+                    return self.write_tasks(displayer, &filtered_tasks);
                 }
+
+                let mut filters = Vec::new();
+                if let Some(p) = priority {
+                    filters.push(format!("priority {:?}", p));
+                }
+                if let Some(c) = completed {
+                    filters.push(format!("completed = {}", c));
+                }
+                if overdue {
+                    filters.push("overdue = true".to_string());
+                }
+                if let Some(before) = due_before {
+                    filters.push(format!("due before {}", before.date));
+                }
+                if let Some(after) = due_after {
+                    filters.push(format!("due after {}", after.date));
+                }
+                if !tags.is_empty() {
+                    let separator = if any_tag { " or " } else { " and " };
+                    let tags = tags.into_iter().collect::<Vec<_>>();
+                    filters.push(format!("tags {}", tags.join(separator)));
+                }
+                if !not_tags.is_empty() {
+                    let not_tags = not_tags.into_iter().collect::<Vec<_>>();
+                    filters.push(format!("excluded tags {}", not_tags.join(", ")));
+                }
+                if ready {
+                    filters.push("ready = true".to_string());
+                }
+                if blocked {
+                    filters.push("blocked = true".to_string());
+                }
+                for (label, since) in [
+                    ("created", created_since),
+                    ("updated", updated_since),
+                    ("completed", completed_since),
+                ] {
+                    if let Some(since) = since {
+                        filters.push(format!("{} since {}", label, format_timestamp(since)));
+                    }
+                }
+                let filters = if filters.is_empty() {
+                    String::new()
+                } else {
+                    format!(" with {}", filters.join(" and "))
+                };
+
+                let kind = if archived { "archived tasks" } else { "tasks" };
                 if filtered_tasks.is_empty() {
-                    let message = match (priority, completed) {
-                        (Some(p), Some(c)) => {
-                            format!("No tasks found with priority {:?} and completed = {}", p, c)
-                        }
-                        (Some(p), None) => format!("No tasks found with priority {:?}", p),
-                        (None, Some(c)) => format!("No tasks found with completed = {}", c),
-                        (None, None) => "No tasks found.".to_string(),
+                    let message = if filters.is_empty() {
+                        format!("No {} found.", kind)
+                    } else {
+                        format!("No {} found{}", kind, filters)
                     };
-                    displayer
-                        .notify(&message)
-                        .expect("Failed to notify no tasks found");
+                    self.notify_text(displayer, &message)?;
                 } else {
-                    let message = match (priority, completed) {
-                        (Some(p), Some(c)) => format!(
-                            "{} tasks found with priority {:?} and completed = {}",
-                            filtered_tasks.len(),
-                            p,
-                            c
-                        ),
-                        (Some(p), None) => {
-                            format!("{} tasks found with priority {:?}", filtered_tasks.len(), p)
+                    self.notify_text(
+                        displayer,
+                        &format!("{} {} found{}", filtered_tasks.len(), kind, filters),
+                    )?;
+                    for (depth, task) in TodoList::as_tree(&filtered_tasks) {
+                        let mut line = manager.todo_list.render_task(task, depth);
+                        if verbose {
+                            line.push_str(&task.timestamps());
                         }
-                        (None, Some(c)) => format!(
-                            "{} tasks found with completed = {}",
-                            filtered_tasks.len(),
-                            c
-                        ),
-                        (None, None) => format!("{} tasks found", filtered_tasks.len()),
-                    };
-                    displayer
-                        .notify(&message)
-                        .expect("Failed to notify tasks found");
-                    for task in filtered_tasks {
-                        displayer
-                            .notify(&format!(
-                                "ID: {}, Description: {}, Priority: {:?}, Completed: {}",
-                                task.id, task.description, task.priority, task.completed
-                            ))
-                            .expect("Failed to notify task details");
+                        self.notify_text(displayer, &line)?;
                     }
                 }
             }
-            CliCommand::Remove { id } => {
-                if Cli::is_task(id.as_ref(), manager, displayer) {
-                    manager.remove_task(id.as_ref());
-                    displayer
-                        .notify("Task removed successfully.")
-                        .expect("Failed to notify removal of a task.");
+            CliCommand::Remove {
+                ids,
+                filter,
+                dry_run,
+            } => {
+                let single = Cli::is_single(&ids, &filter);
+                let selected = Cli::select(manager, &ids, filter.as_deref())?;
+                if selected.is_empty() {
+                    self.notify_text(displayer, "No tasks match, nothing was changed.")?;
+                    return self.write_actions(displayer, "remove", &selected, single);
+                }
+                if dry_run {
+                    self.notify_dry_run(
+                        displayer,
+                        manager,
+                        &selected,
+                        "removed with their subtasks",
+                    )?;
+                    return self.write_actions(displayer, "remove", &selected, single);
+                }
+                manager.remove_tasks(&selected)?;
+                if selected.len() == 1 {
+                    self.notify_text(displayer, "Task removed successfully.")?;
+                } else {
+                    self.notify_text(
+                        displayer,
+                        &format!("{} tasks removed successfully.", selected.len()),
+                    )?;
+                }
+                self.write_actions(displayer, "remove", &selected, single)?;
+            }
+            CliCommand::Archive {
+                older_than,
+                dry_run,
+            } => {
+                let completed_before =
+                    Cli::parse_older_than(older_than, Local::now().naive_local())?;
+                let selected = archivable_tasks(&manager.todo_list, completed_before);
+                if selected.is_empty() {
+                    self.notify_text(
+                        displayer,
+                        "No completed tasks to archive, nothing was changed.",
+                    )?;
+                    return self.write_actions(displayer, "archive", &selected, false);
+                }
+                if dry_run {
+                    self.notify_dry_run(displayer, manager, &selected, "archived")?;
+                    return self.write_actions(displayer, "archive", &selected, false);
+                }
+                manager.archive_tasks(&selected)?;
+                if selected.len() == 1 {
+                    self.notify_text(displayer, "Task archived successfully.")?;
+                } else {
+                    self.notify_text(
+                        displayer,
+                        &format!("{} tasks archived successfully.", selected.len()),
+                    )?;
+                }
+                self.write_actions(displayer, "archive", &selected, false)?;
+            }
+            CliCommand::Unarchive { ids } => {
+                let single = Cli::is_single(&ids, &None);
+                let selected = select_tasks(&manager.todo_list.archived_tasks()?, &ids, None)?;
+                if selected.is_empty() {
+                    self.notify_text(displayer, "No archived tasks match, nothing was changed.")?;
+                    return self.write_actions(displayer, "unarchive", &selected, single);
                 }
+                manager.unarchive_tasks(&selected)?;
+                if selected.len() == 1 {
+                    self.notify_text(displayer, "Task unarchived successfully.")?;
+                } else {
+                    self.notify_text(
+                        displayer,
+                        &format!("{} tasks unarchived successfully.", selected.len()),
+                    )?;
+                }
+                self.write_actions(displayer, "unarchive", &selected, single)?;
             }
-            CliCommand::ToggleStatus { id } => {
-                if Cli::is_task(id.as_ref(), manager, displayer) {
-                    manager.toggle_task_status(id.as_ref());
-                    displayer
-                        .notify("Task status toggled successfully.")
-                        .expect("Failed to notify toggling of task status.");
+            CliCommand::ToggleStatus {
+                ids,
+                filter,
+                force,
+                dry_run,
+            } => {
+                let single = Cli::is_single(&ids, &filter);
+                let selected = Cli::select(manager, &ids, filter.as_deref())?;
+                if selected.is_empty() {
+                    self.notify_text(displayer, "No tasks match, nothing was changed.")?;
+                    return self.write_actions(displayer, "toggle-status", &selected, single);
                 }
+                for (id, dependencies) in manager.blocked_completions(&selected) {
+                    let message = format!(
+                        "Task with ID: {} depends on open tasks: {}",
+                        id,
+                        dependencies.join(", ")
+                    );
+                    if !force {
+                        return Err(TodoError::Validation(format!(
+                            "{}. Use --force to complete it anyway.",
+                            message
+                        )));
+                    }
+                    self.notify_text(displayer, &format!("Warning: {}", message))?;
+                }
+                if dry_run {
+                    self.notify_dry_run(displayer, manager, &selected, "toggled")?;
+                    return self.write_actions(displayer, "toggle-status", &selected, single);
+                }
+                manager.toggle_tasks_status(&selected, force)?;
+                if selected.len() == 1 {
+                    self.notify_text(displayer, "Task status toggled successfully.")?;
+                } else {
+                    self.notify_text(
+                        displayer,
+                        &format!("Status of {} tasks toggled successfully.", selected.len()),
+                    )?;
+                }
+                self.write_actions(displayer, "toggle-status", &selected, single)?;
             }
             CliCommand::Edit {
-                id,
+                ids,
+                filter,
+                dry_run,
                 pattern,
                 replace,
                 priority,
-            } => match id {
-                Some(id) => {
-                    if Cli::is_task(id.as_ref(), manager, displayer) {
-                        let task = manager
-                            .get_task(id.as_ref())
-                            .expect("Failed to get the task when editing");
-                        if pattern.is_some() != replace.is_some() {
-                            displayer
-                                    .notify("Error: --pattern and --replace must both be provided or both omitted.")
-                                    .expect("Failed to notify error when editing task");
-                            return;
-                        }
+                due,
+                no_due,
+                tags,
+                untags,
+                depends_on,
+                no_depends_on,
+                every,
+                no_every,
+            } if !ids.is_empty() || filter.is_some() => {
+                let single = Cli::is_single(&ids, &filter);
+                let selected = Cli::select(manager, &ids, filter.as_deref())?;
+                if pattern.is_some() != replace.is_some() {
+                    return Err(TodoError::Validation(
+                        "--pattern and --replace must both be provided or both omitted."
+                            .to_string(),
+                    ));
+                }
+                let due = Cli::parse_due(due)?;
+                let tags = Cli::parse_tags(tags)?;
+                let untags = Cli::parse_tags(untags)?;
+                let recurrence = Cli::parse_recurrence(every)?;
+                if selected.is_empty() {
+                    self.notify_text(displayer, "No tasks match, nothing was changed.")?;
+                    return self.write_actions(displayer, "edit", &selected, single);
+                }
 
-                        let new_description: Option<String> =
-                            if let (Some(pattern), Some(replace)) = (pattern, replace) {
-                                if task.description.contains(&pattern) {
-                                    displayer
-                                        .notify(
-                                            format!(
-                                                "Replacing pattern '{}' with '{}'",
-                                                pattern, replace
-                                            )
-                                            .as_ref(),
-                                        )
-                                        .expect("Failed when notifing edition of a task");
-
-                                    Some(task.description.replace(&pattern, &replace))
-                                } else {
-                                    None
-                                }
+                let mut edits = Vec::new();
+                for id in &selected {
+                    let task = manager
+                        .get_task(id)
+                        .ok_or_else(|| TodoError::NotFound(id.clone()))?
+                        .clone();
+                    if selected.len() > 1 {
+                        self.notify_text(displayer, &format!("Task with ID: {}", id))?;
+                    }
+                    let mut new_depends_on = task.depends_on.clone();
+                    for dependency in &depends_on {
+                        manager.todo_list.check_dependency(id, dependency)?;
+                        new_depends_on.insert(dependency.clone());
+                    }
+                    new_depends_on.retain(|dependency| !no_depends_on.contains(dependency));
+
+                    let new_description: Option<String> =
+                        if let (Some(pattern), Some(replace)) = (&pattern, &replace) {
+                            if task.description.contains(pattern.as_str()) {
+                                self.notify_text(
+                                    displayer,
+                                    format!("Replacing pattern '{}' with '{}'", pattern, replace)
+                                        .as_ref(),
+                                )?;
+
+                                Some(task.description.replace(pattern.as_str(), replace))
                             } else {
                                 None
-                            };
-
-                        if let Some(priority) = priority {
-                            if !task.priority.eq(&priority) {
-                                displayer
-                                    .notify(
-                                        format!(
-                                            "Replacing task priority from '{}' to '{}'",
-                                            task.priority, priority
-                                        )
-                                        .as_ref(),
-                                    )
-                                    .expect("Failed when notifing edition of a task");
                             }
+                        } else {
+                            None
+                        };
+                    if let Some(priority) = priority
+                        && !task.priority.eq(&priority)
+                    {
+                        self.notify_text(
+                            displayer,
+                            format!(
+                                "Replacing task priority from '{}' to '{}'",
+                                task.priority, priority
+                            )
+                            .as_ref(),
+                        )?;
+                    }
+
+                    let new_due = if no_due { None } else { due.or(task.due) };
+                    if new_due != task.due {
+                        let message = match new_due {
+                            Some(new_due) => format!("Setting task due date to '{}'", new_due),
+                            None => "Removing task due date".to_string(),
+                        };
+                        self.notify_text(displayer, &message)?;
+                    }
+
+                    let mut new_tags = task.tags.clone();
+                    new_tags.extend(tags.iter().cloned());
+                    new_tags.retain(|tag| !untags.contains(tag));
+                    for tag in new_tags.difference(&task.tags) {
+                        self.notify_text(displayer, &format!("Adding tag '{}'", tag))?;
+                    }
+                    for tag in task.tags.difference(&new_tags) {
+                        self.notify_text(displayer, &format!("Removing tag '{}'", tag))?;
+                    }
+
+                    for dependency in new_depends_on.difference(&task.depends_on) {
+                        self.notify_text(
+                            displayer,
+                            &format!("Adding dependency on task '{}'", dependency),
+                        )?;
+                    }
+                    for dependency in task.depends_on.difference(&new_depends_on) {
+                        self.notify_text(
+                            displayer,
+                            &format!("Removing dependency on task '{}'", dependency),
+                        )?;
+                    }
+
+                    let new_recurrence = if no_every {
+                        None
+                    } else {
+                        recurrence.clone().or(task.recurrence.clone())
+                    };
+                    if new_recurrence != task.recurrence {
+                        let message = match &new_recurrence {
+                            Some(new_recurrence) => {
+                                format!("Setting task to repeat {}", new_recurrence)
+                            }
+                            None => "Removing task recurrence".to_string(),
+                        };
+                        self.notify_text(displayer, &message)?;
+                    }
+
+                    edits.push((
+                        id.clone(),
+                        new_description.unwrap_or(task.description.clone()),
+                        priority.unwrap_or(task.priority),
+                        TaskOptions {
+                            due: new_due,
+                            tags: new_tags,
+                            parent: task.parent.clone(),
+                            depends_on: new_depends_on,
+                            recurrence: new_recurrence,
+                        },
+                    ));
+                }
+                if dry_run {
+                    self.notify_dry_run(displayer, manager, &selected, "edited")?;
+                } else {
+                    manager.edit_tasks(edits)?;
+                }
+                self.write_actions(displayer, "edit", &selected, single)?;
+            }
+            CliCommand::Edit { .. } if self.output != OutputFormat::Text => {
+                return Err(Cli::interactive_only("edit", "--id"));
+            }
+            CliCommand::Edit { .. } => displayer.handle_edit_task(manager),
+            CliCommand::Search {
+                text,
+                ignore_case,
+                whole_word,
+                regex,
+                priority,
+                completed,
+                ids_only,
+                no_highlight,
+            } => {
+                let options = SearchOptions {
+                    ignore_case,
+                    whole_word,
+                    regex,
+                };
+                let search = Search::new(&text, options).map_err(TodoError::Validation)?;
+
+                let found_tasks = manager
+                    .get_tasks()
+                    .iter()
+                    .filter(|task| search.is_match(task))
+                    .filter(|task| priority.is_none_or(|p| task.priority == p))
+                    .filter(|task| completed.is_none_or(|c| task.completed == c))
+                    .collect::<Vec<_>>();
+                if self.output != OutputFormat::Text {
+                    return self.write_tasks(displayer, &found_tasks);
+                }
+
+                if ids_only {
+                    let ids = found_tasks
+                        .iter()
+                        .map(|task| task.id.as_str())
+                        .collect::<Vec<_>>();
+                    self.notify_text(displayer, &ids.join(","))?;
+                    return Ok(());
+                }
+
+                let mut filters = Vec::new();
+                if let Some(p) = priority {
+                    filters.push(format!("priority {:?}", p));
+                }
+                if let Some(c) = completed {
+                    filters.push(format!("completed = {}", c));
+                }
+                let filters = if filters.is_empty() {
+                    String::new()
+                } else {
+                    format!(" with {}", filters.join(" and "))
+                };
+
+                if found_tasks.is_empty() {
+                    self.notify_text(
+                        displayer,
+                        &format!("No tasks found matching '{}'{}", text, filters),
+                    )?;
+                } else {
+                    self.notify_text(
+                        displayer,
+                        &format!(
+                            "{} tasks found matching '{}'{}",
+                            found_tasks.len(),
+                            text,
+                            filters
+                        ),
+                    )?;
+                    for (depth, task) in TodoList::as_tree(&found_tasks) {
+                        let mut line = manager.todo_list.render_task(task, depth);
+                        // Highlight codes would end up in the files and pipes
+                        if !no_highlight && displayer.is_terminal() {
+                            line = search.highlight_task_line(&line, task);
                         }
+                        self.notify_text(displayer, &line)?;
+                    }
+                }
+            }
+            CliCommand::Undo { to, action } => {
+                let Some(entry) = manager.undo_stack.last().cloned() else {
+                    return Err(TodoError::Validation("Nothing to undo".to_string()));
+                };
+                if let Some(position) = action {
+                    let entry = manager
+                        .undo_stack
+                        .iter()
+                        .rev()
+                        .nth(position.saturating_sub(1))
+                        .cloned();
+                    manager.undo_action(position)?;
+                    if let Some(entry) = entry {
+                        self.notify_text(displayer, &format!("Undone: {}", entry))?;
+                        self.write_action(displayer, "undo", &entry.undo_data.task_id())?;
+                    }
+                    return Ok(());
+                }
+                let Some(position) = to else {
+                    manager.undo()?;
+                    self.notify_text(displayer, &format!("Undone: {}", entry))?;
+                    return self.write_action(displayer, "undo", &entry.undo_data.task_id());
+                };
+                let undone = manager
+                    .undo_stack
+                    .iter()
+                    .rev()
+                    .take(position)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                manager.undo_to(position)?;
+                if self.output != OutputFormat::Text {
+                    let records = undone
+                        .iter()
+                        .map(|entry| ActionRecord {
+                            action: "undo".to_string(),
+                            id: entry.undo_data.task_id().to_string(),
+                        })
+                        .collect::<Vec<_>>();
+                    return self.write_records(displayer, &records);
+                }
+                self.notify_text(displayer, &format!("Undone {} actions:", undone.len()))?;
+                for entry in undone {
+                    self.notify_text(displayer, &format!("- {}", entry))?;
+                }
+            }
+            CliCommand::Redo => {
+                let entry = manager
+                    .redo_stack
+                    .last()
+                    .cloned()
+                    .ok_or_else(|| TodoError::Validation("Nothing to redo".to_string()))?;
+                manager.redo()?;
+                self.notify_text(displayer, &format!("Redone: {}", entry))?;
+                self.write_action(displayer, "redo", &entry.undo_data.task_id())?;
+            }
+            CliCommand::History => {
+                // The most recent actions first, numbered from the next one to undo or redo
+                let records = [("undo", &manager.undo_stack), ("redo", &manager.redo_stack)]
+                    .into_iter()
+                    .flat_map(|(state, stack)| {
+                        stack
+                            .iter()
+                            .rev()
+                            .enumerate()
+                            .map(move |(i, entry)| HistoryRecord::new(i + 1, state, entry))
+                    })
+                    .collect::<Vec<_>>();
+                if self.output != OutputFormat::Text {
+                    return self.write_records(displayer, &records);
+                }
+                if records.is_empty() {
+                    self.notify_text(displayer, "No actions to undo or redo.")?;
+                }
+                for (stack, title) in [(&manager.undo_stack, "Undo"), (&manager.redo_stack, "Redo")]
+                {
+                    for (i, entry) in stack.iter().rev().enumerate() {
+                        if i == 0 {
+                            self.notify_text(
+                                displayer,
+                                &format!("{} history (most recent first):", title),
+                            )?;
+                        }
+                        self.notify_text(displayer, &history_line(i + 1, entry))?;
+                    }
+                }
+            }
+            CliCommand::Restore { backup } => {
+                let backups = manager.todo_list.storage.backups()?;
+                let Some(number) = backup else {
+                    if self.output != OutputFormat::Text {
+                        let records = backups.iter().map(BackupRecord::from).collect::<Vec<_>>();
+                        return self.write_records(displayer, &records);
+                    }
+                    if backups.is_empty() {
+                        self.notify_text(displayer, "No backups found.")?;
+                    }
+                    for backup in backups {
+                        let saved_at = backup
+                            .saved_at
+                            .map_or("unknown date".to_string(), format_timestamp);
+                        let tasks = match backup.tasks {
+                            Some(tasks) => format!("{} tasks", tasks),
+                            None => "unreadable".to_string(),
+                        };
+                        self.notify_text(
+                            displayer,
+                            &format!("{}: saved {}, {}", backup.number, saved_at, tasks),
+                        )?;
+                    }
+                    return Ok(());
+                };
 
-                        manager.edit_task(
-                            id.as_ref(),
-                            (if new_description.is_some() {
-                                new_description.unwrap()
-                            } else {
-                                task.description.clone()
-                            })
-                            .as_ref(),
-                            &(if priority.is_some() {
-                                priority.unwrap()
-                            } else {
-                                task.priority
-                            }),
-                        );
+                manager.todo_list.storage.restore(number)?;
+                manager.todo_list.load()?;
+                // The actions of the history may not apply to the restored tasks
+                manager.clear_history()?;
+                self.notify_text(
+                    displayer,
+                    &format!(
+                        "Backup {} restored, the replaced todo list is now backup 1.",
+                        number
+                    ),
+                )?;
+                if let Some(backup) = backups.iter().find(|backup| backup.number == number) {
+                    self.write_record(displayer, &BackupRecord::from(backup))?;
+                }
+            }
+            CliCommand::Migrate { dry_run } => {
+                let storage = &mut manager.todo_list.storage;
+                let plan = storage.migrate(dry_run)?;
+                let record = MigrationRecord::new(storage.location(), plan.as_ref(), dry_run);
+                match plan {
+                    None => self.notify_text(
+                        displayer,
+                        &format!(
+                            "The todo list {} is up to date (schema version {}).",
+                            record.location, SCHEMA_VERSION
+                        ),
+                    )?,
+                    Some(plan) => {
+                        self.notify_text(
+                            displayer,
+                            &format!(
+                                "The todo list {} {} from schema version {} to {}:",
+                                record.location,
+                                if dry_run {
+                                    "would be migrated"
+                                } else {
+                                    "was migrated"
+                                },
+                                plan.from,
+                                plan.to
+                            ),
+                        )?;
+                        for step in &plan.steps {
+                            self.notify_text(displayer, &format!("- {}", step))?;
+                        }
                     }
                 }
-                None => {
-                    displayer.handle_edit_task(manager);
+                self.write_record(displayer, &record)?;
+            }
+            CliCommand::Lists { action } => {
+                let lists = ListDirectory::in_data_dir()?;
+                self.execute_lists(action, &lists, displayer)?;
+            }
+            CliCommand::Export {
+                format,
+                path,
+                map,
+                group_by,
+            } => {
+                // Named `path` as `file` is the global option giving the todo list
+                let file = path.filter(|file| file.as_os_str() != "-");
+                let format = Cli::exchange_format(format, file.as_deref())?;
+                let map = Cli::column_map(map, format)?;
+                if group_by.is_some() && format != ExchangeFormat::Markdown {
+                    return Err(TodoError::Validation(
+                        "--group-by only applies to the Markdown format".to_string(),
+                    ));
                 }
-            },
+                let tasks = manager.get_tasks();
+                let text = match format {
+                    ExchangeFormat::TodoTxt => todo_txt::export(tasks),
+                    ExchangeFormat::Csv => csv::export(tasks, &map),
+                    ExchangeFormat::Markdown => {
+                        markdown::export(tasks, group_by.unwrap_or_default())
+                    }
+                    ExchangeFormat::Ical => {
+                        let todo_list = &manager.todo_list;
+                        let token =
+                            ical::list_token(&todo_list.storage.location(), todo_list.created_at);
+                        ical::export(tasks, &token)
+                    }
+                    ExchangeFormat::Taskwarrior => taskwarrior::export(tasks)?,
+                };
+                let Some(file) = file else {
+                    return displayer
+                        .write_output(text.trim_end())
+                        .map_err(TodoError::Io);
+                };
+                std::fs::write(&file, text).map_err(|e| {
+                    TodoError::Io(format!(
+                        "Failed to write the file '{}'. Err: {}",
+                        file.display(),
+                        e
+                    ))
+                })?;
+                self.notify_text(
+                    displayer,
+                    &format!("{} tasks exported to {}.", tasks.len(), file.display()),
+                )?;
+                let ids = tasks.iter().map(|task| task.id.clone()).collect::<Vec<_>>();
+                self.write_actions(displayer, "export", &ids, false)?;
+            }
+            CliCommand::Import {
+                format,
+                path: file,
+                map,
+                dry_run,
+            } => {
+                let format = Cli::exchange_format(format, Some(&file))?;
+                let map = Cli::column_map(map, format)?;
+                let read_error = |e: std::io::Error| {
+                    TodoError::Io(format!(
+                        "Failed to read the file '{}'. Err: {}",
+                        file.display(),
+                        e
+                    ))
+                };
+                let text = if file.as_os_str() == "-" {
+                    std::io::read_to_string(std::io::stdin()).map_err(read_error)?
+                } else {
+                    std::fs::read_to_string(&file).map_err(read_error)?
+                };
+                let mut import = match format {
+                    ExchangeFormat::TodoTxt => todo_txt::import(&text),
+                    ExchangeFormat::Csv => csv::import(&text, &map)?,
+                    ExchangeFormat::Markdown => markdown::import(&text),
+                    ExchangeFormat::Ical => ical::import(&text),
+                    ExchangeFormat::Taskwarrior => taskwarrior::import(&text)?,
+                };
+                let archived = manager.todo_list.archived_tasks()?;
+                import.skip_existing(manager.get_tasks(), &archived);
+                for warning in &import.warnings {
+                    self.notify_text(displayer, warning)?;
+                }
+                if import.tasks.is_empty() {
+                    self.notify_text(displayer, "No tasks to import, nothing was changed.")?;
+                    return self.write_actions(displayer, "import", &[], false);
+                }
+                if dry_run {
+                    self.notify_text(
+                        displayer,
+                        &format!(
+                            "Dry run, nothing was changed. {} tasks would be imported:",
+                            import.tasks.len()
+                        ),
+                    )?;
+                    // Shown with the IDs they would be given
+                    for (index, task) in import.tasks.iter().enumerate() {
+                        let task = Task {
+                            id: format!("{:X}", manager.todo_list.next_id + index as u32),
+                            ..task.clone()
+                        };
+                        self.notify_text(displayer, &task.to_string())?;
+                    }
+                    return self.write_actions(displayer, "import", &[], false);
+                }
+                let ids = manager.import_tasks(import.tasks)?;
+                self.notify_text(
+                    displayer,
+                    &format!("{} tasks imported successfully.", ids.len()),
+                )?;
+                self.write_actions(displayer, "import", &ids, false)?;
+            }
+            CliCommand::Tags => {
+                let counts = manager.todo_list.tag_counts();
+                if self.output != OutputFormat::Text {
+                    let records = counts
+                        .into_iter()
+                        .map(|(tag, (open, closed))| TagRecord { tag, open, closed })
+                        .collect::<Vec<_>>();
+                    return self.write_records(displayer, &records);
+                }
+                if counts.is_empty() {
+                    self.notify_text(displayer, "No tags found.")?;
+                }
+                for (tag, (open, closed)) in counts {
+                    self.notify_text(
+                        displayer,
+                        &format!("{}: {} open, {} closed", tag, open, closed),
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_lists(
+        &self,
+        action: Option<ListsCommand>,
+        lists: &ListDirectory,
+        displayer: &mut dyn TraitCliDisplayer,
+    ) -> Result<(), TodoError> {
+        let record = |name: &str, tasks: Option<usize>| -> Result<ListRecord, TodoError> {
+            Ok(ListRecord {
+                name: name.to_string(),
+                path: lists.path(name)?.to_string_lossy().into_owned(),
+                tasks,
+            })
+        };
+        match action {
+            None => {
+                let records = lists
+                    .names()?
+                    .iter()
+                    .map(|name| record(name, lists.task_count(name).ok()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if self.output != OutputFormat::Text {
+                    return self.write_records(displayer, &records);
+                }
+                if records.is_empty() {
+                    self.notify_text(displayer, "No lists found.")?;
+                }
+                for record in records {
+                    let tasks = match record.tasks {
+                        Some(tasks) => format!("{} tasks", tasks),
+                        None => "unreadable".to_string(),
+                    };
+                    self.notify_text(displayer, &format!("{}: {}", record.name, tasks))?;
+                }
+            }
+            Some(ListsCommand::Create { name }) => {
+                lists.create(&name)?;
+                self.notify_text(displayer, &format!("List '{}' created.", name))?;
+                self.write_record(displayer, &record(&name, Some(0))?)?;
+            }
+            Some(ListsCommand::Rename { name, new_name }) => {
+                lists.rename(&name, &new_name)?;
+                self.notify_text(
+                    displayer,
+                    &format!("List '{}' renamed to '{}'.", name, new_name),
+                )?;
+                let tasks = lists.task_count(&new_name).ok();
+                self.write_record(displayer, &record(&new_name, tasks)?)?;
+            }
+            Some(ListsCommand::Delete { name, force }) => {
+                // A list whose file cannot be read is only deleted with --force
+                let tasks = match lists.task_count(&name) {
+                    Err(e @ TodoError::ListNotFound(_)) => return Err(e),
+                    result => result.ok(),
+                };
+                if !force && tasks != Some(0) {
+                    return Err(TodoError::Validation(format!(
+                        "List '{}' is not empty. Use --force to delete it anyway.",
+                        name
+                    )));
+                }
+                lists.delete(&name)?;
+                self.notify_text(displayer, &format!("List '{}' deleted.", name))?;
+                self.write_record(displayer, &record(&name, tasks)?)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Notify a message meant to be read by people, only shown with the text output
+    fn notify_text(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        message: &str,
+    ) -> Result<(), TodoError> {
+        if self.output != OutputFormat::Text {
+            return Ok(());
+        }
+        displayer.notify(message).map_err(TodoError::Io)
+    }
+
+    /// Write the listed tasks in the selected structured output format,
+    /// subtasks following their parent as in the text listings
+    fn write_tasks(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        tasks: &[&Task],
+    ) -> Result<(), TodoError> {
+        let records = TodoList::as_tree(tasks)
+            .into_iter()
+            .map(|(_, task)| TaskRecord::from(task))
+            .collect::<Vec<_>>();
+        self.write_records(displayer, &records)
+    }
+
+    fn write_records<R: Record>(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        records: &[R],
+    ) -> Result<(), TodoError> {
+        match self.output.render_list(records)? {
+            // An empty JSON lines listing has no line at all
+            Some(output) if !output.is_empty() => {
+                displayer.write_output(&output).map_err(TodoError::Io)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the task changed by a command in the selected structured output format
+    fn write_action(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        action: &str,
+        id: &str,
+    ) -> Result<(), TodoError> {
+        let record = ActionRecord {
+            action: action.to_string(),
+            id: id.to_string(),
+        };
+        self.write_record(displayer, &record)
+    }
+
+    /// Write the tasks changed by a bulk command, as a single result when one task was given
+    fn write_actions(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        action: &str,
+        ids: &[String],
+        single: bool,
+    ) -> Result<(), TodoError> {
+        if single && let [id] = ids {
+            return self.write_action(displayer, action, id);
+        }
+        let records = ids
+            .iter()
+            .map(|id| ActionRecord {
+                action: action.to_string(),
+                id: id.clone(),
+            })
+            .collect::<Vec<_>>();
+        self.write_records(displayer, &records)
+    }
+
+    /// List the tasks a `--dry-run` command would change
+    fn notify_dry_run(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        manager: &Manager,
+        ids: &[String],
+        change: &str,
+    ) -> Result<(), TodoError> {
+        self.notify_text(
+            displayer,
+            &format!(
+                "Dry run, nothing was changed. {} tasks would be {}:",
+                ids.len(),
+                change
+            ),
+        )?;
+        for task in manager
+            .get_tasks()
+            .iter()
+            .filter(|task| ids.contains(&task.id))
+        {
+            self.notify_text(displayer, &manager.todo_list.render_task(task, 0))?;
+        }
+        Ok(())
+    }
+
+    fn write_record<R: Record>(
+        &self,
+        displayer: &mut dyn TraitCliDisplayer,
+        record: &R,
+    ) -> Result<(), TodoError> {
+        match self.output.render_one(record)? {
+            Some(output) => displayer.write_output(&output).map_err(TodoError::Io),
+            None => Ok(()),
         }
     }
 
-    fn is_task(id: &str, manager: &mut Manager, displayer: &mut dyn TraitCliDisplayer) -> bool {
-        if manager.get_task(&id).is_none() {
-            displayer
-                .notify(&format!("Error: Task with ID: {} not found", id))
-                .expect("Failed to notify error for toggling task status");
-            return false;
-        } else {
-            return true;
+    /// Adding or editing a task without arguments prompts for its fields,
+    /// which cannot be mixed with a structured output
+    fn interactive_only(command: &str, argument: &str) -> TodoError {
+        TodoError::Validation(format!(
+            "Interactive {} is only available with the text output, please use {}",
+            command, argument
+        ))
+    }
+
+    /// Normalize the tags given as arguments
+    fn parse_tags(tags: Vec<String>) -> Result<BTreeSet<String>, TodoError> {
+        tags.iter()
+            .map(|tag| normalize_tag(tag))
+            .collect::<Result<_, _>>()
+            .map_err(TodoError::Validation)
+    }
+
+    /// Parse an optional due date argument
+    fn parse_due(text: Option<String>) -> Result<Option<Due>, TodoError> {
+        text.map(|text| Due::parse(&text))
+            .transpose()
+            .map_err(TodoError::Validation)
+    }
+
+    /// Parse an optional recurrence rule argument
+    fn parse_recurrence(text: Option<String>) -> Result<Option<Recurrence>, TodoError> {
+        text.map(|text| Recurrence::parse(&text))
+            .transpose()
+            .map_err(TodoError::Validation)
+    }
+
+    /// Parse an optional time window start
+    fn parse_since(
+        text: Option<String>,
+        now: NaiveDateTime,
+    ) -> Result<Option<NaiveDateTime>, TodoError> {
+        text.map(|text| parse_since(&text, now))
+            .transpose()
+            .map_err(TodoError::Validation)
+    }
+
+    /// Time before which the tasks to archive were completed: an age or a date as accepted
+    /// by `--completed-since`, a bare number being a number of days
+    fn parse_older_than(
+        text: Option<String>,
+        now: NaiveDateTime,
+    ) -> Result<Option<NaiveDateTime>, TodoError> {
+        let text = text.map(|text| {
+            let text = text.trim().to_string();
+            if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+                format!("{}d", text)
+            } else {
+                text
+            }
+        });
+        Cli::parse_since(text, now)
+    }
+
+    /// Format given by `--format`, or the one matching the extension of the file
+    fn exchange_format(
+        format: Option<ExchangeFormat>,
+        file: Option<&Path>,
+    ) -> Result<ExchangeFormat, TodoError> {
+        format
+            .or_else(|| file.and_then(ExchangeFormat::for_path))
+            .ok_or_else(|| {
+                TodoError::Validation(
+                    "Unknown file format, please give it with --format".to_string(),
+                )
+            })
+    }
+
+    /// Mapping of the CSV columns given by `--map`, which only applies to the CSV format
+    fn column_map(map: Option<String>, format: ExchangeFormat) -> Result<ColumnMap, TodoError> {
+        match map {
+            None => Ok(ColumnMap::default()),
+            Some(_) if format != ExchangeFormat::Csv => Err(TodoError::Validation(
+                "--map only applies to the CSV format".to_string(),
+            )),
+            Some(map) => ColumnMap::parse(&map).map_err(TodoError::Validation),
+        }
+    }
+
+    /// Whether only the results are written, without the messages of the todo list loading:
+    /// with a structured output, or when the tasks are exported to the standard output
+    pub fn quiet(&self) -> bool {
+        self.output != OutputFormat::Text
+            || matches!(
+                &self.command,
+                Some(CliCommand::Export { path, .. })
+                    if path.as_ref().is_none_or(|path| path.as_os_str() == "-")
+            )
+    }
+
+    /// A single `--id` without range nor filter gives a single result, as before bulk edits
+    fn is_single(ids: &[String], filter: &Option<String>) -> bool {
+        filter.is_none() && ids.len() == 1 && !ids[0].contains('-')
+    }
+
+    /// IDs of the tasks selected by the `--id` values and the `--where` conditions
+    fn select(
+        manager: &Manager,
+        ids: &[String],
+        filter: Option<&str>,
+    ) -> Result<Vec<String>, TodoError> {
+        let filter = filter
+            .map(Filter::parse)
+            .transpose()
+            .map_err(TodoError::Validation)?;
+        select_tasks(manager.get_tasks(), ids, filter.as_ref())
+    }
+
+    fn check_task(id: &str, manager: &Manager) -> Result<(), TodoError> {
+        match manager.get_task(id) {
+            Some(_) => Ok(()),
+            None => Err(TodoError::NotFound(id.to_string())),
         }
     }
 }
@@ -0,0 +1,411 @@
+use crate::error::TodoError;
+use crate::exchange::csv;
+use crate::model::task::Task;
+use crate::service::history::HistoryEntry;
+use crate::storage::backend::Backup;
+use crate::storage::schema::{MigrationPlan, SCHEMA_VERSION};
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Format of the results written by the command line interface
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable messages
+    #[default]
+    Text,
+    /// A JSON array for listings, a JSON object for single results
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma separated values with a header row
+    Csv,
+    /// Tab separated values with a header row
+    Tsv,
+}
+
+/// Result that can be written in every structured output format
+pub trait Record: Serialize {
+    /// Names of the columns of the CSV and TSV formats, in the same order as `fields`
+    fn header() -> &'static [&'static str];
+    fn fields(&self) -> Vec<String>;
+}
+
+/// Stable representation of a task: every field is always present,
+/// with `null` (or an empty list) when the task does not have it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskRecord {
+    pub id: String,
+    pub description: String,
+    pub priority: String,
+    pub completed: bool,
+    /// `YYYY-MM-DD` or `YYYY-MM-DD HH:MM`
+    pub due: Option<String>,
+    pub tags: Vec<String>,
+    pub parent: Option<String>,
+    pub depends_on: Vec<String>,
+    /// Human readable rule, e.g. `weekly on Mon, Thu`
+    pub recurrence: Option<String>,
+    /// `YYYY-MM-DDTHH:MM:SS` local times
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub completed_at: Option<String>,
+}
+
+/// Number of open and closed tasks having a tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagRecord {
+    pub tag: String,
+    pub open: usize,
+    pub closed: usize,
+}
+
+/// Named todo list, with its number of tasks or `null` when its file cannot be read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListRecord {
+    pub name: String,
+    pub path: String,
+    pub tasks: Option<usize>,
+}
+
+/// Backup of the todo list, with its number of tasks or `null` when it cannot be read
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackupRecord {
+    /// 1 for the most recent backup
+    pub backup: usize,
+    pub path: String,
+    /// `YYYY-MM-DDTHH:MM:SS` local time
+    pub saved_at: Option<String>,
+    pub tasks: Option<usize>,
+}
+
+/// Schema migration of the todo list, done or only planned with `--dry-run`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MigrationRecord {
+    pub location: String,
+    pub from_version: u32,
+    pub to_version: u32,
+    /// Descriptions of the migrations, empty when the todo list is up to date
+    pub migrations: Vec<String>,
+    pub dry_run: bool,
+}
+
+/// Action of the undo history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryRecord {
+    /// 1 for the next action to undo or redo
+    pub position: usize,
+    /// `undo` for the actions that can be undone, `redo` for the undone ones
+    pub state: String,
+    /// Name of the subcommand of the action, e.g. `add`
+    pub action: String,
+    pub id: String,
+    /// `YYYY-MM-DDTHH:MM:SS` local time, `null` for actions saved by older versions
+    pub performed_at: Option<String>,
+    /// Human readable action, e.g. `Add task 3 "Buy milk" (High)`
+    pub description: String,
+}
+
+/// Task changed by a command, e.g. the ID given to a new task
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActionRecord {
+    /// Name of the subcommand: `add`, `edit`, `remove`, `toggle-status`, `archive`,
+    /// `unarchive`, `undo` or `redo`
+    pub action: String,
+    pub id: String,
+}
+
+/// Failure of a command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErrorRecord {
+    /// `validation`, `not_found`, `list_not_found`, `parse`, `io`, `serialization` or `locked`
+    pub error: String,
+    pub message: String,
+    pub exit_code: i32,
+}
+
+impl From<&Task> for TaskRecord {
+    fn from(task: &Task) -> Self {
+        let format = |timestamp: Option<NaiveDateTime>| {
+            timestamp.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string())
+        };
+        TaskRecord {
+            id: task.id.clone(),
+            description: task.description.clone(),
+            priority: task.priority.to_string(),
+            completed: task.completed,
+            due: task.due.map(|due| due.to_string()),
+            tags: task.tags.iter().cloned().collect(),
+            parent: task.parent.clone(),
+            depends_on: task.depends_on.iter().cloned().collect(),
+            recurrence: task.recurrence.as_ref().map(|r| r.to_string()),
+            created_at: format(task.created_at),
+            updated_at: format(task.updated_at),
+            completed_at: format(task.completed_at),
+        }
+    }
+}
+
+impl From<&Backup> for BackupRecord {
+    fn from(backup: &Backup) -> Self {
+        BackupRecord {
+            backup: backup.number,
+            path: backup.path.display().to_string(),
+            saved_at: backup
+                .saved_at
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            tasks: backup.tasks,
+        }
+    }
+}
+
+impl MigrationRecord {
+    pub fn new(location: String, plan: Option<&MigrationPlan>, dry_run: bool) -> Self {
+        MigrationRecord {
+            location,
+            from_version: plan.map_or(SCHEMA_VERSION, |plan| plan.from),
+            to_version: SCHEMA_VERSION,
+            migrations: plan.map(|plan| plan.steps.clone()).unwrap_or_default(),
+            dry_run,
+        }
+    }
+}
+
+impl HistoryRecord {
+    pub fn new(position: usize, state: &str, entry: &HistoryEntry) -> Self {
+        HistoryRecord {
+            position,
+            state: state.to_string(),
+            action: entry.command.name().to_string(),
+            id: entry.undo_data.task_id().to_string(),
+            performed_at: entry
+                .performed_at
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            description: entry.to_string(),
+        }
+    }
+}
+
+impl From<&TodoError> for ErrorRecord {
+    fn from(error: &TodoError) -> Self {
+        ErrorRecord {
+            error: error.kind().to_string(),
+            message: error.to_string(),
+            exit_code: error.exit_code(),
+        }
+    }
+}
+
+impl Record for TaskRecord {
+    fn header() -> &'static [&'static str] {
+        &[
+            "id",
+            "description",
+            "priority",
+            "completed",
+            "due",
+            "tags",
+            "parent",
+            "depends_on",
+            "recurrence",
+            "created_at",
+            "updated_at",
+            "completed_at",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        // Lists are joined with `;` so that they stay in a single column
+        vec![
+            self.id.clone(),
+            self.description.clone(),
+            self.priority.clone(),
+            self.completed.to_string(),
+            self.due.clone().unwrap_or_default(),
+            self.tags.join(";"),
+            self.parent.clone().unwrap_or_default(),
+            self.depends_on.join(";"),
+            self.recurrence.clone().unwrap_or_default(),
+            self.created_at.clone().unwrap_or_default(),
+            self.updated_at.clone().unwrap_or_default(),
+            self.completed_at.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+impl Record for TagRecord {
+    fn header() -> &'static [&'static str] {
+        &["tag", "open", "closed"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.tag.clone(),
+            self.open.to_string(),
+            self.closed.to_string(),
+        ]
+    }
+}
+
+impl Record for ListRecord {
+    fn header() -> &'static [&'static str] {
+        &["name", "path", "tasks"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.path.clone(),
+            self.tasks
+                .map(|tasks| tasks.to_string())
+                .unwrap_or_default(),
+        ]
+    }
+}
+
+impl Record for BackupRecord {
+    fn header() -> &'static [&'static str] {
+        &["backup", "path", "saved_at", "tasks"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.backup.to_string(),
+            self.path.clone(),
+            self.saved_at.clone().unwrap_or_default(),
+            self.tasks
+                .map(|tasks| tasks.to_string())
+                .unwrap_or_default(),
+        ]
+    }
+}
+
+impl Record for MigrationRecord {
+    fn header() -> &'static [&'static str] {
+        &[
+            "location",
+            "from_version",
+            "to_version",
+            "migrations",
+            "dry_run",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.location.clone(),
+            self.from_version.to_string(),
+            self.to_version.to_string(),
+            self.migrations.join(";"),
+            self.dry_run.to_string(),
+        ]
+    }
+}
+
+impl Record for HistoryRecord {
+    fn header() -> &'static [&'static str] {
+        &[
+            "position",
+            "state",
+            "action",
+            "id",
+            "performed_at",
+            "description",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.position.to_string(),
+            self.state.clone(),
+            self.action.clone(),
+            self.id.clone(),
+            self.performed_at.clone().unwrap_or_default(),
+            self.description.clone(),
+        ]
+    }
+}
+
+impl Record for ActionRecord {
+    fn header() -> &'static [&'static str] {
+        &["action", "id"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![self.action.clone(), self.id.clone()]
+    }
+}
+
+impl Record for ErrorRecord {
+    fn header() -> &'static [&'static str] {
+        &["error", "message", "exit_code"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.error.clone(),
+            self.message.clone(),
+            self.exit_code.to_string(),
+        ]
+    }
+}
+
+impl OutputFormat {
+    /// Render a listing. JSON gives an array, the other formats a line per record.
+    /// Returns `None` for the text format, whose messages are written by each command.
+    pub fn render_list<R: Record>(&self, records: &[R]) -> Result<Option<String>, TodoError> {
+        let output = match self {
+            OutputFormat::Text => return Ok(None),
+            OutputFormat::Json => serde_json::to_string_pretty(records).map_err(serialization)?,
+            OutputFormat::Jsonl => records
+                .iter()
+                .map(|record| serde_json::to_string(record).map_err(serialization))
+                .collect::<Result<Vec<_>, _>>()?
+                .join("\n"),
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let header = R::header().iter().map(|name| name.to_string()).collect();
+                std::iter::once(header)
+                    .chain(records.iter().map(|record| record.fields()))
+                    .map(|fields| self.render_row(fields))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        };
+        Ok(Some(output))
+    }
+
+    /// Render a single result. JSON gives an object instead of an array.
+    pub fn render_one<R: Record>(&self, record: &R) -> Result<Option<String>, TodoError> {
+        match self {
+            OutputFormat::Json => serde_json::to_string_pretty(record)
+                .map(Some)
+                .map_err(serialization),
+            _ => self.render_list(std::slice::from_ref(record)),
+        }
+    }
+
+    fn render_row(&self, fields: Vec<String>) -> String {
+        let (separator, escape): (&str, fn(&str) -> String) = match self {
+            OutputFormat::Tsv => ("\t", escape_tsv),
+            _ => (",", csv::escape),
+        };
+        fields
+            .iter()
+            .map(|field| escape(field))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+/// TSV fields cannot contain tabs or line breaks, they are written as escape sequences
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn serialization(error: serde_json::Error) -> TodoError {
+    TodoError::Serialization(format!("Failed to serialize output: {}", error))
+}
@@ -2,4 +2,15 @@ use crate::{service::manager::Manager, ui::displayer::Displayer};
 pub trait TraitCliDisplayer: Displayer {
     fn handle_add_task(&mut self, manager: &mut Manager);
     fn handle_edit_task(&mut self, manager: &mut Manager);
-}
\ No newline at end of file
+
+    /// Write machine-readable output as it is, without the decoration of notifications
+    fn write_output(&mut self, output: &str) -> Result<(), String> {
+        self.notify(output)
+    }
+
+    /// Whether the notifications are shown on a terminal, which renders highlight codes
+    /// instead of writing them to a file or a pipe
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
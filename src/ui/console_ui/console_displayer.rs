@@ -5,7 +5,7 @@ use crate::service::manager::Manager;
 use crate::ui::console_ui::generic_console_displayer::GenericConsoleDisplayer;
 use crate::ui::displayer::Displayer;
 use crate::ui::menu_option::MenuOption;
-use std::io::{BufReader, Stdin, Stdout};
+use std::io::{BufReader, IsTerminal, Stdin, Stdout};
 
 /// ConsoleDisplayer for production, wrapping GenericConsoleDisplayer with Stdin/Stdout.
 pub struct ConsoleDisplayer {
@@ -19,11 +19,18 @@ impl ConsoleDisplayer {
                 BufReader::new(std::io::stdin()),
                 std::io::stdout(),
                 Editor::<(), DefaultHistory>::new().expect("Failed when creating editor"),
-            ),
+            )
+            .with_highlight(std::io::stdout().is_terminal()),
         }
     }
 }
 
+impl Default for ConsoleDisplayer {
+    fn default() -> Self {
+        ConsoleDisplayer::new()
+    }
+}
+
 impl Displayer for ConsoleDisplayer {
     fn new() -> Self {
         ConsoleDisplayer::new()
@@ -38,10 +45,10 @@ impl Displayer for ConsoleDisplayer {
     }
 
     fn notify(&mut self, message: &str) -> Result<(), String> {
-        return self.inner.notify(message);
+        self.inner.notify(message)
     }
 
     fn exit(&mut self) -> Result<(), String> {
-        return self.inner.exit();
+        self.inner.exit()
     }
 }
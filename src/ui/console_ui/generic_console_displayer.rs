@@ -1,4 +1,11 @@
+use crate::error::TodoError;
+use crate::model::due::Due;
 use crate::model::priority::Priority;
+use crate::model::search::{Search, SearchOptions};
+use crate::model::sort::{SortKey, sort_tasks};
+use crate::model::task::{Task, TaskOptions};
+use crate::model::todo_list::TodoList;
+use crate::service::history::history_line;
 use crate::service::manager::{Manager, ManagerTrait};
 use crate::ui::displayer::Displayer;
 use crate::ui::line_editor::LineEditor;
@@ -15,6 +22,7 @@ pub struct GenericConsoleDisplayer<
     pub output: W,
     editor: E,
     buffer: String,
+    highlight: bool,
 }
 
 impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sync>
@@ -26,9 +34,16 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
             output,
             editor,
             buffer: String::new(),
+            highlight: false,
         }
     }
 
+    /// Highlights the search matches, for an output that is a terminal
+    pub fn with_highlight(mut self, highlight: bool) -> Self {
+        self.highlight = highlight;
+        self
+    }
+
     /// Recieves two String messages to ask for the ID of the task and returns the id value
     fn _read_user_input(&mut self, messages: Vec<String>) -> Result<String, String> {
         let prompt = messages.join("\n");
@@ -62,16 +77,13 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
             }
         };
 
-        loop {
+        let priority = loop {
             let priority_input = self._read_user_input(vec![
                 "Enter task priority number (1-High, 2-Medium, 3-Low):".into(),
             ])?;
 
             if let Ok(priority) = Priority::str_to_priority(&priority_input) {
-                manager.add_task(description.as_ref(), &priority);
-                writeln!(self.output, "Task added.")
-                    .map_err(|e| format!("Failed to write: {}", e))?;
-                break;
+                break priority;
             } else {
                 writeln!(
                     self.output,
@@ -79,7 +91,33 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
                 )
                 .map_err(|e| format!("Failed to write: {}", e))?;
             }
-        }
+        };
+
+        let due = loop {
+            let due_input = self._read_user_input(vec![
+                "Enter task due date (optional, e.g. 2026-11-03, tomorrow, +3d, next fri 17:00):"
+                    .into(),
+            ])?;
+
+            if due_input.is_empty() {
+                break None;
+            }
+            match Due::parse(&due_input) {
+                Ok(due) => break Some(due),
+                Err(e) => writeln!(self.output, "{}, please type again a valid one.", e)
+                    .map_err(|e| format!("Failed to write: {}", e))?,
+            }
+        };
+
+        manager.add_task_with_options(
+            description.as_ref(),
+            &priority,
+            TaskOptions {
+                due,
+                ..Default::default()
+            },
+        )?;
+        writeln!(self.output, "Task added.").map_err(|e| format!("Failed to write: {}", e))?;
         Ok(())
     }
 
@@ -91,13 +129,106 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
             writeln!(self.output, "No tasks in the list.")
                 .map_err(|e| format!("Failed to write: {}", e))?;
         } else {
-            for task in manager.get_tasks() {
-                writeln!(
-                    self.output,
-                    "ID: {}, Description: {}, Priority: {:?}, Completed: {}",
-                    task.id, task.description, task.priority, task.completed
-                )
+            let tasks = manager.get_tasks().iter().collect::<Vec<_>>();
+            self.write_tasks(manager, &tasks)?;
+        }
+        self.output
+            .flush()
+            .map_err(|e| format!("Failed to flush: {}", e))?;
+        Ok(())
+    }
+
+    pub fn handle_sort_tasks(&mut self, manager: &Manager) -> Result<(), String> {
+        writeln!(self.output, "You selected: List Tasks Sorted By...")
+            .map_err(|e| format!("Failed to write: {}", e))?;
+
+        let keys = loop {
+            let keys_input = self._read_user_input(vec![
+                "Sort by (comma separated: priority, id, status, description, due, created, updated, completed):"
+                    .into(),
+            ])?;
+
+            match SortKey::parse_list(&keys_input) {
+                Ok(keys) => break keys,
+                Err(e) => writeln!(self.output, "{}, please type again a valid one.", e)
+                    .map_err(|e| format!("Failed to write: {}", e))?,
+            }
+        };
+        let reverse = self
+            ._read_user_input(vec!["Reverse the order? (y/N):".into()])?
+            .eq_ignore_ascii_case("y");
+
+        if manager.get_tasks().is_empty() {
+            writeln!(self.output, "No tasks in the list.")
+                .map_err(|e| format!("Failed to write: {}", e))?;
+        } else {
+            let mut tasks = manager.get_tasks().iter().collect::<Vec<_>>();
+            sort_tasks(&mut tasks, &keys, reverse);
+            self.write_tasks(manager, &tasks)?;
+        }
+        self.output
+            .flush()
+            .map_err(|e| format!("Failed to flush: {}", e))?;
+        Ok(())
+    }
+
+    pub fn handle_search_tasks(&mut self, manager: &Manager) -> Result<(), String> {
+        writeln!(self.output, "You selected: Search Tasks")
+            .map_err(|e| format!("Failed to write: {}", e))?;
+
+        let search = loop {
+            let text = self._read_user_input(vec!["Enter text to search:".into()])?;
+            let mode = self._read_user_input(vec![
+                "Enter match mode number (1-Substring, 2-Ignore case, 3-Whole word, 4-Regex):"
+                    .into(),
+            ])?;
+            let options = match mode.as_str() {
+                "" | "1" => SearchOptions::default(),
+                "2" => SearchOptions {
+                    ignore_case: true,
+                    ..Default::default()
+                },
+                "3" => SearchOptions {
+                    whole_word: true,
+                    ..Default::default()
+                },
+                "4" => SearchOptions {
+                    regex: true,
+                    ..Default::default()
+                },
+                _ => {
+                    writeln!(
+                        self.output,
+                        "Invalid match mode, please type again a valid one."
+                    )
+                    .map_err(|e| format!("Failed to write: {}", e))?;
+                    continue;
+                }
+            };
+            match Search::new(&text, options) {
+                Ok(search) => break search,
+                Err(e) => writeln!(self.output, "{}, please type again a valid one.", e)
+                    .map_err(|e| format!("Failed to write: {}", e))?,
+            }
+        };
+
+        let tasks = manager
+            .get_tasks()
+            .iter()
+            .filter(|task| search.is_match(task))
+            .collect::<Vec<_>>();
+        if tasks.is_empty() {
+            writeln!(self.output, "No tasks found.")
                 .map_err(|e| format!("Failed to write: {}", e))?;
+        } else {
+            for (depth, task) in TodoList::as_tree(&tasks) {
+                let line = manager.todo_list.render_task(task, depth);
+                let line = if self.highlight {
+                    search.highlight_task_line(&line, task)
+                } else {
+                    line
+                };
+                writeln!(self.output, "{}", line).map_err(|e| format!("Failed to write: {}", e))?;
             }
         }
         self.output
@@ -106,22 +237,42 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
         Ok(())
     }
 
+    /// Write the given tasks as a tree, keeping their order among siblings
+    fn write_tasks(&mut self, manager: &Manager, tasks: &[&Task]) -> Result<(), String> {
+        for (depth, task) in TodoList::as_tree(tasks) {
+            writeln!(
+                self.output,
+                "{}",
+                manager.todo_list.render_task(task, depth)
+            )
+            .map_err(|e| format!("Failed to write: {}", e))?;
+        }
+        Ok(())
+    }
+
     pub fn handle_toggle_task(&mut self, manager: &mut Manager) -> Result<(), String> {
         let id_input = self._read_user_input(vec![
             "You selected: Complete Task".into(),
             "Enter task ID to complete:".into(),
         ])?;
 
-        if manager.toggle_task_status(id_input.as_ref()) {
-            writeln!(
+        match manager.toggle_task_status(id_input.as_ref(), false) {
+            Ok(()) => writeln!(
                 self.output,
                 "Task with ID {} marked as completed.",
                 id_input
             )
-            .map_err(|e| format!("Failed to write: {}", e))?;
-        } else {
-            writeln!(self.output, "Task with ID {} not found.", id_input)
-                .map_err(|e| format!("Failed to write: {}", e))?;
+            .map_err(|e| format!("Failed to write: {}", e))?,
+            Err(TodoError::NotFound(_)) => {
+                writeln!(self.output, "Task with ID {} not found.", id_input)
+                    .map_err(|e| format!("Failed to write: {}", e))?
+            }
+            // The task depends on open tasks
+            Err(TodoError::Validation(message)) => {
+                writeln!(self.output, "{}. Complete them first.", message)
+                    .map_err(|e| format!("Failed to write: {}", e))?
+            }
+            Err(e) => return Err(e.into()),
         }
         self.output
             .flush()
@@ -135,10 +286,10 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
             "Enter task ID to remove:".into(),
         ])?;
 
-        let message = if manager.remove_task(id_input.as_ref()) {
-            format!("Task with ID {} removed.", id_input)
-        } else {
-            format!("Task with ID {} not found.", id_input)
+        let message = match manager.remove_task(id_input.as_ref()) {
+            Ok(()) => format!("Task with ID {} removed.", id_input),
+            Err(TodoError::NotFound(_)) => format!("Task with ID {} not found.", id_input),
+            Err(e) => return Err(e.into()),
         };
 
         write!(self.output, "{}", message).map_err(|e| format!("Failed to write: {}", e))?;
@@ -183,10 +334,38 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
                 }
             };
 
-            if manager.edit_task(&id_input, &new_description, &new_priority) {
-                write!(self.output, "Task with ID: {} was edited", id_input)
-                    .map_err(|e| format!("Failed to write: {}", e))?;
-            }
+            let current_due = task.due.map(|due| due.to_string()).unwrap_or_default();
+            let new_due = loop {
+                let input = self
+                    .editor
+                    .readline_with_initial(
+                        "Edit due date (empty for none): ",
+                        (current_due.as_ref(), ""),
+                    )
+                    .map_err(|e| format!("Failed in the line editor: {}", e))?;
+
+                if input.trim().is_empty() {
+                    break None;
+                }
+                match Due::parse(&input) {
+                    Ok(due) => break Some(due),
+                    Err(e) => writeln!(self.output, "{}", e)
+                        .map_err(|e| format!("Failed to write: {}", e))?,
+                }
+            };
+
+            let new_options = TaskOptions {
+                due: new_due,
+                ..task.options()
+            };
+            manager.edit_task_with_options(
+                &id_input,
+                &new_description,
+                &new_priority,
+                new_options,
+            )?;
+            write!(self.output, "Task with ID: {} was edited", id_input)
+                .map_err(|e| format!("Failed to write: {}", e))?;
         } else {
             write!(self.output, "Task with ID: {} not found", id_input)
                 .map_err(|e| format!("Failed to write: {}", e))?;
@@ -223,6 +402,61 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
         Ok(())
     }
 
+    /// Show the actions that can be undone and redone, and go back to before one of them
+    pub fn handle_history(&mut self, manager: &mut Manager) -> Result<(), String> {
+        writeln!(self.output, "You selected: History")
+            .map_err(|e| format!("Failed to write: {}", e))?;
+
+        if manager.undo_stack.is_empty() && manager.redo_stack.is_empty() {
+            writeln!(self.output, "No actions to undo or redo.")
+                .map_err(|e| format!("Failed to write: {}", e))?;
+            return Ok(());
+        }
+        for (stack, title) in [(&manager.undo_stack, "Undo"), (&manager.redo_stack, "Redo")] {
+            if !stack.is_empty() {
+                writeln!(self.output, "{} history (most recent first):", title)
+                    .map_err(|e| format!("Failed to write: {}", e))?;
+            }
+            for (i, entry) in stack.iter().rev().enumerate() {
+                writeln!(self.output, "{}", history_line(i + 1, entry))
+                    .map_err(|e| format!("Failed to write: {}", e))?;
+            }
+        }
+        if manager.undo_stack.is_empty() {
+            return Ok(());
+        }
+
+        let position = loop {
+            let input = self._read_user_input(vec![
+                "Enter the number of an action to go back to before it (empty to keep everything):"
+                    .into(),
+            ])?;
+            if input.is_empty() {
+                return Ok(());
+            }
+            match input.parse::<usize>() {
+                Ok(position) if (1..=manager.undo_stack.len()).contains(&position) => {
+                    break position;
+                }
+                _ => writeln!(
+                    self.output,
+                    "Invalid action number, please type again a valid one."
+                )
+                .map_err(|e| format!("Failed to write: {}", e))?,
+            }
+        };
+        match manager.undo_to(position) {
+            Ok(undone) => writeln!(self.output, "{} actions undone.", undone)
+                .map_err(|e| format!("Failed to write: {}", e))?,
+            Err(e) => writeln!(self.output, "Undo failed: {}", e)
+                .map_err(|e| format!("Failed to write: {}", e))?,
+        }
+        self.output
+            .flush()
+            .map_err(|e| format!("Failed to flush: {}", e))?;
+        Ok(())
+    }
+
     fn handle_error(&mut self, error: &str) -> Result<(), String> {
         writeln!(self.output, "Error: {}", error).map_err(|e| format!("Failed to write: {}", e))?;
         self.output
@@ -243,13 +477,13 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
         let _ = self.notify("Welcome to the ToDo console application!");
         loop {
             match self.display() {
-                Ok(option) => {
-                    if let Ok(continue_) = option.execute(self, manager) {
-                        if !continue_ {
-                            break;
-                        }
+                Ok(option) => match option.execute(self, manager) {
+                    Ok(true) => {}
+                    Ok(false) => break,
+                    Err(e) => {
+                        let _ = self.handle_error(&e);
                     }
-                }
+                },
                 Err(e) => {
                     let _ = self.handle_error(&e);
                 }
@@ -265,7 +499,7 @@ impl<R: BufRead + Send + Sync, W: Write + Send + Sync, E: LineEditor + Send + Sy
             writeln!(self.output, "{}", text).map_err(|e| format!("Failed to write: {}", e))?;
         }
 
-        let input = self._read_user_input(vec!["Enter your choice (1-5): ".into()])?;
+        let input = self._read_user_input(vec!["Enter your choice (1-7): ".into()])?;
 
         MenuOption::str_to_menuoption(input.trim())
     }
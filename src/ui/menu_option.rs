@@ -15,9 +15,12 @@ pub enum MenuOption {
     CompleteTask,
     RemoveTask,
     EditTask,
+    SortTasks,
+    SearchTasks,
     Exit,
     Undo,
     Redo,
+    History,
 }
 
 impl MenuOption {
@@ -53,12 +56,15 @@ impl MenuOption {
             MenuOption::CompleteTask => displayer.handle_toggle_task(manager),
             MenuOption::RemoveTask => displayer.handle_remove_task(manager),
             MenuOption::EditTask => displayer.handle_edit_task(manager),
+            MenuOption::SortTasks => displayer.handle_sort_tasks(manager),
+            MenuOption::SearchTasks => displayer.handle_search_tasks(manager),
             MenuOption::Exit => {
                 let _ = displayer.exit();
                 return Ok(false); // señal para salir del bucle
             }
             MenuOption::Undo => displayer.handle_undo(manager),
             MenuOption::Redo => displayer.handle_redo(manager),
+            MenuOption::History => displayer.handle_history(manager),
         }?;
         Ok(true)
     }
@@ -71,8 +77,11 @@ pub static MENU_OPTIONS: Lazy<Vec<(&'static str, &'static str, MenuOption)>> = L
         ("3. Complete Task", "3", MenuOption::CompleteTask),
         ("4. Remove Task", "4", MenuOption::RemoveTask),
         ("5. Edit Task", "5", MenuOption::EditTask),
+        ("6. List Tasks Sorted By...", "6", MenuOption::SortTasks),
+        ("7. Search Tasks", "7", MenuOption::SearchTasks),
         ("[E] Exit", "e", MenuOption::Exit),
         ("[U] Undo", "u", MenuOption::Undo),
         ("[R] Redo", "r", MenuOption::Redo),
+        ("[H] History", "h", MenuOption::History),
     ]
 });
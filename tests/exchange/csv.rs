@@ -0,0 +1,146 @@
+use chrono::Weekday;
+use to_do::error::TodoError;
+use to_do::exchange::csv::{ColumnMap, export, import};
+use to_do::model::priority::Priority;
+use to_do::model::recurrence::Recurrence;
+use to_do::model::task::TaskOptions;
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::console_ui::mock_displayer::MockDisplayer;
+
+#[test]
+fn test_parse_column_map() {
+    let map = ColumnMap::parse("description=Title, Priority = Prio,depends-on=3").unwrap();
+    assert_eq!(
+        ColumnMap::parse("description=Title,priority=Prio,depends_on=3"),
+        Ok(map)
+    );
+    for text in [
+        "",
+        "description",
+        "description=",
+        "title=Title",
+        "description=A,description=B",
+        "description=A,tags=a",
+    ] {
+        assert!(
+            ColumnMap::parse(text).is_err(),
+            "'{}' should be invalid",
+            text
+        );
+    }
+}
+
+#[test]
+fn test_import_with_mapping() {
+    let text = "\
+Title,Prio,Done,Notes,due
+\"Call mom, then dad\",high,yes,ignored,2026-10-20 17:00
+\"Say \"\"hi\"\"
+on two lines\",2,no,,
+Pay rent,urgent,no,,
+,low,no,,
+Water plants,low,maybe,,
+Book hotel,3,,,someday
+";
+    let map = ColumnMap::parse("description=Title,priority=Prio,completed=Done").unwrap();
+    let import = import(text, &map).unwrap();
+    assert_eq!(import.tasks.len(), 2);
+
+    let task = &import.tasks[0];
+    assert_eq!(task.description, "Call mom, then dad");
+    assert_eq!(task.priority, Priority::High);
+    assert!(task.completed);
+    assert_eq!(task.due.unwrap().to_string(), "2026-10-20 17:00");
+
+    let task = &import.tasks[1];
+    assert_eq!(task.description, "Say \"hi\"\non two lines");
+    assert_eq!(task.priority, Priority::Medium);
+    assert!(!task.completed);
+
+    assert_eq!(
+        import.warnings,
+        [
+            "Column 'Notes' is not imported",
+            "Line 5: invalid priority 'urgent', skipped",
+            "Line 6: no description, skipped",
+            "Line 7: invalid completed 'maybe', skipped",
+            "Line 8: invalid due 'someday', skipped",
+        ]
+    );
+}
+
+#[test]
+fn test_import_without_header() {
+    let map = ColumnMap::parse("description=2,priority=1").unwrap();
+    let import = import("medium,Buy milk\r\nhigh,Pay rent\r\n", &map).unwrap();
+    assert_eq!(import.tasks.len(), 2);
+    assert_eq!(import.tasks[1].description, "Pay rent");
+    assert_eq!(import.tasks[1].priority, Priority::High);
+
+    // Without mapping, the columns are the exported ones
+    let import = import_default("7,Buy milk,Low,false,,home;shop\n");
+    assert_eq!(import.tasks[0].id, "7");
+    assert_eq!(import.tasks[0].tags.len(), 2);
+
+    let map = ColumnMap::parse("description=Title").unwrap();
+    assert!(matches!(
+        to_do::exchange::csv::import("Buy milk\n", &map),
+        Err(TodoError::Validation(_))
+    ));
+    assert!(matches!(
+        to_do::exchange::csv::import("Title,Prio\n\"Buy milk\n", &map),
+        Err(TodoError::Parse(_))
+    ));
+}
+
+fn import_default(text: &str) -> to_do::exchange::format::Import {
+    import(text, &ColumnMap::default()).unwrap()
+}
+
+#[test]
+fn test_export_and_import_round_trip() {
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(MemoryStorage::new());
+    manager
+        .add_task("Plan the trip, with \"care\"".as_ref(), &Priority::High)
+        .unwrap();
+    let options = TaskOptions {
+        tags: ["travel".to_string(), "work".to_string()].into(),
+        parent: Some("1".to_string()),
+        recurrence: Some(Recurrence::Weekly {
+            weekdays: vec![Weekday::Mon, Weekday::Thu],
+        }),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Book the hotel".as_ref(), &Priority::Medium, options)
+        .unwrap();
+
+    let map = ColumnMap::parse("description=Title").unwrap();
+    let text = export(manager.get_tasks(), &map);
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "id,Title,priority,completed,due,tags,parent,depends_on,recurrence,\
+         created_at,updated_at,completed_at"
+    );
+    assert!(lines[1].starts_with("1,\"Plan the trip, with \"\"care\"\"\",High,false,"));
+    assert!(
+        lines[2].starts_with("2,Book the hotel,Medium,false,,travel;work,1,,\"weekly mon,thu\",")
+    );
+
+    let import = import(&text, &map).unwrap();
+    assert!(import.warnings.is_empty());
+    for (imported, task) in import.tasks.iter().zip(manager.get_tasks()) {
+        assert_eq!(imported, task);
+    }
+
+    let ids = manager.import_tasks(import.tasks).unwrap();
+    assert_eq!(ids, ["3", "4"]);
+    assert_eq!(manager.get_task("4").unwrap().parent.as_deref(), Some("3"));
+    assert_eq!(
+        manager.get_task("4").unwrap().created_at,
+        manager.get_task("2").unwrap().created_at
+    );
+}
@@ -0,0 +1,207 @@
+use super::trip_manager;
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use to_do::exchange::ical::{export, import, list_token};
+use to_do::model::priority::Priority;
+use to_do::model::recurrence::Recurrence;
+use to_do::model::task::TaskOptions;
+use to_do::service::manager::{Manager, ManagerTrait};
+
+/// Token of the exported todo list in the UIDs
+const TOKEN: &str = "1b4e28ba2fa1d2e4";
+
+fn manager() -> Manager {
+    let mut manager = trip_manager(|hotel| {
+        if let Some(due) = &mut hotel.due {
+            due.time = NaiveTime::from_hms_opt(17, 0, 0);
+        }
+        hotel.tags.insert("work".to_string());
+        hotel.parent = Some("1".to_string());
+        hotel.depends_on = ["1".to_string()].into();
+        hotel.recurrence = Some(Recurrence::Weekly {
+            weekdays: vec![Weekday::Mon, Weekday::Thu],
+        });
+    });
+    // Separators to escape in the summary
+    manager.get_task_mut("1").unwrap().description = "Plan the trip; pack, then go".to_string();
+    let options = TaskOptions {
+        recurrence: Some(Recurrence::AfterCompletion { days: 3 }),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Water plants".as_ref(), &Priority::Low, options)
+        .unwrap();
+    manager
+}
+
+#[test]
+fn test_export_vtodos() {
+    let mut manager = manager();
+    manager.toggle_task_status("1", false).unwrap();
+    let text = export(manager.get_tasks(), TOKEN);
+    assert!(text.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:"));
+    assert!(text.ends_with("END:VTODO\r\nEND:VCALENDAR\r\n"));
+    let lines = text.lines().collect::<Vec<_>>();
+    for line in [
+        "UID:1-1b4e28ba2fa1d2e4@todo",
+        r"SUMMARY:Plan the trip\; pack\, then go",
+        "PRIORITY:1",
+        "STATUS:COMPLETED",
+        "UID:2-1b4e28ba2fa1d2e4@todo",
+        "PRIORITY:5",
+        "STATUS:NEEDS-ACTION",
+        "DUE:20261103T170000",
+        "CATEGORIES:travel,work",
+        "RELATED-TO;RELTYPE=PARENT:1-1b4e28ba2fa1d2e4@todo",
+        "RELATED-TO;RELTYPE=DEPENDS-ON:1-1b4e28ba2fa1d2e4@todo",
+        "RRULE:FREQ=WEEKLY;BYDAY=MO,TH",
+        "PRIORITY:9",
+        "X-TODO-RECURRENCE:3d",
+    ] {
+        assert!(lines.contains(&line), "Missing line {}", line);
+    }
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|line| line.starts_with("CREATED:"))
+            .count(),
+        3
+    );
+    assert!(lines.iter().any(|line| line.starts_with("COMPLETED:")));
+}
+
+#[test]
+fn test_export_and_import_round_trip() {
+    let mut manager = manager();
+    // Long lines are folded, without splitting the characters
+    let description = "Réserver l'hôtel ".repeat(8);
+    manager
+        .add_task(description.trim_end(), &Priority::Low)
+        .unwrap();
+    let text = export(manager.get_tasks(), TOKEN);
+    assert!(text.lines().all(|line| line.len() <= 75));
+    let import = import(&text);
+    assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+    for (imported, task) in import.tasks.iter().zip(manager.get_tasks()) {
+        assert_eq!(imported, task);
+    }
+
+    let ids = manager.import_tasks(import.tasks).unwrap();
+    assert_eq!(ids, ["5", "6", "7", "8"]);
+    let hotel = manager.get_task("6").unwrap();
+    assert_eq!(hotel.parent.as_deref(), Some("5"));
+    assert!(hotel.depends_on.contains("5"));
+}
+
+#[test]
+fn test_import_reports_unsupported_content() {
+    let text = "\
+BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+SUMMARY:Meeting
+END:VEVENT
+BEGIN:VTODO
+UID:abc-123
+SUMMARY:Write the
+  report
+DESCRIPTION:Quarterly numbers
+PRIORITY:3
+STATUS:CANCELLED
+DUE;VALUE=DATE:20261103
+CATEGORIES:work,Q4
+RELATED-TO;RELTYPE=SIBLING:other
+RRULE:FREQ=YEARLY
+BEGIN:VALARM
+ACTION:DISPLAY
+END:VALARM
+END:VTODO
+BEGIN:VTODO
+DESCRIPTION:No summary
+END:VTODO
+BEGIN:VTODO
+SUMMARY:Sub
+RELATED-TO:abc-123
+PRIORITY:high
+END:VTODO
+END:VCALENDAR
+";
+    let import = import(text);
+    assert_eq!(import.tasks.len(), 2);
+    let task = &import.tasks[0];
+    assert_eq!(task.id, "abc-123");
+    assert_eq!(task.description, "Write the report");
+    assert_eq!(task.priority, Priority::High);
+    assert!(task.completed);
+    assert_eq!(task.due.unwrap().to_string(), "2026-11-03");
+    assert_eq!(task.tags, ["q4".to_string(), "work".to_string()].into());
+    assert_eq!(import.tasks[1].parent.as_deref(), Some("abc-123"));
+    assert_eq!(import.tasks[1].priority, Priority::Low);
+    assert_eq!(
+        import.warnings,
+        [
+            "Line 3: VEVENT component not imported",
+            "Line 12: status CANCELLED imported as completed",
+            "Line 15: unsupported relation SIBLING to 'other', dropped",
+            "Line 16: invalid RRULE 'FREQ=YEARLY', dropped",
+            "Task 'Write the report': unsupported DESCRIPTION, VALARM component dropped",
+            "Line 21: VTODO without SUMMARY, skipped",
+            "Line 27: invalid PRIORITY 'high', dropped",
+        ]
+    );
+}
+
+#[test]
+fn test_list_token_tells_lists_apart() {
+    let created_at = NaiveDate::from_ymd_opt(2026, 10, 18)
+        .unwrap()
+        .and_hms_opt(9, 30, 0);
+    let token = list_token("work.json", created_at);
+    assert_eq!(token.len(), 16);
+    assert_eq!(list_token("work.json", created_at), token);
+    assert_ne!(list_token("home.json", created_at), token);
+    assert_ne!(list_token("work.json", None), token);
+
+    // The UIDs exported before the token give back their IDs as well
+    let text = "\
+BEGIN:VTODO
+UID:1@todo
+SUMMARY:Plan the trip
+END:VTODO
+BEGIN:VTODO
+UID:2-1b4e28ba2fa1d2e4@todo
+SUMMARY:Book the hotel
+RELATED-TO:1@todo
+END:VTODO
+";
+    let import = import(text);
+    assert_eq!(import.tasks[0].id, "1");
+    assert_eq!(import.tasks[1].id, "2");
+    assert_eq!(import.tasks[1].parent.as_deref(), Some("1"));
+}
+
+#[test]
+fn test_import_due_time_zones() {
+    let text = "\
+BEGIN:VTODO
+SUMMARY:Call the office
+DUE;TZID=UTC:20261103T170000
+END:VTODO
+BEGIN:VTODO
+SUMMARY:Call Paris
+DUE;TZID=Europe/Paris:20261103T170000
+END:VTODO
+";
+    let import = import(text);
+    let utc = Utc
+        .with_ymd_and_hms(2026, 11, 3, 17, 0, 0)
+        .unwrap()
+        .with_timezone(&Local)
+        .naive_local();
+    let due = import.tasks[0].due.unwrap();
+    assert_eq!(due.date.and_time(due.time.unwrap()), utc);
+    assert_eq!(import.tasks[1].due.unwrap().to_string(), "2026-11-03 17:00");
+    assert_eq!(
+        import.warnings,
+        ["Line 7: unsupported time zone Europe/Paris of DUE, read as a local time"]
+    );
+}
@@ -0,0 +1,147 @@
+use super::trip_manager;
+use to_do::exchange::markdown::{Grouping, export, import};
+use to_do::model::priority::Priority;
+use to_do::model::task::TaskOptions;
+use to_do::service::manager::{Manager, ManagerTrait};
+
+fn manager() -> Manager {
+    let mut manager = trip_manager(|hotel| hotel.parent = Some("1".to_string()));
+    let options = TaskOptions {
+        tags: ["home".to_string(), "money".to_string()].into(),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Pay rent".as_ref(), &Priority::Low, options)
+        .unwrap();
+    manager.toggle_task_status("2", false).unwrap();
+    manager
+}
+
+#[test]
+fn test_export_groups() {
+    let manager = manager();
+    assert_eq!(
+        export(manager.get_tasks(), Grouping::Priority),
+        "\
+## High
+
+- [ ] Plan the trip
+  - [x] Book the hotel +travel (due 2026-11-03)
+
+## Low
+
+- [ ] Pay rent +home +money
+"
+    );
+    assert_eq!(
+        export(manager.get_tasks(), Grouping::Tag),
+        "\
+## home
+
+- [ ] Pay rent +home +money
+
+## No tag
+
+- [ ] Plan the trip
+  - [x] Book the hotel +travel (due 2026-11-03)
+"
+    );
+    assert_eq!(
+        export(manager.get_tasks(), Grouping::Flat),
+        "\
+- [ ] Plan the trip
+  - [x] Book the hotel +travel (due 2026-11-03)
+- [ ] Pay rent +home +money
+"
+    );
+}
+
+#[test]
+fn test_import_nested_items() {
+    let text = "\
+# Release checklist
+
+Some notes that are not tasks.
+
+## High
+
+- [ ] Ship it
+  - [x] Write the changelog +docs
+    * [ ] Proofread (due 2026-11-03 17:00)
+  - [X] Tag the release
+1. [ ] Announce it (due someday)
+- [ ]
+- not a checklist item
+
+### Later
+
+\t- [ ] Clean up
+";
+    let import = import(text);
+    let tasks = import
+        .tasks
+        .iter()
+        .map(|task| {
+            (
+                task.id.as_str(),
+                task.description.as_str(),
+                task.priority,
+                task.completed,
+                task.parent.as_deref(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tasks,
+        [
+            ("#7", "Ship it", Priority::High, false, None),
+            (
+                "#8",
+                "Write the changelog",
+                Priority::High,
+                true,
+                Some("#7")
+            ),
+            ("#9", "Proofread", Priority::High, false, Some("#8")),
+            ("#10", "Tag the release", Priority::High, true, Some("#7")),
+            (
+                "#11",
+                "Announce it (due someday)",
+                Priority::High,
+                false,
+                None
+            ),
+            ("#17", "Clean up", Priority::Low, false, None),
+        ]
+    );
+    assert_eq!(import.tasks[1].tags, ["docs".to_string()].into());
+    assert_eq!(import.tasks[2].due.unwrap().to_string(), "2026-11-03 17:00");
+    assert_eq!(
+        import.warnings,
+        [
+            "Line 11: invalid due date 'someday', kept in the description",
+            "Line 12: no description, skipped",
+        ]
+    );
+}
+
+#[test]
+fn test_export_and_import_round_trip() {
+    let mut manager = manager();
+    let text = export(manager.get_tasks(), Grouping::Priority);
+    let import = import(&text);
+    assert!(import.warnings.is_empty());
+    let ids = manager.import_tasks(import.tasks).unwrap();
+    assert_eq!(ids, ["4", "5", "6"]);
+    let hotel = manager.get_task("5").unwrap();
+    assert_eq!(hotel.description, "Book the hotel");
+    assert_eq!(hotel.parent.as_deref(), Some("4"));
+    assert!(hotel.completed);
+    // Subtasks take the priority of their group
+    assert_eq!(hotel.priority, Priority::High);
+    assert_eq!(hotel.due, manager.get_task("2").unwrap().due);
+    assert_eq!(
+        manager.get_task("6").unwrap().tags,
+        manager.get_task("3").unwrap().tags
+    );
+}
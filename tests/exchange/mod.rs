@@ -0,0 +1,37 @@
+use chrono::NaiveDate;
+use to_do::model::due::Due;
+use to_do::model::priority::Priority;
+use to_do::model::task::TaskOptions;
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::console_ui::mock_displayer::MockDisplayer;
+
+/// Todo list kept in memory with task 1 "Plan the trip" (High) and task 2
+/// "Book the hotel" (Medium) tagged travel and due on 2026-11-03, whose options
+/// each format changes with `hotel` to cover the fields it exchanges
+fn trip_manager(hotel: impl FnOnce(&mut TaskOptions)) -> Manager {
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(MemoryStorage::new());
+    manager
+        .add_task("Plan the trip".as_ref(), &Priority::High)
+        .unwrap();
+    let mut options = TaskOptions {
+        due: Some(Due {
+            date: NaiveDate::from_ymd_opt(2026, 11, 3).unwrap(),
+            time: None,
+        }),
+        tags: ["travel".to_string()].into(),
+        ..Default::default()
+    };
+    hotel(&mut options);
+    manager
+        .add_task_with_options("Book the hotel".as_ref(), &Priority::Medium, options)
+        .unwrap();
+    manager
+}
+
+mod csv;
+mod ical;
+mod markdown;
+mod taskwarrior;
+mod todo_txt;
@@ -0,0 +1,199 @@
+use super::trip_manager;
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone, Utc};
+use serde_json::Value;
+use to_do::exchange::taskwarrior::{export, import};
+use to_do::model::priority::Priority;
+use to_do::service::manager::{Manager, ManagerTrait};
+
+fn manager() -> Manager {
+    trip_manager(|hotel| {
+        hotel.tags = ["project:travel".to_string(), "work".to_string()].into();
+        hotel.depends_on = ["1".to_string()].into();
+    })
+}
+
+#[test]
+fn test_export_tasks() {
+    let mut manager = manager();
+    manager.toggle_task_status("1", false).unwrap();
+    let text = export(manager.get_tasks()).unwrap();
+    assert!(text.starts_with("[\n{\"uuid\":"));
+    assert!(text.ends_with("}\n]\n"));
+    let tasks = serde_json::from_str::<Vec<Value>>(&text).unwrap();
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0]["description"], "Plan the trip");
+    assert_eq!(tasks[0]["status"], "completed");
+    assert_eq!(tasks[0]["priority"], "H");
+    assert!(tasks[0]["end"].is_string());
+    assert!(tasks[0]["entry"].as_str().unwrap().ends_with('Z'));
+
+    assert_eq!(tasks[1]["status"], "pending");
+    assert_eq!(tasks[1]["priority"], "M");
+    assert_eq!(tasks[1]["project"], "travel");
+    assert_eq!(tasks[1]["tags"], serde_json::json!(["work"]));
+    assert_eq!(tasks[1]["depends"], serde_json::json!([tasks[0]["uuid"]]));
+    assert!(tasks[1].get("end").is_none());
+    // Due dates without time are due at local midnight
+    let midnight = Local
+        .from_local_datetime(
+            &NaiveDate::from_ymd_opt(2026, 11, 3)
+                .unwrap()
+                .and_time(NaiveTime::MIN),
+        )
+        .unwrap()
+        .with_timezone(&Utc);
+    assert_eq!(
+        tasks[1]["due"],
+        midnight.format("%Y%m%dT%H%M%SZ").to_string()
+    );
+
+    // The UUIDs do not change from one export to the next
+    assert_eq!(export(manager.get_tasks()).unwrap(), text);
+}
+
+#[test]
+fn test_import_tasks() {
+    let text = r#"[
+{"id":1,"uuid":"6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01","description":"Write the report","status":"pending","entry":"20261015T081500Z","due":"20261103T170000Z","priority":"H","project":"work.q4","tags":["Office","two words"],"urgency":8.2,"annotations":[{"entry":"20261015T081600Z","description":"draft"}],"scheduled":"20261101T000000Z"},
+{"id":0,"uuid":"0b2c4f7e-3e7f-4c2e-8d6b-7d7b1e3f2a02","description":"Collect the numbers","status":"completed","end":"20261016T090000Z","depends":"6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01,ffffffff-0000-0000-0000-000000000000"},
+{"id":0,"uuid":"5d0b6a52-7ad8-4e1c-9f54-8c1a4d2b3c03","description":"Old idea","status":"deleted"},
+{"id":0,"uuid":"8e9a7c31-2b4f-4d6e-a1c2-3f5e7d9b0c04","description":"Water plants","status":"recurring","recur":"weekly"},
+{"id":2,"description":"No UUID","status":"waiting","priority":"X"}
+]"#;
+    let import = import(text).unwrap();
+    assert_eq!(import.tasks.len(), 3);
+    let report = &import.tasks[0];
+    assert_eq!(report.id, "6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01");
+    assert_eq!(report.uuid.as_deref(), Some(report.id.as_str()));
+    assert_eq!(report.priority, Priority::High);
+    assert!(!report.completed);
+    assert_eq!(
+        report.tags,
+        ["office".to_string(), "project:work.q4".to_string()].into()
+    );
+    let due = Utc
+        .with_ymd_and_hms(2026, 11, 3, 17, 0, 0)
+        .unwrap()
+        .with_timezone(&Local)
+        .naive_local();
+    assert_eq!(report.due.unwrap().date, due.date());
+    assert!(report.created_at.is_some());
+
+    let numbers = &import.tasks[1];
+    assert!(numbers.completed);
+    assert!(numbers.completed_at.is_some());
+    assert_eq!(numbers.priority, Priority::Low);
+    assert_eq!(numbers.depends_on, [report.id.clone()].into());
+
+    let waiting = &import.tasks[2];
+    assert_eq!(waiting.id, "#5");
+    assert_eq!(waiting.uuid, None);
+    assert!(!waiting.completed);
+    assert_eq!(
+        import.warnings,
+        [
+            "Task 1: invalid tag \"two words\", dropped",
+            "Task 'Write the report': unsupported annotations, scheduled dropped",
+            "Task 3: 'Old idea' was deleted, skipped",
+            "Task 4: 'Water plants' is the template of a recurring task, skipped",
+            "Task 5: invalid priority \"X\", dropped",
+            "Task 'Collect the numbers': unknown dependency \
+             'ffffffff-0000-0000-0000-000000000000', dropped",
+        ]
+    );
+}
+
+#[test]
+fn test_import_keeps_uuid_for_round_trip() {
+    let text = "\
+{\"uuid\":\"6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01\",\"description\":\"Write the report\",\"status\":\"pending\",\"priority\":\"M\"}
+{\"uuid\":\"0b2c4f7e-3e7f-4c2e-8d6b-7d7b1e3f2a02\",\"description\":\"Collect the numbers\",\"status\":\"pending\",\"depends\":[\"6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01\"]}
+";
+    let mut manager = manager();
+    let imported = import(text).unwrap();
+    assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+    let ids = manager.import_tasks(imported.tasks).unwrap();
+    assert_eq!(ids, ["3", "4"]);
+    assert!(manager.get_task("4").unwrap().depends_on.contains("3"));
+
+    let exported =
+        serde_json::from_str::<Vec<Value>>(&export(manager.get_tasks()).unwrap()).unwrap();
+    assert_eq!(exported[2]["uuid"], "6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01");
+    assert_eq!(exported[3]["uuid"], "0b2c4f7e-3e7f-4c2e-8d6b-7d7b1e3f2a02");
+    assert_eq!(
+        exported[3]["depends"],
+        serde_json::json!(["6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01"])
+    );
+
+    // Importing the same tasks again leaves them out
+    let mut again = import(text).unwrap();
+    again.skip_existing(manager.get_tasks(), &[]);
+    assert!(again.tasks.is_empty());
+    assert_eq!(
+        again.warnings,
+        [
+            "Task 'Write the report': already in the todo list as task 3, skipped",
+            "Task 'Collect the numbers': already in the todo list as task 4, skipped",
+        ]
+    );
+}
+
+#[test]
+fn test_import_own_export_adds_nothing() {
+    let mut manager = manager();
+    manager.archive_tasks(&["2".to_string()]).unwrap();
+    manager
+        .add_task("Buy milk".as_ref(), &Priority::Low)
+        .unwrap();
+    let mut tasks = manager.get_tasks().clone();
+    tasks.extend(manager.todo_list.archived_tasks().unwrap());
+    let text = export(&tasks).unwrap();
+
+    let mut again = import(&text).unwrap();
+    let archived = manager.todo_list.archived_tasks().unwrap();
+    again.skip_existing(manager.get_tasks(), &archived);
+    assert!(again.tasks.is_empty());
+    assert_eq!(
+        again.warnings,
+        [
+            "Task 'Plan the trip': already in the todo list as task 1, skipped",
+            "Task 'Buy milk': already in the todo list as task 3, skipped",
+            "Task 'Book the hotel': already in the archive as task 2, skipped",
+        ]
+    );
+}
+
+#[test]
+fn test_import_links_to_existing_tasks() {
+    let report = "{\"uuid\":\"6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01\",\"description\":\"Write the report\",\"status\":\"pending\"}";
+    let numbers = "{\"uuid\":\"0b2c4f7e-3e7f-4c2e-8d6b-7d7b1e3f2a02\",\"description\":\"Collect the numbers\",\"status\":\"completed\"}";
+    let summary = "{\"uuid\":\"5d0b6a52-7ad8-4e1c-9f54-8c1a4d2b3c03\",\"description\":\"Send the summary\",\"status\":\"pending\",\"depends\":\"6f1a6e0e-1c7d-4d7b-9a38-0c6c0f4c1a01,0b2c4f7e-3e7f-4c2e-8d6b-7d7b1e3f2a02\"}";
+    let mut manager = manager();
+    let first = import(&format!("{}\n{}", report, numbers)).unwrap();
+    assert_eq!(manager.import_tasks(first.tasks).unwrap(), ["3", "4"]);
+    manager.archive_tasks(&["4".to_string()]).unwrap();
+
+    // The dependencies on the skipped tasks follow the task of the todo list
+    let mut again = import(&format!("{}\n{}\n{}", report, numbers, summary)).unwrap();
+    let archived = manager.todo_list.archived_tasks().unwrap();
+    again.skip_existing(manager.get_tasks(), &archived);
+    assert_eq!(
+        again.warnings,
+        [
+            "Task 'Write the report': already in the todo list as task 3, skipped",
+            "Task 'Collect the numbers': already in the archive as task 4, skipped",
+            "Task 'Send the summary': dependency \
+             '0b2c4f7e-3e7f-4c2e-8d6b-7d7b1e3f2a02' is archived, dropped",
+        ]
+    );
+    assert_eq!(manager.import_tasks(again.tasks).unwrap(), ["5"]);
+    assert_eq!(
+        manager.get_task("5").unwrap().depends_on,
+        ["3".to_string()].into()
+    );
+}
+
+#[test]
+fn test_import_invalid_json() {
+    assert!(import("[{\"description\": }]").is_err());
+}
@@ -0,0 +1,133 @@
+use chrono::{NaiveDate, NaiveTime};
+use to_do::exchange::todo_txt::{export, import};
+use to_do::model::due::Due;
+use to_do::model::priority::Priority;
+use to_do::model::recurrence::Recurrence;
+use to_do::model::task::TaskOptions;
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::console_ui::mock_displayer::MockDisplayer;
+
+fn date(text: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_import_lines() {
+    let text = "\
+(A) 2026-10-01 Call mom @phone +family due:2026-10-20 url:http://example.com
+x 2026-10-18 2026-10-02 Pay rent +home pri:B
+(D) Someday task
+Water plants rec:+1w due:tomorrow
+
++only_tags
+";
+    let import = import(text);
+    assert_eq!(import.tasks.len(), 4);
+
+    let task = &import.tasks[0];
+    assert_eq!(task.description, "Call mom @phone url:http://example.com");
+    assert_eq!(task.priority, Priority::High);
+    assert_eq!(task.tags, ["family".to_string()].into());
+    assert_eq!(task.due.unwrap().date, date("2026-10-20"));
+    assert_eq!(task.created_at.unwrap().date(), date("2026-10-01"));
+
+    let task = &import.tasks[1];
+    assert!(task.completed);
+    assert_eq!(task.priority, Priority::Medium);
+    assert_eq!(task.completed_at.unwrap().date(), date("2026-10-18"));
+    assert_eq!(task.created_at.unwrap().date(), date("2026-10-02"));
+
+    assert_eq!(import.tasks[2].priority, Priority::Low);
+    assert_eq!(
+        import.tasks[3].recurrence,
+        Some(Recurrence::Weekly {
+            weekdays: Vec::new()
+        })
+    );
+    assert_eq!(import.tasks[3].description, "Water plants due:tomorrow");
+    assert_eq!(
+        import.warnings,
+        [
+            "Line 3: priority (D) imported as Low",
+            "Line 4: invalid value 'due:tomorrow', kept in the description",
+            "Line 6: no description, skipped",
+        ]
+    );
+}
+
+#[test]
+fn test_import_links_tasks() {
+    let text = "\
+Plan the trip id:trip
+Book the hotel parent:trip dep:visa
+Get a visa id:visa
+Pack parent:unknown
+";
+    let import = import(text);
+    assert_eq!(import.tasks[1].parent.as_deref(), Some("trip"));
+    assert!(import.tasks[1].depends_on.contains("visa"));
+    assert_eq!(import.tasks[3].parent, None);
+    assert_eq!(
+        import.warnings,
+        ["Task 'Pack': unknown parent 'unknown', imported as a top level task"]
+    );
+}
+
+#[test]
+fn test_export_and_import_round_trip() {
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(MemoryStorage::new());
+    manager
+        .add_task("Plan the trip".as_ref(), &Priority::High)
+        .unwrap();
+    let options = TaskOptions {
+        due: Some(Due {
+            date: date("2026-11-03"),
+            time: NaiveTime::from_hms_opt(17, 0, 0),
+        }),
+        tags: ["travel".to_string()].into(),
+        parent: Some("1".to_string()),
+        recurrence: Some(Recurrence::Monthly { day: Some(15) }),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Book the hotel".as_ref(), &Priority::Medium, options)
+        .unwrap();
+    // Completing the recurring task adds its next occurrence 3
+    manager.toggle_task_status("2", false).unwrap();
+
+    let text = export(manager.get_tasks());
+    let lines = text.lines().collect::<Vec<_>>();
+    assert!(lines[0].starts_with("(A) "));
+    assert!(lines[0].ends_with(" Plan the trip id:1"));
+    assert!(lines[1].starts_with("x "));
+    assert!(
+        lines[1].ends_with(
+            " Book the hotel +travel due:2026-11-03T17:00 rec:monthly_15 parent:1 pri:B"
+        )
+    );
+
+    // Imported tasks get fresh IDs, and the import is undone as a whole
+    let import = import(&text);
+    assert!(import.warnings.is_empty());
+    let ids = manager.import_tasks(import.tasks).unwrap();
+    assert_eq!(ids, ["4", "5", "6"]);
+    let imported = manager.get_task("5").unwrap();
+    assert_eq!(imported.parent.as_deref(), Some("4"));
+    assert_eq!(imported.options(), {
+        let mut options = manager.get_task("2").unwrap().options();
+        options.parent = Some("4".to_string());
+        options
+    });
+    assert!(imported.completed);
+    assert_eq!(
+        manager.undo_stack.last().unwrap().to_string(),
+        "Import tasks 4, 5, 6"
+    );
+
+    manager.undo().expect("Undo failed");
+    assert_eq!(manager.get_tasks().len(), 3);
+    manager.redo().expect("Redo failed");
+    assert_eq!(manager.get_tasks().len(), 6);
+}
@@ -4,7 +4,7 @@ use std::process::{Command, Stdio};
 use tempfile::TempDir;
 
 /// Helper function to run the application in a temporary directory with given input and capture output.
-/// This ensures the test case isolation by using a new todo_list.json for each test.
+/// This ensures the test case isolation by using a new data directory for each test.
 fn run_app_with_input(input: &str) -> io::Result<String> {
     let temp_dir = TempDir::new()?;
     let prev_dir = env::current_dir()?;
@@ -12,6 +12,8 @@ fn run_app_with_input(input: &str) -> io::Result<String> {
 
     let exe = assert_cmd::cargo::cargo_bin("ToDo");
     let mut child = Command::new(exe)
+        .env("XDG_DATA_HOME", temp_dir.path())
+        .env_remove("TODO_FILE")
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -54,7 +56,7 @@ fn test_console_menu_display_and_invalid_input() {
 /// Test adding a task with description and priority, then list to verify
 #[test]
 fn test_add_task() {
-    let input = "1\nTest Task Description\n2\n2\ne\n"; // Add task, desc, priority Medium (2), list, exit
+    let input = "1\nTest Task Description\n2\n\n2\ne\n"; // Add task, desc, priority Medium (2), no due date, list, exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(output.contains("You selected: Add Task"));
@@ -74,7 +76,7 @@ fn test_add_task() {
 /// Test adding a task with invalid priority (defaults to Low)
 #[test]
 fn test_add_task_default_priority() {
-    let input = "1\nLow Priority Task\ninvalid\n3\n2\ne\n"; // Add, desc, invalid priority, valid priority (1), list, exit
+    let input = "1\nLow Priority Task\ninvalid\n3\n\n2\ne\n"; // Add, desc, invalid priority, valid priority (1), no due date, list, exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(output.contains("Invalid priority, please type again a valid one."));
@@ -97,7 +99,7 @@ fn test_list_tasks_empty() {
 /// Test listing tasks with multiple entries
 #[test]
 fn test_list_tasks_with_entries() {
-    let input = "1\nTask1\n1\n1\nTask2\n3\n2\ne\n"; // Add Task1 High, Add Task2 Low, List, Exit
+    let input = "1\nTask1\n1\n\n1\nTask2\n3\n\n2\ne\n"; // Add Task1 High, Add Task2 Low, List, Exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(output.contains("ID: 1, Description: Task1, Priority: High, Completed: false"));
@@ -107,7 +109,7 @@ fn test_list_tasks_with_entries() {
 /// Test completing a task (toggle status)
 #[test]
 fn test_complete_task_success() {
-    let input = "1\nTask to Complete\n1\n3\n1\ne\n"; // Add task High, Complete with ID 1, Exit
+    let input = "1\nTask to Complete\n1\n\n3\n1\ne\n"; // Add task High, Complete with ID 1, Exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(output.contains("You selected: Complete Task"));
@@ -127,7 +129,7 @@ fn test_complete_task_not_found() {
 /// Test deleting a task
 #[test]
 fn test_delete_task_success() {
-    let input = "1\nTask to Delete\n1\n4\n1\ne\n"; // Add task, Delete with ID 1, Exit
+    let input = "1\nTask to Delete\n1\n\n4\n1\ne\n"; // Add task, Delete with ID 1, Exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(output.contains("You selected: Remove Task"));
@@ -147,7 +149,7 @@ fn test_delete_task_not_found() {
 /// Test Undo after adding a task
 #[test]
 fn test_undo_after_add() {
-    let input = "1\nTask to Undo\n1\nU\n2\ne\n"; // Add task, Undo, List (should be empty), Exit
+    let input = "1\nTask to Undo\n1\n\nU\n2\ne\n"; // Add task, Undo, List (should be empty), Exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(!output.contains("ID:"));
@@ -157,7 +159,7 @@ fn test_undo_after_add() {
 /// Test Redo after Undo the adding of a task
 #[test]
 fn test_redo_after_undo() {
-    let input = "1\nTask to Redo\n1\nU\nR\n2\ne\n"; // Add task, Undo, Redo, List, Exit
+    let input = "1\nTask to Redo\n1\n\nU\nR\n2\ne\n"; // Add task, Undo, Redo, List, Exit
     let output = run_app_with_input(input).expect("Failed to run app");
 
     assert!(output.contains("Task added."));
@@ -165,3 +167,602 @@ fn test_redo_after_undo() {
     assert!(output.contains("Redo operation successful."));
     assert!(output.contains("Description: Task to Redo, Priority: High, Completed: false"));
 }
+
+/// Test that the search matches are not highlighted when the output is not a terminal
+#[test]
+fn test_search_output_to_pipe_is_not_highlighted() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    run(&["add", "-d", "Buy milk"]);
+    let output = run(&["search", "milk"]);
+    assert!(output.contains("Description: Buy milk,"));
+    assert!(!output.contains('\x1b'));
+}
+
+/// Test that command line errors are notified and mapped to their exit codes
+#[test]
+fn test_cli_error_exit_codes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+
+    let output = Command::new(&exe)
+        .args(["remove", "-i", "999"])
+        .env("XDG_DATA_HOME", temp_dir.path())
+        .env_remove("TODO_FILE")
+        .output()
+        .expect("Failed to run app");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Error: Task with ID: 999 not found"));
+
+    // Usage errors of the arguments and rejected values have their own codes
+    let output = Command::new(&exe)
+        .args(["add", "--bogus"])
+        .env("XDG_DATA_HOME", temp_dir.path())
+        .env_remove("TODO_FILE")
+        .output()
+        .expect("Failed to run app");
+    assert_eq!(output.status.code(), Some(2));
+    let output = Command::new(&exe)
+        .args(["add", "-d", "Task", "--due", "someday"])
+        .env("XDG_DATA_HOME", temp_dir.path())
+        .env_remove("TODO_FILE")
+        .output()
+        .expect("Failed to run app");
+    assert_eq!(output.status.code(), Some(8));
+
+    // An unreadable todo list is reported and left untouched
+    let file = temp_dir.path().join("todo_list.json");
+    std::fs::write(&file, "not json").expect("Failed to write file");
+    let output = Command::new(&exe)
+        .args(["list"])
+        .env("TODO_FILE", &file)
+        .output()
+        .expect("Failed to run app");
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Error: Failed to deserialize tasks"));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "not json");
+}
+
+/// Test that the todo list file is chosen by --file, --list, TODO_FILE and the data directory
+#[test]
+fn test_storage_location_and_named_lists() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let run = |args: &[&str], env_file: Option<&str>| {
+        let mut command = Command::new(&exe);
+        command
+            .args(args)
+            .current_dir(&temp_dir)
+            .env("XDG_DATA_HOME", temp_dir.path())
+            .env_remove("TODO_FILE");
+        if let Some(env_file) = env_file {
+            command.env("TODO_FILE", env_file);
+        }
+        let output = command.output().expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    run(&["add", "-d", "Default task"], None);
+    assert!(temp_dir.path().join("todo/default.json").exists());
+    run(&["add", "-d", "File task", "--file", "mine.json"], None);
+    assert!(temp_dir.path().join("mine.json").exists());
+    run(&["add", "-d", "Env task"], Some("env.json"));
+    assert!(temp_dir.path().join("env.json").exists());
+
+    let (code, output) = run(&["--list", "work", "list"], None);
+    assert_eq!(code, Some(3));
+    assert!(output.contains("Error: List 'work' not found"));
+
+    run(&["lists", "create", "work"], None);
+    run(
+        &["--list", "work", "add", "-d", "Work task"],
+        Some("env.json"),
+    );
+    let (_, output) = run(&["list", "--list", "work"], None);
+    assert!(output.contains("Description: Work task"));
+    assert!(!output.contains("Default task"));
+
+    let (_, output) = run(&["lists"], None);
+    assert!(output.contains("[default: 1 tasks]"));
+    assert!(output.contains("[work: 1 tasks]"));
+}
+
+/// Test that every save keeps a backup which the restore subcommand brings back
+#[test]
+fn test_restore_backups() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .env_remove("TODO_BACKUPS")
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (_, output) = run(&["restore"]);
+    assert!(output.contains("No backups found."));
+
+    std::fs::write(&file, "not json").expect("Failed to write file");
+    let (code, output) = run(&["list"]);
+    assert_eq!(code, Some(4));
+    assert!(!output.contains("ToDo restore"));
+    std::fs::remove_file(&file).expect("Failed to remove file");
+
+    run(&["add", "-d", "First task"]);
+    run(&["add", "-d", "Second task"]);
+    std::fs::write(&file, "not json").expect("Failed to write file");
+    let (code, output) = run(&["list"]);
+    assert_eq!(code, Some(4));
+    assert!(output.contains("Run `ToDo restore` to list its backups."));
+
+    let (_, output) = run(&["restore"]);
+    assert!(output.contains("1: saved "));
+    assert!(output.contains(", 1 tasks"));
+
+    let (code, output) = run(&["restore", "1"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Backup 1 restored"));
+    let (_, output) = run(&["list"]);
+    assert!(output.contains("First task"));
+    assert!(!output.contains("Second task"));
+
+    let (code, output) = run(&["--output", "json", "restore"]);
+    assert_eq!(code, Some(0));
+    let backups: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(backups[0]["tasks"], serde_json::Value::Null);
+    assert_eq!(backups[1]["tasks"], 1);
+}
+
+/// Test that ToDo processes adding tasks at the same time do not lose each other's tasks
+#[test]
+fn test_concurrent_adds() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+
+    let children = (0..8)
+        .map(|i| {
+            Command::new(&exe)
+                .args(["add", "-d", &format!("Task {}", i)])
+                .env("TODO_FILE", &file)
+                .stdout(Stdio::null())
+                .spawn()
+                .expect("Failed to run app")
+        })
+        .collect::<Vec<_>>();
+    for mut child in children {
+        assert!(child.wait().expect("Failed to wait for app").success());
+    }
+
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    let mut ids = stored["tasks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["id"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 8);
+}
+
+/// Test that the migrate subcommand checks and upgrades a todo list saved as a bare array
+#[test]
+fn test_migrate_old_todo_list() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let old = r#"[{"id":"1","description":"Buy milk","priority":"Low","completed":false}]"#;
+    std::fs::write(&file, old).expect("Failed to write file");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (code, output) = run(&["migrate", "--dry-run"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("would be migrated from schema version 1 to 2"));
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), old);
+
+    let (_, output) = run(&["--output", "json", "migrate"]);
+    let record: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(record["from_version"], 1);
+    assert_eq!(record["dry_run"], false);
+
+    let (_, output) = run(&["migrate"]);
+    assert!(output.contains("is up to date (schema version 2)"));
+    let (_, output) = run(&["list"]);
+    assert!(output.contains("Buy milk"));
+}
+
+/// Test that the actions of previous commands can be undone, redone and listed
+#[test]
+fn test_persistent_undo_history() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .env("TODO_HISTORY_LIMIT", "2")
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (code, output) = run(&["undo"]);
+    assert_eq!(code, Some(8));
+    assert!(output.contains("Nothing to undo"));
+
+    run(&["add", "-d", "First task"]);
+    run(&["add", "-d", "Second task"]);
+    run(&["add", "-d", "Third task"]);
+    let (code, output) = run(&["undo"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Undone: Add task 3 \"Third task\" (Low)"));
+    let (_, output) = run(&["list"]);
+    assert!(!output.contains("Third task"));
+
+    let (_, output) = run(&["history"]);
+    assert!(output.contains("[Undo history (most recent first):]\n[1: "));
+    assert!(output.contains(" Add task 2 \"Second task\" (Low)]\n[Redo history"));
+
+    let (_, output) = run(&["--output", "json", "history"]);
+    let history: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(history[1]["state"], "redo");
+    assert_eq!(history[1]["description"], "Add task 3 \"Third task\" (Low)");
+
+    let (code, output) = run(&["--output", "json", "redo"]);
+    assert_eq!(code, Some(0));
+    let action: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(action["action"], "redo");
+    assert_eq!(action["id"], "3");
+
+    // Only the last two actions are kept
+    run(&["undo"]);
+    run(&["undo"]);
+    let (code, _) = run(&["undo"]);
+    assert_eq!(code, Some(8));
+    let (_, output) = run(&["list"]);
+    assert!(output.contains("First task"));
+    assert!(!output.contains("Second task"));
+}
+
+/// Test going back to an earlier point of the history from the command line
+#[test]
+fn test_undo_to_position() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .env_remove("TODO_HISTORY_LIMIT")
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    run(&["add", "-d", "First task"]);
+    run(&["add", "-d", "Second task"]);
+    run(&["toggle-status", "-i", "1"]);
+
+    let (code, output) = run(&["undo", "--action", "2"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Undone: Add task 2 \"Second task\" (Low)"));
+
+    let (code, output) = run(&["undo", "--to", "2"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Undone 2 actions:"));
+    assert!(output.contains("- Complete task 1"));
+    let (_, output) = run(&["list"]);
+    assert!(output.contains("No tasks found"));
+
+    let (code, _) = run(&["undo", "--to", "1"]);
+    assert_eq!(code, Some(8));
+}
+
+/// Test archiving the completed tasks, listing and unarchiving them
+#[test]
+fn test_archive_and_unarchive() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let run = |args: &[&str], archive_on_complete: &str| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .env("TODO_ARCHIVE_ON_COMPLETE", archive_on_complete)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    run(&["add", "-d", "First task"], "false");
+    run(&["add", "-d", "Second task"], "false");
+    run(&["toggle-status", "-i", "1"], "false");
+
+    let (code, output) = run(&["archive", "--older-than", "30"], "false");
+    assert_eq!(code, Some(0));
+    assert!(output.contains("No completed tasks to archive, nothing was changed."));
+
+    let (code, output) = run(&["archive"], "false");
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Task archived successfully."));
+    let (_, output) = run(&["list"], "false");
+    assert!(output.contains("1 tasks found"));
+    assert!(!output.contains("First task"));
+    let (_, output) = run(&["list", "--archived"], "false");
+    assert!(output.contains("1 archived tasks found"));
+    assert!(output.contains("First task"));
+
+    let (code, output) = run(&["unarchive", "-i", "1"], "false");
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Task unarchived successfully."));
+    let (code, output) = run(&["undo"], "false");
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Undone: Unarchive task 1 \"First task\""));
+
+    // Completed tasks go straight to the archive
+    run(&["toggle-status", "-i", "2"], "true");
+    let (_, output) = run(&["list", "--archived"], "false");
+    assert!(output.contains("2 archived tasks found"));
+    let (code, _) = run(&["unarchive", "-i", "5"], "false");
+    assert_eq!(code, Some(3));
+    let (code, _) = run(&["list"], "maybe");
+    assert_eq!(code, Some(8));
+}
+
+#[test]
+fn test_export_and_import_todo_txt() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let exported = temp_dir.path().join("todo.txt");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    run(&["add", "-d", "Call mom", "-p", "high"]);
+    run(&["edit", "-i", "1", "-t", "family"]);
+    run(&["add", "-d", "Pay rent"]);
+    run(&["toggle-status", "-i", "2"]);
+
+    // Only the tasks are written to the standard output
+    let (code, output) = run(&["export", "--format", "todo-txt"]);
+    assert_eq!(code, Some(0));
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("(A) ") && lines[0].ends_with(" Call mom +family"));
+    assert!(lines[1].starts_with("x ") && lines[1].ends_with(" Pay rent pri:C"));
+
+    let exported_arg = exported.to_str().unwrap();
+    let (code, output) = run(&["export", exported_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("2 tasks exported to"));
+
+    let (code, output) = run(&["import", "--dry-run", exported_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Dry run, nothing was changed. 2 tasks would be imported:"));
+    let (_, output) = run(&["list"]);
+    assert!(output.contains("2 tasks found"));
+
+    let (code, output) = run(&["import", exported_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("2 tasks imported successfully."));
+    let (_, output) = run(&["list"]);
+    assert!(output.contains("4 tasks found"));
+
+    let (code, output) = run(&["undo"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Undone: Import tasks 3, 4"));
+
+    // The format cannot be deduced from the extension
+    let (code, _) = run(&["import", file.to_str().unwrap()]);
+    assert_eq!(code, Some(8));
+}
+
+#[test]
+fn test_import_csv_with_mapping() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let sheet = temp_dir.path().join("sheet.csv");
+    std::fs::write(
+        &sheet,
+        "Title,Prio\n\"Buy milk, eggs\",High\nPay rent,urgent\n",
+    )
+    .expect("Failed to write the CSV file");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+    let sheet = sheet.to_str().unwrap();
+
+    let (code, output) = run(&["import", sheet, "--map", "description=Title,priority=Prio"]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Line 3: invalid priority 'urgent', skipped"));
+    assert!(output.contains("1 tasks imported successfully."));
+
+    let (code, output) = run(&["export", "--format", "csv"]);
+    assert_eq!(code, Some(0));
+    assert!(output.starts_with("id,description,priority,"));
+    assert!(output.contains("1,\"Buy milk, eggs\",High,false,"));
+
+    // The mapping only applies to CSV, and must name existing columns
+    let (code, _) = run(&[
+        "export",
+        "--format",
+        "todo-txt",
+        "--map",
+        "description=Title",
+    ]);
+    assert_eq!(code, Some(8));
+    let (code, _) = run(&["import", sheet, "--map", "description=Name"]);
+    assert_eq!(code, Some(8));
+}
+
+#[test]
+fn test_export_and_import_markdown() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let checklist = temp_dir.path().join("checklist.md");
+    std::fs::write(
+        &checklist,
+        "## Medium\n\n- [ ] Release\n  - [x] Changelog\n",
+    )
+    .expect("Failed to write the checklist");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+
+    let (code, output) = run(&["import", checklist.to_str().unwrap()]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("2 tasks imported successfully."));
+
+    let (code, output) = run(&["export", "--format", "md", "--group-by", "flat"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(output, "- [ ] Release\n  - [x] Changelog\n");
+
+    let (code, _) = run(&["export", "--format", "csv", "--group-by", "tag"]);
+    assert_eq!(code, Some(8));
+}
+
+#[test]
+fn test_export_and_import_ical() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let calendar = temp_dir.path().join("tasks.ics");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+    let calendar_arg = calendar.to_str().unwrap();
+
+    run(&["add", "-d", "Call mom", "-p", "high", "--due", "2026-11-03"]);
+    let (code, output) = run(&["export", calendar_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("1 tasks exported to"));
+    let text = std::fs::read_to_string(&calendar).expect("Failed to read the calendar");
+    assert!(text.contains("BEGIN:VTODO\r\nUID:1-"));
+    assert!(text.contains("@todo\r\n"));
+    assert!(text.contains("DUE;VALUE=DATE:20261103\r\n"));
+
+    std::fs::write(
+        &calendar,
+        text.replace("END:VTODO", "LOCATION:Home\r\nEND:VTODO"),
+    )
+    .expect("Failed to write the calendar");
+    let (code, output) = run(&["import", calendar_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Task 'Call mom': unsupported LOCATION dropped"));
+    assert!(output.contains("1 tasks imported successfully."));
+    let (_, output) = run(&["list", "-p", "high"]);
+    assert!(output.contains("2 tasks found"));
+}
+
+#[test]
+fn test_export_and_import_taskwarrior() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let exe = assert_cmd::cargo::cargo_bin("ToDo");
+    let file = temp_dir.path().join("todo.json");
+    let export = temp_dir.path().join("tasks.json");
+    let run = |args: &[&str]| {
+        let output = Command::new(&exe)
+            .args(args)
+            .env("TODO_FILE", &file)
+            .output()
+            .expect("Failed to run app");
+        (
+            output.status.code(),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    };
+    let export_arg = export.to_str().unwrap();
+
+    run(&["add", "-d", "Call mom", "-p", "high"]);
+    run(&["edit", "-i", "1", "-t", "project:family"]);
+    let (code, output) = run(&["export", "--format", "taskwarrior", export_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("1 tasks exported to"));
+    let text = std::fs::read_to_string(&export).expect("Failed to read the export");
+    assert!(text.contains("\"priority\":\"H\""));
+    assert!(text.contains("\"project\":\"family\""));
+
+    // The exported task has the UUID of its export, so importing it back leaves it out
+    let (code, output) = run(&["import", "--format", "tw", export_arg]);
+    assert_eq!(code, Some(0));
+    assert!(output.contains("Task 'Call mom': already in the todo list as task 1, skipped"));
+    assert!(output.contains("No tasks to import, nothing was changed."));
+}
@@ -7,9 +7,48 @@ mod ui {
     mod cli_argument_parser {
         #[path = "cli_parser.rs"]
         mod cli_parser;
+        #[path = "output_format.rs"]
+        mod output_format;
     }
 }
+mod exchange;
+mod service {
+    #[path = "archive.rs"]
+    mod archive;
+    #[path = "history.rs"]
+    mod history;
+    #[path = "lists.rs"]
+    mod lists;
+    #[path = "transaction.rs"]
+    mod transaction;
+}
+mod storage {
+    #[path = "backend.rs"]
+    mod backend;
+    #[path = "file.rs"]
+    mod file;
+    #[path = "lock.rs"]
+    mod lock;
+    #[path = "schema.rs"]
+    mod schema;
+}
 mod model {
-        #[path = "todo_list.rs"]
-        mod todo_list;
+    #[path = "due.rs"]
+    mod due;
+    #[path = "merge.rs"]
+    mod merge;
+    #[path = "recurrence.rs"]
+    mod recurrence;
+    #[path = "search.rs"]
+    mod search;
+    #[path = "selection.rs"]
+    mod selection;
+    #[path = "sort.rs"]
+    mod sort;
+    #[path = "tags.rs"]
+    mod tags;
+    #[path = "timestamp.rs"]
+    mod timestamp;
+    #[path = "todo_list.rs"]
+    mod todo_list;
 }
@@ -0,0 +1,94 @@
+use chrono::{NaiveDate, NaiveTime};
+use to_do::model::due::Due;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Invalid test date")
+}
+
+// 2026-10-18 is a Sunday
+fn today() -> NaiveDate {
+    date(2026, 10, 18)
+}
+
+#[test]
+fn test_parse_absolute_date() {
+    let due = Due::parse_relative_to("2026-11-03", today()).expect("Failed to parse");
+    assert_eq!(due.date, date(2026, 11, 3));
+    assert_eq!(due.time, None);
+}
+
+#[test]
+fn test_parse_keywords() {
+    assert_eq!(
+        Due::parse_relative_to("today", today()).unwrap().date,
+        today()
+    );
+    assert_eq!(
+        Due::parse_relative_to("Tomorrow", today()).unwrap().date,
+        date(2026, 10, 19)
+    );
+    assert_eq!(
+        Due::parse_relative_to("yesterday", today()).unwrap().date,
+        date(2026, 10, 17)
+    );
+}
+
+#[test]
+fn test_parse_offsets() {
+    assert_eq!(
+        Due::parse_relative_to("+3d", today()).unwrap().date,
+        date(2026, 10, 21)
+    );
+    assert_eq!(
+        Due::parse_relative_to("+2w", today()).unwrap().date,
+        date(2026, 11, 1)
+    );
+    assert_eq!(
+        Due::parse_relative_to("+1m", today()).unwrap().date,
+        date(2026, 11, 18)
+    );
+}
+
+#[test]
+fn test_parse_weekdays() {
+    assert_eq!(
+        Due::parse_relative_to("fri", today()).unwrap().date,
+        date(2026, 10, 23)
+    );
+    assert_eq!(
+        Due::parse_relative_to("next fri", today()).unwrap().date,
+        date(2026, 10, 23)
+    );
+    // The same weekday as today refers to the following week
+    assert_eq!(
+        Due::parse_relative_to("sunday", today()).unwrap().date,
+        date(2026, 10, 25)
+    );
+}
+
+#[test]
+fn test_parse_with_time() {
+    let due = Due::parse_relative_to("next fri 17:30", today()).expect("Failed to parse");
+    assert_eq!(due.date, date(2026, 10, 23));
+    assert_eq!(due.time, NaiveTime::from_hms_opt(17, 30, 0));
+    assert_eq!(due.to_string(), "2026-10-23 17:30");
+}
+
+#[test]
+fn test_parse_invalid() {
+    assert!(Due::parse_relative_to("", today()).is_err());
+    assert!(Due::parse_relative_to("someday", today()).is_err());
+    assert!(Due::parse_relative_to("+3x", today()).is_err());
+    assert!(Due::parse_relative_to("2026-13-01", today()).is_err());
+}
+
+#[test]
+fn test_is_past() {
+    let due = Due::parse_relative_to("2026-10-18", today()).unwrap();
+    assert!(!due.is_past(today().and_hms_opt(23, 59, 0).unwrap()));
+    assert!(due.is_past(date(2026, 10, 19).and_hms_opt(0, 0, 0).unwrap()));
+
+    let due = Due::parse_relative_to("2026-10-18 12:00", today()).unwrap();
+    assert!(due.is_past(today().and_hms_opt(12, 0, 0).unwrap()));
+    assert!(!due.is_past(today().and_hms_opt(11, 59, 0).unwrap()));
+}
@@ -0,0 +1,107 @@
+use to_do::model::merge::merge_tasks;
+use to_do::model::priority::Priority;
+use to_do::model::task::Task;
+use to_do::model::todo_list::TodoList;
+
+fn tasks(descriptions: &[&str]) -> Vec<Task> {
+    let mut todo_list =
+        TodoList::with_storage(Box::new(to_do::storage::memory::MemoryStorage::new()));
+    for description in descriptions {
+        todo_list
+            .add_task(description.to_string(), Priority::Low)
+            .unwrap();
+    }
+    todo_list.tasks
+}
+
+fn descriptions(tasks: &[Task]) -> Vec<(&str, &str)> {
+    tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task.description.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_merge_changes_of_both_sides() {
+    let base = tasks(&["Buy milk", "Walk the dog", "Call mom"]);
+
+    let mut ours = base.clone();
+    ours[0].description = "Buy oat milk".into();
+    ours.remove(2);
+
+    let mut theirs = base.clone();
+    theirs[1].completed = true;
+    theirs[2].priority = Priority::High;
+
+    let merge = merge_tasks(&base, &ours, &theirs);
+    assert_eq!(
+        descriptions(&merge.tasks),
+        [("1", "Buy oat milk"), ("2", "Walk the dog")]
+    );
+    assert!(merge.tasks[1].completed);
+    assert!(merge.renamed.is_empty());
+}
+
+#[test]
+fn test_merge_conflicting_changes() {
+    let base = tasks(&["Buy milk", "Walk the dog"]);
+
+    let mut ours = base.clone();
+    ours[0].description = "Ours".into();
+    ours[1].description = "Removed by them".into();
+
+    let mut theirs = base.clone();
+    theirs[0].description = "Theirs".into();
+    theirs.remove(1);
+
+    let merge = merge_tasks(&base, &ours, &theirs);
+    assert_eq!(descriptions(&merge.tasks), [("1", "Ours")]);
+}
+
+#[test]
+fn test_merge_renumbers_our_added_tasks() {
+    let base = tasks(&["Buy milk"]);
+    let all = tasks(&["Buy milk", "Ours", "Our subtask", "Theirs"]);
+
+    let mut ours = all[..3].to_vec();
+    ours[2].parent = Some("2".into());
+    ours[0].depends_on.insert("3".into());
+
+    let mut theirs = base.clone();
+    let mut added = all[3].clone();
+    added.id = "2".into();
+    theirs.push(added);
+
+    let merge = merge_tasks(&base, &ours, &theirs);
+    assert_eq!(
+        descriptions(&merge.tasks),
+        [
+            ("1", "Buy milk"),
+            ("2", "Theirs"),
+            ("4", "Ours"),
+            ("3", "Our subtask")
+        ]
+    );
+    assert_eq!(merge.renamed.get("2").map(String::as_str), Some("4"));
+    assert_eq!(merge.tasks[3].parent.as_deref(), Some("4"));
+    assert!(merge.tasks[0].depends_on.contains("3"));
+}
+
+#[test]
+fn test_merge_drops_references_to_removed_tasks() {
+    let base = tasks(&["Parent", "Blocker"]);
+
+    let mut ours = base.clone();
+    ours.remove(1);
+    ours.remove(0);
+    let mut theirs = base.clone();
+    let mut child = tasks(&["", "", "Child"]).remove(2);
+    child.parent = Some("1".into());
+    child.depends_on.insert("2".into());
+    theirs.push(child);
+
+    let merge = merge_tasks(&base, &ours, &theirs);
+    assert_eq!(descriptions(&merge.tasks), [("3", "Child")]);
+    assert_eq!(merge.tasks[0].parent, None);
+    assert!(merge.tasks[0].depends_on.is_empty());
+}
@@ -0,0 +1,133 @@
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use to_do::model::due::Due;
+use to_do::model::recurrence::Recurrence;
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Invalid test date")
+}
+
+fn due(year: i32, month: u32, day: u32) -> Option<Due> {
+    Some(Due {
+        date: date(year, month, day),
+        time: None,
+    })
+}
+
+// 2026-10-18 is a Sunday
+fn today() -> NaiveDate {
+    date(2026, 10, 18)
+}
+
+#[test]
+fn test_parse_rules() {
+    assert_eq!(Recurrence::parse("daily"), Ok(Recurrence::Daily));
+    assert_eq!(
+        Recurrence::parse("Weekly"),
+        Ok(Recurrence::Weekly { weekdays: vec![] })
+    );
+    assert_eq!(
+        Recurrence::parse("weekly thu,mon"),
+        Ok(Recurrence::Weekly {
+            weekdays: vec![Weekday::Mon, Weekday::Thu]
+        })
+    );
+    assert_eq!(
+        Recurrence::parse("mon,thu"),
+        Ok(Recurrence::Weekly {
+            weekdays: vec![Weekday::Mon, Weekday::Thu]
+        })
+    );
+    assert_eq!(
+        Recurrence::parse("monthly 15"),
+        Ok(Recurrence::Monthly { day: Some(15) })
+    );
+    assert_eq!(
+        Recurrence::parse("3d"),
+        Ok(Recurrence::AfterCompletion { days: 3 })
+    );
+}
+
+#[test]
+fn test_rule_is_parsed_back() {
+    for text in [
+        "daily",
+        "weekly",
+        "weekly mon,thu",
+        "monthly",
+        "monthly 15",
+        "3d",
+    ] {
+        let recurrence = Recurrence::parse(text).unwrap();
+        assert_eq!(recurrence.rule(), text);
+        assert_eq!(Recurrence::parse(&recurrence.rule()), Ok(recurrence));
+    }
+}
+
+#[test]
+fn test_parse_invalid_rules() {
+    for text in ["", "yearly", "monthly 32", "0d", "weekly funday"] {
+        assert!(
+            Recurrence::parse(text).is_err(),
+            "'{}' should be invalid",
+            text
+        );
+    }
+}
+
+#[test]
+fn test_next_due_daily_and_weekly() {
+    assert_eq!(
+        Recurrence::Daily.next_due(due(2026, 10, 16), today()).date,
+        date(2026, 10, 17)
+    );
+    assert_eq!(
+        Recurrence::parse("weekly")
+            .unwrap()
+            .next_due(due(2026, 10, 16), today())
+            .date,
+        date(2026, 10, 23)
+    );
+    // Thursday 2026-10-22 is followed by Monday 2026-10-26
+    assert_eq!(
+        Recurrence::parse("mon,thu")
+            .unwrap()
+            .next_due(due(2026, 10, 22), today())
+            .date,
+        date(2026, 10, 26)
+    );
+}
+
+#[test]
+fn test_next_due_monthly_clamps_to_month_end() {
+    let recurrence = Recurrence::parse("monthly 31").unwrap();
+    assert_eq!(
+        recurrence.next_due(due(2027, 1, 31), today()).date,
+        date(2027, 2, 28)
+    );
+    assert_eq!(
+        Recurrence::parse("monthly")
+            .unwrap()
+            .next_due(due(2026, 10, 5), today())
+            .date,
+        date(2026, 11, 5)
+    );
+}
+
+#[test]
+fn test_next_due_after_completion_keeps_time() {
+    let previous = Some(Due {
+        date: date(2026, 10, 10),
+        time: NaiveTime::from_hms_opt(9, 30, 0),
+    });
+    let next = Recurrence::AfterCompletion { days: 3 }.next_due(previous, today());
+    assert_eq!(next.date, date(2026, 10, 21));
+    assert_eq!(next.time, NaiveTime::from_hms_opt(9, 30, 0));
+}
+
+#[test]
+fn test_next_due_without_previous_due_date() {
+    assert_eq!(
+        Recurrence::Daily.next_due(None, today()).date,
+        date(2026, 10, 19)
+    );
+}
@@ -0,0 +1,92 @@
+use to_do::model::priority::Priority;
+use to_do::model::search::{Search, SearchOptions};
+use to_do::model::todo_list::TodoList;
+use to_do::storage::memory::MemoryStorage;
+
+fn create_todo_list() -> TodoList {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    todo_list
+        .add_task("Buy milk".into(), Priority::Low)
+        .unwrap();
+    todo_list
+        .add_task("Call the milkman".into(), Priority::High)
+        .unwrap();
+    todo_list
+        .add_task("MILK the cow".into(), Priority::Medium)
+        .unwrap();
+    todo_list
+}
+
+fn found_ids(todo_list: &TodoList, text: &str, options: SearchOptions) -> Vec<String> {
+    let search = Search::new(text, options).expect("Invalid search");
+    todo_list
+        .tasks
+        .iter()
+        .filter(|task| search.is_match(task))
+        .map(|task| task.id.clone())
+        .collect()
+}
+
+#[test]
+fn test_search_modes() {
+    let todo_list = create_todo_list();
+    assert_eq!(
+        found_ids(&todo_list, "milk", SearchOptions::default()),
+        ["1", "2"]
+    );
+    let ignore_case = SearchOptions {
+        ignore_case: true,
+        ..Default::default()
+    };
+    assert_eq!(found_ids(&todo_list, "milk", ignore_case), ["1", "2", "3"]);
+    let whole_word = SearchOptions {
+        ignore_case: true,
+        whole_word: true,
+        ..Default::default()
+    };
+    assert_eq!(found_ids(&todo_list, "milk", whole_word), ["1", "3"]);
+    let regex = SearchOptions {
+        regex: true,
+        ..Default::default()
+    };
+    assert_eq!(found_ids(&todo_list, "^(Buy|Call)", regex), ["1", "2"]);
+}
+
+#[test]
+fn test_plain_search_escapes_regex_characters() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    todo_list
+        .add_task("Fix a.b (urgent)".into(), Priority::Low)
+        .unwrap();
+    todo_list.add_task("Fix axb".into(), Priority::Low).unwrap();
+    assert_eq!(
+        found_ids(&todo_list, "a.b (", SearchOptions::default()),
+        ["1"]
+    );
+}
+
+#[test]
+fn test_invalid_search() {
+    let regex = SearchOptions {
+        regex: true,
+        ..Default::default()
+    };
+    assert!(Search::new("(unclosed", regex).is_err());
+    assert_eq!(
+        Search::new("", SearchOptions::default()).unwrap_err(),
+        "Empty search text"
+    );
+}
+
+#[test]
+fn test_highlight() {
+    let options = SearchOptions {
+        ignore_case: true,
+        ..Default::default()
+    };
+    let search = Search::new("milk", options).unwrap();
+    assert_eq!(
+        search.highlight("Milk and milk"),
+        "\x1b[1;33mMilk\x1b[0m and \x1b[1;33mmilk\x1b[0m"
+    );
+}
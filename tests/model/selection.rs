@@ -0,0 +1,92 @@
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::selection::{Filter, select_tasks};
+use to_do::model::task::TaskOptions;
+use to_do::model::todo_list::TodoList;
+use to_do::storage::memory::MemoryStorage;
+
+fn create_todo_list() -> TodoList {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    for (description, priority) in [
+        ("Write docs +work", Priority::Low),
+        ("Deploy", Priority::High),
+        ("Answer mail", Priority::Low),
+        ("Review", Priority::Medium),
+    ] {
+        todo_list.add_task(description.into(), priority).unwrap();
+    }
+    todo_list
+        .add_task_with_options(
+            "Proofread".into(),
+            Priority::Low,
+            TaskOptions {
+                parent: Some("1".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    todo_list.tasks[0].tags.insert("work".to_string());
+    todo_list.tasks[2].completed = true;
+    todo_list
+}
+
+fn select(todo_list: &TodoList, ids: &[&str], filter: Option<&str>) -> Vec<String> {
+    let ids = ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+    let filter = filter.map(|filter| Filter::parse(filter).unwrap());
+    select_tasks(&todo_list.tasks, &ids, filter.as_ref()).unwrap()
+}
+
+#[test]
+fn test_select_ids_and_ranges() {
+    let todo_list = create_todo_list();
+    assert_eq!(select(&todo_list, &["4", "2"], None), vec!["2", "4"]);
+    assert_eq!(select(&todo_list, &["2-4"], None), vec!["2", "3", "4"]);
+    assert_eq!(select(&todo_list, &["4-9"], None), vec!["4", "5"]);
+    assert_eq!(
+        select_tasks(&todo_list.tasks, &["9".to_string()], None),
+        Err(TodoError::NotFound("9".to_string()))
+    );
+    assert_eq!(
+        select_tasks(&todo_list.tasks, &["4-2".to_string()], None),
+        Err(TodoError::Validation(
+            "Invalid ID or ID range '4-2'".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_select_with_filter() {
+    let todo_list = create_todo_list();
+    assert_eq!(
+        select(&todo_list, &[], Some("priority=low,completed=false")),
+        vec!["1", "5"]
+    );
+    assert_eq!(select(&todo_list, &[], Some("completed=true")), vec!["3"]);
+    assert_eq!(select(&todo_list, &[], Some("tag=+Work")), vec!["1"]);
+    assert_eq!(select(&todo_list, &[], Some("parent=1")), vec!["5"]);
+    assert_eq!(
+        select(&todo_list, &[], Some("parent=none, priority!=low")),
+        vec!["2", "4"]
+    );
+    assert_eq!(
+        select(&todo_list, &["1-3"], Some("priority=low")),
+        vec!["1", "3"]
+    );
+}
+
+#[test]
+fn test_invalid_filters() {
+    assert_eq!(
+        Filter::parse("owner=me"),
+        Err("Unknown filter key 'owner', expected priority, completed, tag or parent".to_string())
+    );
+    assert_eq!(
+        Filter::parse("priority"),
+        Err("Invalid condition 'priority', expected key=value or key!=value".to_string())
+    );
+    assert_eq!(
+        Filter::parse("completed=maybe"),
+        Err("Invalid completed value 'maybe', expected true or false".to_string())
+    );
+    assert!(Filter::parse(" , ").is_err());
+}
@@ -0,0 +1,96 @@
+use to_do::model::due::Due;
+use to_do::model::priority::Priority;
+use to_do::model::sort::{SortKey, sort_tasks};
+use to_do::model::task::TaskOptions;
+use to_do::model::todo_list::TodoList;
+use to_do::storage::memory::MemoryStorage;
+
+fn create_todo_list() -> TodoList {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    todo_list
+        .add_task("write docs".into(), Priority::Low)
+        .unwrap();
+    todo_list.add_task("Deploy".into(), Priority::High).unwrap();
+    todo_list
+        .add_task_with_options(
+            "Answer mail".into(),
+            Priority::Low,
+            TaskOptions {
+                due: Some(Due::parse("2030-01-01").unwrap()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    todo_list
+        .add_task("Review".into(), Priority::Medium)
+        .unwrap();
+    todo_list.tasks[1].completed = true;
+    todo_list
+}
+
+fn sorted_ids(todo_list: &TodoList, keys: &[SortKey], reverse: bool) -> Vec<String> {
+    let mut tasks = todo_list.tasks.iter().collect::<Vec<_>>();
+    sort_tasks(&mut tasks, keys, reverse);
+    tasks.iter().map(|task| task.id.clone()).collect()
+}
+
+#[test]
+fn test_priority_ordering() {
+    assert!(Priority::High > Priority::Medium);
+    assert!(Priority::Medium > Priority::Low);
+}
+
+#[test]
+fn test_sort_by_single_key() {
+    let todo_list = create_todo_list();
+    assert_eq!(
+        sorted_ids(&todo_list, &[SortKey::Priority], false),
+        ["2", "4", "1", "3"]
+    );
+    assert_eq!(
+        sorted_ids(&todo_list, &[SortKey::Description], false),
+        ["3", "2", "4", "1"]
+    );
+    assert_eq!(
+        sorted_ids(&todo_list, &[SortKey::Status], false),
+        ["1", "3", "4", "2"]
+    );
+    assert_eq!(
+        sorted_ids(&todo_list, &[SortKey::Due], false),
+        ["3", "1", "2", "4"]
+    );
+}
+
+#[test]
+fn test_sort_by_multiple_keys_and_reverse() {
+    let todo_list = create_todo_list();
+    let keys = [SortKey::Priority, SortKey::Description];
+    assert_eq!(sorted_ids(&todo_list, &keys, false), ["2", "4", "3", "1"]);
+    assert_eq!(sorted_ids(&todo_list, &keys, true), ["1", "3", "4", "2"]);
+    assert_eq!(sorted_ids(&todo_list, &[], true), ["4", "3", "2", "1"]);
+}
+
+#[test]
+fn test_sort_by_id_is_numeric() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    for i in 0..11 {
+        todo_list
+            .add_task(format!("Task {}", i), Priority::Low)
+            .unwrap();
+    }
+    let ids = sorted_ids(&todo_list, &[SortKey::Id], true);
+    assert_eq!(ids[0], "B");
+    assert_eq!(ids[10], "1");
+}
+
+#[test]
+fn test_parse_sort_keys() {
+    assert_eq!(
+        SortKey::parse_list("Priority, due"),
+        Ok(vec![SortKey::Priority, SortKey::Due])
+    );
+    assert_eq!(
+        SortKey::parse_list("priority,size"),
+        Err("Invalid sort key: size".to_string())
+    );
+}
@@ -0,0 +1,30 @@
+use to_do::model::tags::{extract_tags, normalize_tag};
+
+#[test]
+fn test_extract_tags() {
+    let (description, tags) = extract_tags("Deploy the api +Backend +ops");
+    assert_eq!(description, "Deploy the api");
+    assert_eq!(tags.into_iter().collect::<Vec<_>>(), vec!["backend", "ops"]);
+}
+
+#[test]
+fn test_extract_tags_none() {
+    let (description, tags) = extract_tags("Write  report + summary");
+    assert_eq!(description, "Write  report + summary");
+    assert!(tags.is_empty());
+}
+
+#[test]
+fn test_extract_tags_only_tags() {
+    let (description, tags) = extract_tags("+docs");
+    assert_eq!(description, "+docs");
+    assert!(tags.contains("docs"));
+}
+
+#[test]
+fn test_normalize_tag() {
+    assert_eq!(normalize_tag("+Docs"), Ok("docs".to_string()));
+    assert_eq!(normalize_tag("ops"), Ok("ops".to_string()));
+    assert!(normalize_tag("+").is_err());
+    assert!(normalize_tag("two words").is_err());
+}
@@ -0,0 +1,46 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use to_do::model::timestamp::{format_timestamp, parse_since};
+
+fn now() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2026, 10, 18)
+        .unwrap()
+        .and_hms_opt(15, 30, 0)
+        .unwrap()
+}
+
+#[test]
+fn test_parse_since_ages() {
+    assert_eq!(
+        format_timestamp(parse_since("7d", now()).unwrap()),
+        "2026-10-11 15:30"
+    );
+    assert_eq!(
+        format_timestamp(parse_since("2w", now()).unwrap()),
+        "2026-10-04 15:30"
+    );
+    assert_eq!(
+        format_timestamp(parse_since("12H", now()).unwrap()),
+        "2026-10-18 03:30"
+    );
+}
+
+#[test]
+fn test_parse_since_dates() {
+    assert_eq!(
+        format_timestamp(parse_since("2026-10-01", now()).unwrap()),
+        "2026-10-01 00:00"
+    );
+    assert_eq!(
+        format_timestamp(parse_since("yesterday 09:00", now()).unwrap()),
+        "2026-10-17 09:00"
+    );
+}
+
+#[test]
+fn test_parse_since_invalid() {
+    assert_eq!(
+        parse_since("soon", now()),
+        Err("Invalid time value: soon".to_string())
+    );
+    assert!(parse_since("3y", now()).is_err());
+}
@@ -1,88 +1,105 @@
-
-use to_do::model::{todo_list::TodoList, priority::Priority};
-use std::fs;
+use to_do::error::TodoError;
+use to_do::model::{priority::Priority, task::TaskOptions, todo_list::TodoList};
+use to_do::storage::json_file::JsonFileStorage;
+use to_do::storage::memory::MemoryStorage;
 
 #[test]
 fn test_new_todo_list() {
     let todo_list = TodoList::new();
     assert_eq!(todo_list.tasks.len(), 0);
     assert_eq!(todo_list.next_id, 1);
-    assert_eq!(todo_list.file_name, "todo_list.json");
+    assert_eq!(todo_list.storage.location(), "todo_list.json");
 }
 
 #[test]
 fn test_add_task() {
-    let mut todo_list = TodoList::new();
-    let id = todo_list.add_task("Test task".to_string(), Priority::High);
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let id = todo_list
+        .add_task("Test task".to_string(), Priority::High)
+        .unwrap();
     assert_eq!(todo_list.tasks.len(), 1);
     assert_eq!(todo_list.next_id, 2);
     assert_eq!(todo_list.tasks[0].id, id);
     assert_eq!(todo_list.tasks[0].description, "Test task");
     assert_eq!(todo_list.tasks[0].priority, Priority::High);
-    assert_eq!(todo_list.tasks[0].completed, false);
+    assert!(!todo_list.tasks[0].completed);
 }
 
 #[test]
 fn test_complete_task() {
-    let mut todo_list = TodoList::new();
-    let id = todo_list.add_task("Test task".to_string(), Priority::Medium);
-    todo_list.toggle_task_status(id.clone());
-    assert_eq!(todo_list.tasks[0].completed, true);
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let id = todo_list
+        .add_task("Test task".to_string(), Priority::Medium)
+        .unwrap();
+    todo_list.toggle_task_status(id.clone()).unwrap();
+    assert!(todo_list.tasks[0].completed);
     // Toggle again to test flipping back
-    todo_list.toggle_task_status(id.clone());
-    assert_eq!(todo_list.tasks[0].completed, false);
+    todo_list.toggle_task_status(id.clone()).unwrap();
+    assert!(!todo_list.tasks[0].completed);
 }
 
 #[test]
-#[should_panic(expected = "IllegalArgument Error: Task with ID: notfound not found")]
 fn test_complete_task_not_found() {
-    let mut todo_list = TodoList::new();
-    todo_list.toggle_task_status("notfound".to_string());
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let result = todo_list.toggle_task_status("notfound".to_string());
+    assert_eq!(result, Err(TodoError::NotFound("notfound".to_string())));
 }
 
 #[test]
 fn test_remove_task() {
-    let mut todo_list = TodoList::new();
-    let id = todo_list.add_task("Test task".to_string(), Priority::Low);
-    todo_list.remove_task(id.clone());
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let id = todo_list
+        .add_task("Test task".to_string(), Priority::Low)
+        .unwrap();
+    todo_list.remove_task(id.clone()).unwrap();
     assert_eq!(todo_list.tasks.len(), 0);
 }
 
 #[test]
-#[should_panic(expected = "IllegalArgument Error: Task with ID: notfound not found")]
 fn test_remove_task_not_found() {
-    let mut todo_list = TodoList::new();
-    todo_list.remove_task("notfound".to_string());
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let error = todo_list.remove_task("notfound".to_string()).unwrap_err();
+    assert_eq!(error, TodoError::NotFound("notfound".to_string()));
+    assert_eq!(error.to_string(), "Task with ID: notfound not found");
+}
+
+#[test]
+fn test_edit_task_not_found() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let result = todo_list.edit_task("notfound", ("Text", &Priority::Low));
+    assert_eq!(result, Err(TodoError::NotFound("notfound".to_string())));
 }
 
 #[test]
 fn test_save_and_load() {
-    let mut todo_list = TodoList::new();
-    let test_file = "test_todo_list.json";
-    todo_list.file_name = test_file.to_string();
+    let dir = tempfile::tempdir().unwrap();
+    let test_file = dir.path().join("test_todo_list.json");
+    let mut todo_list =
+        TodoList::with_storage(Box::new(JsonFileStorage::new(&test_file).with_backups(0)));
 
     // Add a task and save
-    todo_list.add_task("Test task".to_string(), Priority::High);
-    todo_list.save();
+    todo_list
+        .add_task("Test task".to_string(), Priority::High)
+        .unwrap();
+    todo_list.save().unwrap();
 
     // Load into a new TodoList
-    let mut new_todo_list = TodoList::new();
-    new_todo_list.file_name = test_file.to_string();
-    new_todo_list.load();
+    let mut new_todo_list = TodoList::with_storage(Box::new(JsonFileStorage::new(&test_file)));
+    new_todo_list.load().unwrap();
 
     assert_eq!(new_todo_list.tasks.len(), 1);
     assert_eq!(new_todo_list.tasks[0].description, "Test task");
     assert_eq!(new_todo_list.tasks[0].priority, Priority::High);
-
-    // Clean up
-    fs::remove_file(test_file).expect("Failed to clean up test file");
 }
 
 #[test]
 fn test_try_load_no_file() {
-    let mut todo_list = TodoList::new();
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
     let result = todo_list.load_from_file("no_exist.json");
-    assert!(result.is_err());
+    assert!(matches!(result, Err(TodoError::Io(_))));
+
+    todo_list.storage = Box::new(JsonFileStorage::new("no_exist.json"));
+    assert_eq!(todo_list.try_load(), Ok(false));
 }
 
 #[test]
@@ -90,18 +107,40 @@ fn test_try_load_invalid_json() {
     let dir = tempfile::tempdir().unwrap();
     let file_path = dir.path().join("todo.json");
     std::fs::write(&file_path, "invalid json").unwrap();
-    let mut todo_list = TodoList::new();
-    let result = todo_list.load_from_file("todo.json");
-    assert!(result.is_err());
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let result = todo_list.load_from_file(file_path.to_str().unwrap());
+    assert!(matches!(result, Err(TodoError::Parse(_))));
+}
+
+#[test]
+fn test_load_invalid_task_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("todo.json");
+    std::fs::write(
+        &file_path,
+        r#"[{"id":"xyz","description":"Bad id","priority":"Low","completed":false}]"#,
+    )
+    .unwrap();
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let result = todo_list.load_from_file(file_path.to_str().unwrap());
+    assert_eq!(
+        result,
+        Err(TodoError::Parse("Failed to parse task ID: xyz".to_string()))
+    );
+    assert!(todo_list.tasks.is_empty());
 }
 
 #[test]
 fn test_edit_task() {
-    let mut todo_list = TodoList::new();
-    let id = todo_list.add_task("Test task".into(), Priority::High);
-    let new_text = "Edited text".into();
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let id = todo_list
+        .add_task("Test task".into(), Priority::High)
+        .unwrap();
+    let new_text = "Edited text";
     let new_priority = Priority::Low;
-    todo_list.edit_task(id.as_ref(), (new_text, &new_priority));
+    todo_list
+        .edit_task(id.as_ref(), (new_text, &new_priority))
+        .unwrap();
 
     let edited_task = todo_list.get_task_mut(id.as_ref()).expect("Task not found");
     assert_eq!(edited_task.description, new_text.to_string())
@@ -109,8 +148,10 @@ fn test_edit_task() {
 
 #[test]
 fn test_get_task_mut_exist() {
-    let mut todo_list = TodoList::new();
-    let id = todo_list.add_task("Test task".into(), Priority::High);
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let id = todo_list
+        .add_task("Test task".into(), Priority::High)
+        .unwrap();
 
     let task = todo_list.get_task_mut(id.as_ref());
     assert!(task.is_some())
@@ -118,8 +159,240 @@ fn test_get_task_mut_exist() {
 
 #[test]
 fn test_task_mut_not_found() {
-    let mut todo_list = TodoList::new();
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
     let id = "2".to_string();
     let task = todo_list.get_task_mut(id.as_ref());
     assert!(task.is_none())
 }
+
+#[test]
+fn test_load_file_without_due_dates() {
+    let dir = tempfile::tempdir().unwrap();
+    let file_path = dir.path().join("todo.json");
+    std::fs::write(
+        &file_path,
+        r#"[{"id":"1","description":"Old task","priority":"High","completed":false}]"#,
+    )
+    .unwrap();
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let result = todo_list.load_from_file(file_path.to_str().unwrap());
+    assert!(result.is_ok());
+    assert_eq!(todo_list.tasks.len(), 1);
+    assert!(todo_list.tasks[0].due.is_none());
+    assert!(todo_list.tasks[0].created_at.is_none());
+    assert!(todo_list.tasks[0].completed_at.is_none());
+    assert_eq!(todo_list.tasks[0].timestamps(), "");
+    assert_eq!(todo_list.next_id, 2);
+}
+
+#[test]
+fn test_timestamps_are_recorded() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let id = todo_list
+        .add_task("Test task".to_string(), Priority::Low)
+        .unwrap();
+    let task = &todo_list.tasks[0];
+    assert!(task.created_at.is_some());
+    assert_eq!(task.updated_at, task.created_at);
+    assert!(task.completed_at.is_none());
+
+    todo_list.toggle_task_status(id.clone()).unwrap();
+    assert!(todo_list.tasks[0].completed_at.is_some());
+    assert!(todo_list.tasks[0].updated_at >= todo_list.tasks[0].created_at);
+
+    todo_list.toggle_task_status(id.clone()).unwrap();
+    assert!(todo_list.tasks[0].completed_at.is_none());
+
+    todo_list.tasks[0].updated_at = None;
+    todo_list
+        .edit_task(&id, ("Test task", &Priority::Low))
+        .unwrap();
+    assert!(todo_list.tasks[0].updated_at.is_none());
+    todo_list
+        .edit_task(&id, ("Edited task", &Priority::Low))
+        .unwrap();
+    assert!(todo_list.tasks[0].updated_at.is_some());
+}
+
+fn create_todo_list_with_subtasks() -> TodoList {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let parent = todo_list.add_task("Parent".into(), Priority::High).unwrap();
+    let child = todo_list
+        .add_task_with_options(
+            "Child".into(),
+            Priority::Low,
+            TaskOptions {
+                parent: Some(parent.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    todo_list
+        .add_task_with_options(
+            "Grandchild".into(),
+            Priority::Low,
+            TaskOptions {
+                parent: Some(child),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    todo_list
+        .add_task("Other".into(), Priority::Medium)
+        .unwrap();
+    todo_list
+}
+
+#[test]
+fn test_descendants_and_progress() {
+    let mut todo_list = create_todo_list_with_subtasks();
+    assert_eq!(todo_list.descendants("1"), vec!["2", "3"]);
+    assert!(todo_list.descendants("4").is_empty());
+    assert_eq!(todo_list.progress("1"), Some((0, 1)));
+    assert_eq!(todo_list.progress("4"), None);
+
+    assert_eq!(todo_list.complete_descendants("1").unwrap(), vec!["2", "3"]);
+    assert_eq!(todo_list.progress("1"), Some((1, 1)));
+}
+
+#[test]
+fn test_remove_task_with_subtasks() {
+    let mut todo_list = create_todo_list_with_subtasks();
+    let removed = todo_list.remove_task("1".to_string()).unwrap();
+    let removed_ids = removed.iter().map(|t| t.id.as_str()).collect::<Vec<_>>();
+    assert_eq!(removed_ids, vec!["1", "2", "3"]);
+    assert_eq!(todo_list.tasks.len(), 1);
+    assert_eq!(todo_list.tasks[0].description, "Other");
+}
+
+#[test]
+fn test_as_tree() {
+    let todo_list = create_todo_list_with_subtasks();
+    let tasks = todo_list.tasks.iter().collect::<Vec<_>>();
+    let tree = TodoList::as_tree(&tasks)
+        .into_iter()
+        .map(|(depth, task)| (depth, task.id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(tree, vec![(0, "1"), (1, "2"), (2, "3"), (0, "4")]);
+
+    // A subtask whose parent is filtered out is shown as a root
+    let tasks = todo_list.tasks.iter().skip(1).collect::<Vec<_>>();
+    let tree = TodoList::as_tree(&tasks)
+        .into_iter()
+        .map(|(depth, task)| (depth, task.id.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(tree, vec![(0, "2"), (1, "3"), (0, "4")]);
+}
+
+#[test]
+fn test_render_task() {
+    let todo_list = create_todo_list_with_subtasks();
+    assert_eq!(
+        todo_list.render_task(&todo_list.tasks[0], 0),
+        "ID: 1, Description: Parent, Priority: High, Completed: false, Progress: 0/1 done"
+    );
+    assert_eq!(
+        todo_list.render_task(&todo_list.tasks[2], 2),
+        "   └─ ID: 3, Description: Grandchild, Priority: Low, Completed: false"
+    );
+}
+
+#[test]
+fn test_check_dependency() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let first = todo_list.add_task("First".into(), Priority::High).unwrap();
+    let second = todo_list.add_task("Second".into(), Priority::High).unwrap();
+    let third = todo_list.add_task("Third".into(), Priority::High).unwrap();
+
+    assert!(todo_list.check_dependency(&second, &first).is_ok());
+    todo_list.add_dependency(&second, &first).unwrap();
+    todo_list.add_dependency(&third, &second).unwrap();
+
+    assert!(todo_list.check_dependency(&first, &first).is_err());
+    assert!(todo_list.check_dependency(&first, "99").is_err());
+    assert_eq!(
+        todo_list.check_dependency(&first, &third),
+        Err(TodoError::Validation(
+            "Task with ID: 1 cannot depend on 3 because it would create a cycle".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_blocked_tasks() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let first = todo_list.add_task("First".into(), Priority::High).unwrap();
+    let second = todo_list.add_task("Second".into(), Priority::High).unwrap();
+    todo_list.add_dependency(&second, &first).unwrap();
+
+    assert!(todo_list.is_blocked(&second));
+    assert!(!todo_list.is_blocked(&first));
+    assert_eq!(todo_list.open_dependencies(&second)[0].id, first);
+
+    todo_list.toggle_task_status(first.clone()).unwrap();
+    assert!(!todo_list.is_blocked(&second));
+}
+
+#[test]
+fn test_remove_task_cleans_dependencies() {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    let first = todo_list.add_task("First".into(), Priority::High).unwrap();
+    let second = todo_list.add_task("Second".into(), Priority::High).unwrap();
+    todo_list.add_dependency(&second, &first).unwrap();
+    assert_eq!(
+        todo_list.dependents_of(std::slice::from_ref(&first)),
+        vec![(second.clone(), first.clone())]
+    );
+
+    todo_list.remove_task(first).unwrap();
+    assert!(todo_list.tasks[0].depends_on.is_empty());
+}
+
+#[test]
+fn test_concurrent_changes_are_merged() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let open = || {
+        let mut todo_list = TodoList::with_storage(Box::new(JsonFileStorage::new(&path)));
+        todo_list.try_load().unwrap();
+        todo_list
+    };
+
+    let mut first = open();
+    first.add_task("Buy milk".into(), Priority::Low).unwrap();
+    let mut second = open();
+    let mut third = open();
+
+    // Both add a task with ID 2 and change the first task
+    assert_eq!(
+        second.add_task("Walk the dog".into(), Priority::Low),
+        Ok("2".into())
+    );
+    second.toggle_task_status("1".into()).unwrap();
+    assert_eq!(
+        third.add_task("Call mom".into(), Priority::High),
+        Ok("3".into())
+    );
+    third
+        .edit_task("1", ("Buy oat milk", &Priority::Low))
+        .unwrap();
+
+    let mut loaded = open();
+    loaded.load().unwrap();
+    let descriptions = loaded
+        .tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task.description.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        descriptions,
+        [
+            ("1", "Buy oat milk"),
+            ("2", "Walk the dog"),
+            ("3", "Call mom")
+        ]
+    );
+    assert!(loaded.tasks[0].completed);
+    assert_eq!(third.tasks, loaded.tasks);
+    assert_eq!(third.next_id, 4);
+}
@@ -0,0 +1,139 @@
+use chrono::{Duration, Local};
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::task::TaskOptions;
+use to_do::service::archive::archivable_tasks;
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::backend::Storage;
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::console_ui::mock_displayer::MockDisplayer;
+
+/// Manager with a completed task 1 having a subtask 2, an open task 3 depending on
+/// task 1 and a completed task 4
+fn create_manager(storage: &MemoryStorage) -> Manager {
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(storage.clone());
+    manager
+        .add_task("Plan the trip".as_ref(), &Priority::High)
+        .unwrap();
+    let subtask = TaskOptions {
+        parent: Some("1".to_string()),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Book the hotel".as_ref(), &Priority::Low, subtask)
+        .unwrap();
+    let dependent = TaskOptions {
+        depends_on: ["1".to_string()].into(),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Pack".as_ref(), &Priority::Medium, dependent)
+        .unwrap();
+    manager
+        .add_task("Buy milk".as_ref(), &Priority::Low)
+        .unwrap();
+    manager.toggle_task_status("1", false).unwrap();
+    manager.toggle_task_status("4", false).unwrap();
+    manager
+}
+
+fn ids(tasks: &[to_do::model::task::Task]) -> Vec<String> {
+    tasks.iter().map(|task| task.id.clone()).collect()
+}
+
+#[test]
+fn test_archive_is_undoable() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+
+    let archivable = archivable_tasks(&manager.todo_list, None);
+    assert_eq!(archivable, ["1", "2", "4"]);
+    manager.archive_tasks(&archivable).unwrap();
+    assert_eq!(ids(&storage.tasks().unwrap()), ["3"]);
+    assert_eq!(ids(&storage.load_archive().unwrap()), ["1", "2", "4"]);
+    assert!(manager.get_tasks()[0].depends_on.is_empty());
+    assert_eq!(
+        manager.undo_stack.last().unwrap().to_string(),
+        "Archive tasks 1, 2, 4"
+    );
+
+    // The tasks come back at the end of the list
+    manager.undo().expect("Undo failed");
+    assert_eq!(ids(&storage.tasks().unwrap()), ["3", "1", "2", "4"]);
+    assert!(storage.load_archive().unwrap().is_empty());
+    assert!(manager.get_task("3").unwrap().depends_on.contains("1"));
+
+    manager.redo().expect("Redo failed");
+    assert_eq!(ids(&storage.tasks().unwrap()), ["3"]);
+    assert_eq!(storage.load_archive().unwrap().len(), 3);
+}
+
+#[test]
+fn test_unarchive_restores_subtasks() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+    manager.archive_tasks(&["1".to_string()]).unwrap();
+
+    // A subtask comes back with its parent
+    assert_eq!(
+        manager.unarchive_tasks(&["2".to_string()]),
+        Err(TodoError::Validation(
+            "Task 2 is a subtask of the archived task 1, unarchive that one instead".to_string()
+        ))
+    );
+    assert_eq!(
+        manager.unarchive_tasks(&["9".to_string()]),
+        Err(TodoError::NotFound("9".to_string()))
+    );
+    manager.unarchive_tasks(&["1".to_string()]).unwrap();
+    assert_eq!(ids(manager.get_tasks()), ["3", "4", "1", "2"]);
+    assert!(storage.load_archive().unwrap().is_empty());
+    assert_eq!(
+        manager.undo_stack.last().unwrap().to_string(),
+        "Unarchive tasks 1, 2"
+    );
+
+    manager.undo().expect("Undo failed");
+    assert_eq!(ids(manager.get_tasks()), ["3", "4"]);
+    assert_eq!(ids(&storage.load_archive().unwrap()), ["1", "2"]);
+}
+
+#[test]
+fn test_archivable_tasks_older_than() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+    let now = Local::now().naive_local();
+    manager.get_task_mut("4").unwrap().completed_at = Some(now - Duration::days(40));
+
+    let before = Some(now - Duration::days(30));
+    assert_eq!(archivable_tasks(&manager.todo_list, before), ["4"]);
+
+    // A task is kept while one of its subtasks is
+    manager.get_task_mut("1").unwrap().completed_at = Some(now - Duration::days(40));
+    assert_eq!(archivable_tasks(&manager.todo_list, before), ["4"]);
+    manager.get_task_mut("2").unwrap().completed_at = None;
+    assert_eq!(archivable_tasks(&manager.todo_list, before), ["4"]);
+}
+
+#[test]
+fn test_archive_on_complete() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+    manager.archive_on_complete = true;
+    manager.toggle_task_status("3", false).unwrap();
+    assert_eq!(ids(&storage.load_archive().unwrap()), ["3"]);
+    assert_eq!(ids(manager.get_tasks()), ["1", "2", "4"]);
+
+    // Completing and archiving is a single action
+    manager.undo().expect("Undo failed");
+    assert!(storage.load_archive().unwrap().is_empty());
+    assert!(!manager.get_task("3").unwrap().completed);
+
+    // Reopened tasks stay in the list
+    manager
+        .toggle_tasks_status(&["3".to_string(), "4".to_string()], false)
+        .unwrap();
+    assert_eq!(ids(&storage.load_archive().unwrap()), ["3"]);
+    assert!(!manager.get_task("4").unwrap().completed);
+}
@@ -0,0 +1,184 @@
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::task::TaskOptions;
+use to_do::service::history::{History, history_line};
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::json_file::JsonFileStorage;
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::console_ui::mock_displayer::MockDisplayer;
+
+fn create_manager() -> Manager {
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(MemoryStorage::new());
+    manager
+        .add_task("Buy milk".as_ref(), &Priority::High)
+        .unwrap();
+    manager
+        .add_task("Call mom".as_ref(), &Priority::Low)
+        .unwrap();
+    manager
+}
+
+fn descriptions(manager: &Manager) -> Vec<String> {
+    manager
+        .undo_stack
+        .iter()
+        .rev()
+        .map(|entry| entry.to_string())
+        .collect()
+}
+
+#[test]
+fn test_actions_are_described() {
+    let mut manager = create_manager();
+    let subtask = TaskOptions {
+        parent: Some("1".to_string()),
+        ..Default::default()
+    };
+    manager
+        .add_task_with_options("Go to the shop".as_ref(), &Priority::Low, subtask)
+        .unwrap();
+    manager.toggle_task_status("1", false).unwrap();
+    manager.toggle_task_status("1", false).unwrap();
+    manager
+        .edit_task("2", "Call dad".as_ref(), &Priority::Medium)
+        .unwrap();
+    manager.remove_task("1").unwrap();
+
+    assert_eq!(
+        descriptions(&manager),
+        vec![
+            "Remove task 1 \"Buy milk\" and its subtasks 3",
+            "Edit task 2: description \"Call mom\" -> \"Call dad\", priority Low -> Medium",
+            "Reopen task 1",
+            "Complete task 1 and its subtasks 3",
+            "Add task 3 \"Go to the shop\" (Low)",
+            "Add task 2 \"Call mom\" (Low)",
+            "Add task 1 \"Buy milk\" (High)",
+        ]
+    );
+    let line = history_line(1, &manager.undo_stack[0]);
+    assert!(line.starts_with("1: 20"));
+    assert!(line.ends_with(" Add task 1 \"Buy milk\" (High)"));
+}
+
+#[test]
+fn test_history_without_timestamps_is_loaded() {
+    let manager = create_manager();
+    let mut value = History::new(&manager.undo_stack, &manager.redo_stack)
+        .to_value()
+        .unwrap();
+    value["undo"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("performed_at");
+
+    let (undo_stack, _) = History::from_value(value).unwrap().into_stacks();
+    assert_eq!(undo_stack[0].performed_at, None);
+    assert_eq!(
+        history_line(2, &undo_stack[0]),
+        "2: Add task 1 \"Buy milk\" (High)"
+    );
+}
+
+#[test]
+fn test_undo_to_position() {
+    let mut manager = create_manager();
+    manager.toggle_task_status("1", false).unwrap();
+
+    assert_eq!(manager.undo_to(2), Ok(2));
+    assert_eq!(manager.get_tasks().len(), 1);
+    assert!(!manager.get_task("1").unwrap().completed);
+    assert_eq!(manager.redo_stack.len(), 2);
+    assert_eq!(
+        manager.undo_to(2),
+        Err(TodoError::Validation(
+            "No action 2 in the undo history, which has 1 actions".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_failed_undo_to_undoes_nothing() {
+    let mut manager = create_manager();
+    manager.toggle_task_status("2", false).unwrap();
+    // The first task vanished without going through the history
+    manager.todo_list.tasks.retain(|task| task.id != "1");
+
+    assert_eq!(
+        manager.undo_to(3),
+        Err(TodoError::NotFound("1".to_string()))
+    );
+    assert_eq!(manager.undo_stack.len(), 3);
+    assert!(manager.redo_stack.is_empty());
+    assert!(manager.get_task("2").unwrap().completed);
+}
+
+#[test]
+fn test_undo_single_action() {
+    let mut manager = create_manager();
+    manager.toggle_task_status("2", false).unwrap();
+
+    assert_eq!(
+        manager.undo_action(2),
+        Err(TodoError::Validation(
+            "Action 2 cannot be undone on its own, task 2 was also changed by action 1. \
+             Use `undo --to 2` to undo both"
+                .to_string()
+        ))
+    );
+
+    manager.undo_action(3).unwrap();
+    assert!(manager.get_task("1").is_none());
+    assert!(manager.get_task("2").unwrap().completed);
+    assert_eq!(manager.undo_stack.len(), 2);
+
+    manager.redo().unwrap();
+    assert_eq!(manager.get_task("1").unwrap().description, "Buy milk");
+}
+
+#[test]
+fn test_history_saved_by_another_todo_is_kept() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let open = || {
+        let mut manager = Manager::new(Box::new(MockDisplayer));
+        manager.todo_list.storage = Box::new(JsonFileStorage::new(&path).with_backups(0));
+        if manager.todo_list.storage.exists() {
+            manager.todo_list.load().unwrap();
+            manager.load_history().unwrap();
+        }
+        manager
+    };
+    let mut first = open();
+    first
+        .add_task("Buy milk".as_ref(), &Priority::High)
+        .unwrap();
+    let mut second = open();
+    first.add_task("Call mom".as_ref(), &Priority::Low).unwrap();
+    second.toggle_task_status("1", false).unwrap();
+
+    assert_eq!(
+        descriptions(&open()),
+        vec![
+            "Complete task 1",
+            "Add task 2 \"Call mom\" (Low)",
+            "Add task 1 \"Buy milk\" (High)",
+        ]
+    );
+
+    // The actions undone by one are taken out of the history of the other
+    second.undo().unwrap();
+    first
+        .edit_task("2", "Call dad".as_ref(), &Priority::Low)
+        .unwrap();
+    assert_eq!(
+        descriptions(&open()),
+        vec![
+            "Edit task 2: description \"Call mom\" -> \"Call dad\"",
+            "Add task 2 \"Call mom\" (Low)",
+            "Add task 1 \"Buy milk\" (High)",
+        ]
+    );
+}
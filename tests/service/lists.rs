@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::todo_list::TodoList;
+use to_do::service::lists::{DEFAULT_LIST, ListDirectory, resolve_file};
+use to_do::storage::json_file::JsonFileStorage;
+
+fn add_task(path: &Path, description: &str) {
+    let mut todo_list = TodoList::with_storage(Box::new(JsonFileStorage::new(path)));
+    todo_list.try_load().unwrap();
+    todo_list
+        .add_task(description.to_string(), Priority::Low)
+        .unwrap();
+}
+
+#[test]
+fn test_create_rename_delete_lists() {
+    let temp_dir = TempDir::new().unwrap();
+    let lists = ListDirectory::new(temp_dir.path().join("todo"));
+    assert!(lists.names().unwrap().is_empty());
+
+    let path = lists.create("work").unwrap();
+    assert_eq!(path, temp_dir.path().join("todo/work.json"));
+    assert_eq!(lists.task_count("work").unwrap(), 0);
+    assert_eq!(
+        lists.create("work").unwrap_err(),
+        TodoError::Validation("List 'work' already exists".to_string())
+    );
+
+    add_task(&path, "Write report");
+    lists.create("home").unwrap();
+    assert_eq!(lists.names().unwrap(), ["home", "work"]);
+
+    lists.rename("work", "job").unwrap();
+    assert_eq!(lists.names().unwrap(), ["home", "job"]);
+    assert_eq!(lists.task_count("job").unwrap(), 1);
+    assert_eq!(
+        lists.rename("work", "other").unwrap_err(),
+        TodoError::ListNotFound("work".to_string())
+    );
+    assert!(lists.rename("job", "home").is_err());
+
+    lists.delete("home").unwrap();
+    assert_eq!(lists.names().unwrap(), ["job"]);
+    assert_eq!(
+        lists.delete("home").unwrap_err(),
+        TodoError::ListNotFound("home".to_string())
+    );
+}
+
+#[test]
+fn test_rename_list_moves_all_its_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let lists = ListDirectory::new(temp_dir.path().to_path_buf());
+    let path = lists.create("work").unwrap();
+    add_task(&path, "Write report");
+    add_task(&path, "Send report");
+    let files = || {
+        let mut names = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+
+    // A file that cannot be moved leaves the list as it was
+    std::fs::create_dir(temp_dir.path().join("job.json.2.bak")).unwrap();
+    assert!(matches!(lists.rename("work", "job"), Err(TodoError::Io(_))));
+    assert_eq!(
+        files(),
+        [
+            "job.json.2.bak",
+            "work.json",
+            "work.json.1.bak",
+            "work.json.2.bak",
+            "work.json.lock"
+        ]
+    );
+
+    std::fs::remove_dir(temp_dir.path().join("job.json.2.bak")).unwrap();
+    lists.rename("work", "job").unwrap();
+    assert_eq!(files(), ["job.json", "job.json.1.bak", "job.json.2.bak"]);
+}
+
+#[test]
+fn test_delete_list_leaves_no_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let lists = ListDirectory::new(temp_dir.path().to_path_buf());
+    let path = lists.create("work").unwrap();
+    add_task(&path, "Write report");
+    add_task(&path, "Send report");
+    assert!(temp_dir.path().join("work.json.1.bak").exists());
+    assert!(temp_dir.path().join("work.json.lock").exists());
+
+    lists.delete("work").unwrap();
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_invalid_list_names() {
+    let lists = ListDirectory::new(PathBuf::from("todo"));
+    for name in ["", "a b", "../work", "work.json"] {
+        assert!(matches!(lists.path(name), Err(TodoError::Validation(_))));
+    }
+    assert!(lists.path("work-2_b").is_ok());
+}
+
+#[test]
+fn test_default_list_needs_no_creation() {
+    let temp_dir = TempDir::new().unwrap();
+    let lists = ListDirectory::new(temp_dir.path().to_path_buf());
+    assert_eq!(
+        lists.existing_path(DEFAULT_LIST).unwrap(),
+        temp_dir.path().join("default.json")
+    );
+    assert_eq!(lists.task_count(DEFAULT_LIST).unwrap(), 0);
+    assert_eq!(
+        lists.existing_path("work").unwrap_err(),
+        TodoError::ListNotFound("work".to_string())
+    );
+}
+
+#[test]
+fn test_resolve_file_precedence() {
+    let file = PathBuf::from("file.json");
+    let env_file = PathBuf::from("env.json");
+    assert_eq!(
+        resolve_file(Some(&file), Some("work"), Some(env_file.clone())).unwrap(),
+        file
+    );
+    assert_eq!(
+        resolve_file(None, None, Some(env_file.clone())).unwrap(),
+        env_file
+    );
+}
@@ -0,0 +1,140 @@
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::task::TaskOptions;
+use to_do::service::actions::Command;
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::json_file::JsonFileStorage;
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::console_ui::mock_displayer::MockDisplayer;
+
+fn create_manager(storage: &MemoryStorage) -> Manager {
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(storage.clone());
+    manager
+        .add_task("Buy milk".as_ref(), &Priority::High)
+        .unwrap();
+    manager
+}
+
+fn add(description: &str) -> Command {
+    Command::AddTask {
+        description: description.to_string(),
+        priority: Priority::Low,
+        options: TaskOptions::default(),
+    }
+}
+
+#[test]
+fn test_transaction_is_a_single_action() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+
+    manager
+        .execute_transaction(vec![
+            add("Call mom"),
+            Command::CompleteTask {
+                id: "1".to_string(),
+            },
+            add("Walk the dog"),
+        ])
+        .unwrap();
+    assert_eq!(storage.tasks().unwrap().len(), 3);
+    assert!(storage.tasks().unwrap()[0].completed);
+    assert_eq!(manager.undo_stack.len(), 2);
+    assert_eq!(
+        manager.undo_stack[1].to_string(),
+        "3 actions: Add task 2 \"Call mom\" (Low); Complete task 1; Add task 3 \"Walk the dog\" (Low)"
+    );
+
+    manager.undo().expect("Undo failed");
+    assert_eq!(storage.tasks().unwrap().len(), 1);
+    assert!(!storage.tasks().unwrap()[0].completed);
+
+    manager.redo().expect("Redo failed");
+    let ids = storage
+        .tasks()
+        .unwrap()
+        .iter()
+        .map(|task| task.id.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["1", "2", "3"]);
+}
+
+#[test]
+fn test_failed_transaction_is_rolled_back() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+
+    let result = manager.execute_transaction(vec![
+        add("Call mom"),
+        Command::CompleteTask {
+            id: "1".to_string(),
+        },
+        Command::CompleteTask {
+            id: "9".to_string(),
+        },
+    ]);
+    assert_eq!(result, Err(TodoError::NotFound("9".to_string())));
+    assert_eq!(manager.get_tasks().len(), 1);
+    assert!(!manager.get_task("1").unwrap().completed);
+    assert_eq!(manager.todo_list.next_id, 2);
+    assert_eq!(storage.tasks().unwrap().len(), 1);
+    assert_eq!(manager.undo_stack.len(), 1);
+
+    manager
+        .add_task("Call mom".as_ref(), &Priority::Low)
+        .unwrap();
+    assert_eq!(manager.get_task("2").unwrap().description, "Call mom");
+}
+
+#[test]
+fn test_transaction_saves_once_at_the_end() {
+    let storage = MemoryStorage::new();
+    let mut manager = create_manager(&storage);
+
+    manager
+        .transaction(|manager| {
+            manager.add_task("Call mom".as_ref(), &Priority::Low)?;
+            manager.toggle_task_status("1", false)?;
+            // Nothing is saved before the transaction ends
+            assert_eq!(storage.tasks().unwrap().len(), 1);
+            assert!(!storage.tasks().unwrap()[0].completed);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(storage.tasks().unwrap().len(), 2);
+    assert!(storage.tasks().unwrap()[0].completed);
+    // The actions are recorded separately
+    assert_eq!(manager.undo_stack.len(), 3);
+
+    let result = manager.transaction(|manager| {
+        manager.remove_task("2")?;
+        manager.remove_task("2")
+    });
+    assert_eq!(result, Err(TodoError::NotFound("2".to_string())));
+    assert_eq!(storage.tasks().unwrap().len(), 2);
+    assert_eq!(manager.undo_stack.len(), 3);
+}
+
+#[test]
+fn test_transaction_on_a_locked_file_saves_its_history() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(JsonFileStorage::new(&path).with_backups(0));
+
+    manager
+        .transaction(|manager| {
+            manager.add_task("Buy milk".as_ref(), &Priority::High)?;
+            manager.add_task("Call mom".as_ref(), &Priority::Low)
+        })
+        .unwrap();
+
+    let mut manager = Manager::new(Box::new(MockDisplayer));
+    manager.todo_list.storage = Box::new(JsonFileStorage::new(&path));
+    manager.todo_list.load().unwrap();
+    manager.load_history().unwrap();
+    assert_eq!(manager.get_tasks().len(), 2);
+    assert_eq!(manager.undo_stack.len(), 2);
+}
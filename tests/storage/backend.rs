@@ -0,0 +1,202 @@
+use std::path::Path;
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::todo_list::TodoList;
+use to_do::service::lists::ListDirectory;
+use to_do::storage::backend::{Backend, Storage};
+use to_do::storage::json_file::JsonFileStorage;
+use to_do::storage::memory::MemoryStorage;
+use to_do::storage::sqlite::SqliteStorage;
+
+/// Save two tasks through a todo list, then load them back into another one
+fn check_round_trip(storage: Box<dyn Storage>, reopened: Box<dyn Storage>) {
+    assert!(!storage.exists());
+    let mut todo_list = TodoList::with_storage(storage);
+    assert_eq!(todo_list.try_load(), Ok(false));
+    todo_list
+        .add_task("Buy milk".into(), Priority::High)
+        .unwrap();
+    let id = todo_list
+        .add_task("Walk the dog".into(), Priority::Low)
+        .unwrap();
+    todo_list.toggle_task_status(id).unwrap();
+
+    let mut loaded = TodoList::with_storage(reopened);
+    assert_eq!(loaded.try_load(), Ok(true));
+    assert_eq!(loaded.tasks.len(), 2);
+    assert_eq!(loaded.tasks[0].description, "Buy milk");
+    assert_eq!(loaded.tasks[0].priority, Priority::High);
+    assert!(loaded.tasks[1].completed);
+    assert_eq!(loaded.next_id, 3);
+}
+
+#[test]
+fn test_json_file_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested/todo.json");
+    check_round_trip(
+        Box::new(JsonFileStorage::new(&path)),
+        Box::new(JsonFileStorage::new(&path)),
+    );
+}
+
+#[test]
+fn test_sqlite_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested/todo.db");
+    check_round_trip(
+        Box::new(SqliteStorage::new(&path)),
+        Box::new(SqliteStorage::new(&path)),
+    );
+
+    // Removed tasks are removed from the database as well
+    let mut todo_list = TodoList::with_storage(Box::new(SqliteStorage::new(&path)));
+    todo_list.load().unwrap();
+    todo_list.remove_task("1".into()).unwrap();
+    assert_eq!(SqliteStorage::new(&path).load().unwrap().tasks.len(), 1);
+}
+
+/// Positions and IDs of the rows of the `tasks` table
+fn sqlite_rows(path: &Path) -> Vec<(i64, String)> {
+    let connection = rusqlite::Connection::open(path).unwrap();
+    let mut statement = connection
+        .prepare("SELECT position, id FROM tasks ORDER BY position")
+        .unwrap();
+    statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_sqlite_saves_only_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.db");
+    let mut todo_list = TodoList::with_storage(Box::new(SqliteStorage::new(&path)));
+    todo_list.try_load().unwrap();
+    for description in ["First", "Second", "Third"] {
+        todo_list
+            .add_task(description.into(), Priority::Low)
+            .unwrap();
+    }
+    todo_list.remove_task("1".into()).unwrap();
+    todo_list.toggle_task_status("3".into()).unwrap();
+    todo_list.add_task("Fourth".into(), Priority::Low).unwrap();
+    // The other rows keep their position
+    assert_eq!(
+        sqlite_rows(&path),
+        [
+            (1, "2".to_string()),
+            (2, "3".to_string()),
+            (3, "4".to_string())
+        ]
+    );
+    let loaded = SqliteStorage::new(&path).load().unwrap();
+    assert_eq!(loaded.tasks, todo_list.tasks);
+
+    // Tasks moved before the stored ones are written again in their new order
+    todo_list.tasks.rotate_right(1);
+    todo_list.save().unwrap();
+    assert_eq!(
+        sqlite_rows(&path),
+        [
+            (0, "4".to_string()),
+            (1, "2".to_string()),
+            (2, "3".to_string())
+        ]
+    );
+}
+
+/// Archive a task, then read the archive back from another storage of the same file
+fn check_archive(storage: Box<dyn Storage>, reopened: Box<dyn Storage>) {
+    let mut todo_list = TodoList::with_storage(storage);
+    todo_list
+        .add_task("Buy milk".into(), Priority::High)
+        .unwrap();
+    todo_list
+        .add_task("Walk the dog".into(), Priority::Low)
+        .unwrap();
+    todo_list.archive_tasks(&["1".to_string()]).unwrap();
+
+    assert_eq!(reopened.load().unwrap().tasks.len(), 1);
+    let archive = reopened.load_archive().unwrap();
+    assert_eq!(archive.len(), 1);
+    assert_eq!(archive[0].description, "Buy milk");
+
+    todo_list.unarchive_tasks(&["1".to_string()]).unwrap();
+    assert_eq!(reopened.load().unwrap().tasks.len(), 2);
+    assert!(reopened.load_archive().unwrap().is_empty());
+}
+
+#[test]
+fn test_archive_in_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    check_archive(
+        Box::new(JsonFileStorage::new(&path)),
+        Box::new(JsonFileStorage::new(&path)),
+    );
+    assert!(temp_dir.path().join("todo.json.archive").exists());
+
+    let path = temp_dir.path().join("todo.db");
+    check_archive(
+        Box::new(SqliteStorage::new(&path)),
+        Box::new(SqliteStorage::new(&path)),
+    );
+}
+
+#[test]
+fn test_memory_round_trip() {
+    let storage = MemoryStorage::new();
+    check_round_trip(Box::new(storage.clone()), Box::new(storage.clone()));
+    assert_eq!(storage.tasks().unwrap().len(), 2);
+}
+
+#[test]
+fn test_sqlite_rejects_other_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.db");
+    std::fs::write(&path, "not a database").unwrap();
+    let storage = SqliteStorage::new(&path);
+    assert!(!storage.exists());
+    assert!(matches!(storage.load(), Err(TodoError::Io(_))));
+}
+
+#[test]
+fn test_backend_configuration() {
+    assert_eq!(Backend::parse("SQLite"), Ok(Backend::Sqlite));
+    assert_eq!(Backend::parse("json"), Ok(Backend::JsonFile));
+    assert_eq!(Backend::parse("memory"), Ok(Backend::Memory));
+    assert_eq!(
+        Backend::parse("yaml"),
+        Err(TodoError::Validation(
+            "Invalid storage backend: yaml".to_string()
+        ))
+    );
+
+    assert_eq!(Backend::for_path(Path::new("work.db")), Backend::Sqlite);
+    assert_eq!(
+        Backend::for_path(Path::new("work.sqlite3")),
+        Backend::Sqlite
+    );
+    assert_eq!(Backend::for_path(Path::new("work.json")), Backend::JsonFile);
+    assert_eq!(Backend::for_path(Path::new("work")), Backend::JsonFile);
+}
+
+#[test]
+fn test_named_lists_with_sqlite() {
+    let temp_dir = TempDir::new().unwrap();
+    let lists = ListDirectory::with_backend(temp_dir.path().to_path_buf(), Backend::Sqlite);
+    let path = lists.create("work").unwrap();
+    assert_eq!(path, temp_dir.path().join("work.db"));
+    std::fs::write(temp_dir.path().join("home.json"), "[]").unwrap();
+    assert_eq!(lists.names().unwrap(), ["work"]);
+
+    let mut todo_list = TodoList::with_storage(Backend::Sqlite.open(&path));
+    todo_list
+        .add_task("Write report".into(), Priority::Low)
+        .unwrap();
+    assert_eq!(lists.task_count("work").unwrap(), 1);
+}
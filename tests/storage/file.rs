@@ -0,0 +1,139 @@
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::storage::backend::{Storage, TodoData};
+use to_do::storage::file::{backup_path, backup_paths, write_atomically};
+use to_do::storage::json_file::JsonFileStorage;
+use to_do::storage::sqlite::SqliteStorage;
+
+fn tasks(descriptions: &[&str]) -> TodoData {
+    let tasks = descriptions
+        .iter()
+        .enumerate()
+        .map(|(i, description)| {
+            serde_json::from_value(serde_json::json!({
+                "id": (i + 1).to_string(),
+                "description": description,
+                "priority": Priority::Low,
+                "completed": false,
+            }))
+            .unwrap()
+        })
+        .collect();
+    TodoData::from_tasks(tasks)
+}
+
+fn descriptions(storage: &dyn Storage) -> Vec<String> {
+    storage
+        .load()
+        .unwrap()
+        .tasks
+        .into_iter()
+        .map(|task| task.description)
+        .collect()
+}
+
+/// Save three successive versions, keeping two backups
+fn check_rotation_and_restore(mut storage: Box<dyn Storage>, path: &Path) {
+    assert!(storage.backups().unwrap().is_empty());
+    storage.save(&tasks(&["First"])).unwrap();
+    storage.save(&tasks(&["Second"])).unwrap();
+    storage.save(&tasks(&["Third"])).unwrap();
+    storage.save(&tasks(&["Fourth"])).unwrap();
+
+    let backups = storage.backups().unwrap();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(backups[0].number, 1);
+    assert_eq!(backups[0].path, backup_path(path, 1));
+    assert_eq!(backups[0].tasks, Some(1));
+    assert!(backups[1].saved_at.is_some());
+
+    storage.restore(2).unwrap();
+    assert_eq!(descriptions(storage.as_ref()), ["Second"]);
+    // The replaced version became the most recent backup, the restore can be reverted
+    storage.restore(1).unwrap();
+    assert_eq!(descriptions(storage.as_ref()), ["Fourth"]);
+    assert_eq!(backup_paths(path).len(), 2);
+
+    assert_eq!(
+        storage.restore(5).unwrap_err(),
+        TodoError::Validation(format!("Backup 5 of {} not found", storage.location()))
+    );
+}
+
+#[test]
+fn test_json_file_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    check_rotation_and_restore(Box::new(JsonFileStorage::new(&path).with_backups(2)), &path);
+}
+
+#[test]
+fn test_sqlite_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.db");
+    let storage = SqliteStorage::new(&path)
+        .with_backups(2)
+        .with_backup_interval(Duration::ZERO);
+    check_rotation_and_restore(Box::new(storage), &path);
+
+    // By default the database is copied once per hour, not on every save
+    let path = temp_dir.path().join("other.db");
+    let mut storage = SqliteStorage::new(&path);
+    for description in ["First", "Second", "Third"] {
+        storage.save(&tasks(&[description])).unwrap();
+    }
+    assert_eq!(backup_paths(&path).len(), 1);
+    assert_eq!(
+        descriptions(&SqliteStorage::new(backup_path(&path, 1))),
+        ["First"]
+    );
+}
+
+#[test]
+fn test_no_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let mut storage = JsonFileStorage::new(&path).with_backups(0);
+    storage.save(&tasks(&["First"])).unwrap();
+    storage.save(&tasks(&["Second"])).unwrap();
+    assert!(backup_paths(&path).is_empty());
+}
+
+#[test]
+fn test_write_atomically_leaves_no_temporary_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested/todo.json");
+    write_atomically(&path, b"first").unwrap();
+    write_atomically(&path, b"second").unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+    assert_eq!(
+        std::fs::read_dir(temp_dir.path().join("nested"))
+            .unwrap()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn test_unreadable_file_is_not_overwritten() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let mut storage = JsonFileStorage::new(&path);
+    storage.save(&tasks(&["First"])).unwrap();
+    storage.save(&tasks(&["Second"])).unwrap();
+    std::fs::write(&path, "not json").unwrap();
+
+    assert!(matches!(storage.load(), Err(TodoError::Parse(_))));
+    assert!(matches!(
+        storage.save(&tasks(&["Third"])),
+        Err(TodoError::Validation(_))
+    ));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "not json");
+
+    storage.restore(1).unwrap();
+    assert_eq!(descriptions(&storage), ["First"]);
+    storage.save(&tasks(&["Third"])).unwrap();
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::storage::file::lock_path;
+use to_do::storage::lock::FileLock;
+
+#[test]
+fn test_lock_is_exclusive_until_released() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("nested/todo.json");
+
+    let lock = FileLock::acquire(&path, Duration::ZERO).unwrap();
+    assert_eq!(lock.path, lock_path(&path));
+    assert_eq!(lock.path, temp_dir.path().join("nested/todo.json.lock"));
+
+    let error = FileLock::acquire(&path, Duration::from_millis(100)).unwrap_err();
+    assert!(matches!(error, TodoError::Locked(_)));
+    assert_eq!(error.exit_code(), 7);
+    assert!(error.to_string().contains("is locked by another ToDo"));
+
+    drop(lock);
+    assert!(FileLock::acquire(&path, Duration::ZERO).is_ok());
+}
+
+#[test]
+fn test_lock_waits_for_release() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+
+    let lock = FileLock::acquire(&path, Duration::ZERO).unwrap();
+    let releaser = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(100));
+        drop(lock);
+    });
+    assert!(FileLock::acquire(&path, Duration::from_secs(5)).is_ok());
+    releaser.join().unwrap();
+}
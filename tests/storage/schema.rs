@@ -0,0 +1,152 @@
+use serde_json::json;
+use tempfile::TempDir;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::todo_list::TodoList;
+use to_do::storage::backend::Storage;
+use to_do::storage::file::backup_path;
+use to_do::storage::json_file::JsonFileStorage;
+use to_do::storage::schema::{SCHEMA_VERSION, json_version, migrate_json};
+use to_do::storage::sqlite::SqliteStorage;
+
+/// Todo list file as saved before the schema was versioned
+fn bare_array() -> serde_json::Value {
+    json!([
+        {"id": "1", "description": "Buy milk", "priority": "High", "completed": false,
+         "created_at": "2026-10-01T09:00:00", "updated_at": "2026-10-03T09:00:00"},
+        {"id": "A", "description": "Walk the dog", "priority": "Low", "completed": true,
+         "created_at": "2026-09-01T09:00:00", "updated_at": "2026-09-02T09:00:00"},
+    ])
+}
+
+#[test]
+fn test_json_versions() {
+    assert_eq!(json_version(&bare_array()), Ok(1));
+    assert_eq!(json_version(&json!({"version": 2, "tasks": []})), Ok(2));
+    assert!(matches!(
+        json_version(&json!({"tasks": []})),
+        Err(TodoError::Parse(_))
+    ));
+    assert!(matches!(
+        json_version(&json!("tasks")),
+        Err(TodoError::Parse(_))
+    ));
+
+    let newer = json!({"version": SCHEMA_VERSION + 1, "next_id": 1, "tasks": []});
+    assert!(matches!(
+        migrate_json(newer, None),
+        Err(TodoError::Parse(_))
+    ));
+}
+
+#[test]
+fn test_migrate_bare_array() {
+    let (value, plan) = migrate_json(bare_array(), Some("work")).unwrap();
+    let plan = plan.unwrap();
+    assert_eq!((plan.from, plan.to), (1, 2));
+    assert_eq!(plan.steps.len(), 1);
+    assert_eq!(value["version"], 2);
+    assert_eq!(value["list"], "work");
+    assert_eq!(value["next_id"], 11);
+    assert_eq!(value["created_at"], "2026-09-01T09:00:00");
+    assert_eq!(value["updated_at"], "2026-10-03T09:00:00");
+    assert_eq!(value["tasks"][1]["description"], "Walk the dog");
+
+    let (same, plan) = migrate_json(value.clone(), Some("work")).unwrap();
+    assert_eq!(same, value);
+    assert_eq!(plan, None);
+}
+
+#[test]
+fn test_load_upgrades_file_in_place() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("work.json");
+    let old = bare_array().to_string();
+    std::fs::write(&path, &old).unwrap();
+
+    // A dry run leaves the file untouched
+    let mut storage = JsonFileStorage::new(&path);
+    assert_eq!(storage.migrate(true).unwrap().unwrap().from, 1);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), old);
+
+    let mut todo_list = TodoList::new();
+    todo_list.load_from_file(path.to_str().unwrap()).unwrap();
+    assert_eq!(todo_list.tasks.len(), 2);
+    assert_eq!(todo_list.next_id, 11);
+
+    let stored: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(stored["version"], SCHEMA_VERSION);
+    assert_eq!(stored["list"], "work");
+    assert_eq!(std::fs::read_to_string(backup_path(&path, 1)).unwrap(), old);
+    assert_eq!(storage.migrate(true), Ok(None));
+}
+
+#[test]
+fn test_next_id_is_not_reused() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let mut todo_list = TodoList::with_storage(Box::new(JsonFileStorage::new(&path)));
+    todo_list.add_task("First".into(), Priority::Low).unwrap();
+    let id = todo_list.add_task("Second".into(), Priority::Low).unwrap();
+    todo_list.remove_task(id).unwrap();
+
+    let mut loaded = TodoList::with_storage(Box::new(JsonFileStorage::new(&path)));
+    loaded.load().unwrap();
+    assert_eq!(loaded.next_id, 3);
+    assert!(loaded.created_at.is_some());
+    assert_eq!(loaded.created_at, todo_list.created_at);
+}
+
+#[test]
+fn test_newer_schema_is_not_overwritten() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.json");
+    let newer = json!({"version": SCHEMA_VERSION + 1, "next_id": 1, "tasks": []}).to_string();
+    std::fs::write(&path, &newer).unwrap();
+
+    let mut todo_list = TodoList::with_storage(Box::new(JsonFileStorage::new(&path)));
+    let error = todo_list.load().unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .contains("saved by a newer version of ToDo")
+    );
+    assert!(todo_list.add_task("Task".into(), Priority::Low).is_err());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), newer);
+}
+
+#[test]
+fn test_migrate_sqlite_database() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("todo.db");
+    // Database as saved before the schema was versioned
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    connection
+        .execute_batch(
+            "CREATE TABLE tasks (position INTEGER PRIMARY KEY, id TEXT NOT NULL UNIQUE, data TEXT NOT NULL);
+             INSERT INTO tasks VALUES (0, '3', '{\"id\":\"3\",\"description\":\"Buy milk\",\"priority\":\"Low\",\"completed\":false}');",
+        )
+        .unwrap();
+    drop(connection);
+
+    let mut storage = SqliteStorage::new(&path);
+    assert_eq!(storage.load().unwrap().next_id, 4);
+    assert_eq!(storage.migrate(true).unwrap().unwrap().from, 1);
+    assert_eq!(storage.migrate(false).unwrap().unwrap().to, SCHEMA_VERSION);
+    assert_eq!(storage.migrate(false), Ok(None));
+    assert!(backup_path(&path, 1).exists());
+
+    let connection = rusqlite::Connection::open(&path).unwrap();
+    let version: u32 = connection
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(version, SCHEMA_VERSION);
+    let list: String = connection
+        .query_row("SELECT value FROM metadata WHERE key = 'list'", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(list, "\"todo\"");
+    assert_eq!(storage.load().unwrap().tasks.len(), 1);
+}
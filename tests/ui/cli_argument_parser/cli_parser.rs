@@ -1,10 +1,15 @@
+use chrono::NaiveDate;
 use clap::Parser;
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::service::lists::ListDirectory;
+use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::memory::MemoryStorage;
 use to_do::ui::cli_argument_parser::cli_parser::{Cli, CliCommand};
+use to_do::ui::cli_argument_parser::output_format::OutputFormat;
 use to_do::ui::cli_argument_parser::trait_cli_displayer::TraitCliDisplayer;
-use to_do::{model::priority::Priority};
 use to_do::ui::{displayer::Displayer, menu_option::MenuOption};
-use to_do::service::manager::{Manager, ManagerTrait};
-    
+
 struct StackMockDisplayer {
     notifications: Vec<String>,
 }
@@ -37,9 +42,7 @@ impl Displayer for StackMockDisplayer {
     }
 }
 
-
 impl TraitCliDisplayer for StackMockDisplayer {
-    
     fn handle_add_task(&mut self, _manager: &mut Manager) {
         self.notifications.push("You selected: Add Task".into());
     }
@@ -47,33 +50,53 @@ impl TraitCliDisplayer for StackMockDisplayer {
     fn handle_edit_task(&mut self, _manager: &mut Manager) {
         self.notifications.push("You selected: Edit Task".into());
     }
+
+    fn is_terminal(&self) -> bool {
+        true
+    }
 }
 
 impl Clone for StackMockDisplayer {
     fn clone(&self) -> Self {
-        StackMockDisplayer { notifications: self.notifications.clone() }
+        StackMockDisplayer {
+            notifications: self.notifications.clone(),
+        }
     }
 }
 
+/// Manager keeping its tasks in memory, so that the tests leave no file behind
+fn create_manager(displayer: StackMockDisplayer) -> Manager {
+    let mut manager = Manager::new(Box::new(displayer));
+    manager.todo_list.storage = Box::new(MemoryStorage::new());
+    manager
+}
 
 #[test]
 fn test_parse_add_command() {
-    let cli = Cli::parse_from(&["ToDo", "add", "-d", "Test task", "-p", "High"]);
+    let cli = Cli::parse_from(["ToDo", "add", "-d", "Test task", "-p", "High"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::Add {
             description: Some("Test task".to_string()),
             priority: Priority::High,
+            due: None,
+            parent: None,
+            depends_on: vec![],
+            every: None,
         })
     );
 
     // Test default priority
-    let cli = Cli::parse_from(&["ToDo", "add", "-d", "Test task"]);
+    let cli = Cli::parse_from(["ToDo", "add", "-d", "Test task"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::Add {
             description: Some("Test task".to_string()),
             priority: Priority::Low,
+            due: None,
+            parent: None,
+            depends_on: vec![],
+            every: None,
         })
     );
 }
@@ -81,135 +104,254 @@ fn test_parse_add_command() {
 #[test]
 fn test_parse_list_command() {
     // No filters
-    let cli = Cli::parse_from(&["ToDo", "list"]);
+    let cli = Cli::parse_from(["ToDo", "list"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::List {
             priority: None,
             completed: None,
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         })
     );
 
     // With priority
-    let cli = Cli::parse_from(&["ToDo", "list", "-p", "Medium"]);
+    let cli = Cli::parse_from(["ToDo", "list", "-p", "Medium"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::List {
             priority: Some(Priority::Medium),
             completed: None,
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         })
     );
 
     // With completed
-    let cli = Cli::parse_from(&["ToDo", "list", "-c", "true"]);
+    let cli = Cli::parse_from(["ToDo", "list", "-c", "true"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::List {
             priority: None,
             completed: Some(true),
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         })
     );
 
     // With both
-    let cli = Cli::parse_from(&["ToDo", "list", "-p", "Low", "-c", "false"]);
+    let cli = Cli::parse_from(["ToDo", "list", "-p", "Low", "-c", "false"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::List {
             priority: Some(Priority::Low),
             completed: Some(false),
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         })
     );
 }
 
 #[test]
 fn test_parse_toggle_status_command() {
-    let cli = Cli::parse_from(&["ToDo", "toggle-status", "-i", "1"]);
+    let cli = Cli::parse_from(["ToDo", "toggle-status", "-i", "1"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::ToggleStatus {
-            id: "1".to_string()
+            ids: vec!["1".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
         })
     );
 
     // Test alias
-    let cli = Cli::parse_from(&["ToDo", "toggle", "-i", "2"]);
+    let cli = Cli::parse_from(["ToDo", "toggle", "-i", "2"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::ToggleStatus {
-            id: "2".to_string()
+            ids: vec!["2".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
         })
     );
 
-    let cli = Cli::parse_from(&["ToDo", "check", "-i", "3"]);
+    let cli = Cli::parse_from(["ToDo", "check", "-i", "3"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::ToggleStatus {
-            id: "3".to_string()
+            ids: vec!["3".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
         })
     );
 }
 
 #[test]
 fn test_parse_remove_command() {
-    let cli = Cli::parse_from(&["ToDo", "remove", "-i", "1"]);
+    let cli = Cli::parse_from(["ToDo", "remove", "-i", "1"]);
     assert_eq!(
         cli.command,
         Some(CliCommand::Remove {
-            id: "1".to_string()
+            ids: vec!["1".to_string()],
+            filter: None,
+            dry_run: false,
         })
     );
 }
 
 #[test]
 fn test_evaluate_add_command() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
+    let mut manager = create_manager(StackMockDisplayer::new());
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::Add {
             description: Some("Test task".to_string()),
             priority: Priority::High,
+            due: None,
+            parent: None,
+            depends_on: vec![],
+            every: None,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(manager.get_tasks().len(), 1);
     assert_eq!(manager.get_tasks()[0].description, "Test task");
     assert_eq!(manager.get_tasks()[0].priority, Priority::High);
     assert_eq!(
         displayer.get_notifications(),
-        vec!["Task added successfully."]
+        vec!["Task added successfully with ID: 1."]
     );
 }
 
 #[test]
 fn test_evaluate_list_command_empty() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
+    let mut manager = create_manager(StackMockDisplayer::new());
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::List {
             priority: None,
             completed: None,
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(displayer.notifications, vec!["No tasks found."]);
 }
 
 #[test]
 fn test_evaluate_list_command_with_tasks() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
-    manager.add_task("Task 2".as_ref(), &Priority::High);
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    manager
+        .add_task("Task 2".as_ref(), &Priority::High)
+        .unwrap();
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::List {
             priority: None,
             completed: None,
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(
         displayer.notifications,
         vec![
@@ -222,18 +364,39 @@ fn test_evaluate_list_command_with_tasks() {
 
 #[test]
 fn test_evaluate_list_command_filtered_priority() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
-    manager.add_task("Task 2".as_ref(), &Priority::High);
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    manager
+        .add_task("Task 2".as_ref(), &Priority::High)
+        .unwrap();
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::List {
             priority: Some(Priority::Low),
             completed: None,
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(
         displayer.notifications,
         vec![
@@ -245,19 +408,40 @@ fn test_evaluate_list_command_filtered_priority() {
 
 #[test]
 fn test_evaluate_list_command_filtered_completed() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
-    manager.toggle_task_status("1".as_ref());
-    manager.add_task("Task 2".as_ref(), &Priority::High);
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    manager.toggle_task_status("1".as_ref(), false).unwrap();
+    manager
+        .add_task("Task 2".as_ref(), &Priority::High)
+        .unwrap();
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::List {
             priority: None,
             completed: Some(true),
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(
         displayer.notifications,
         vec![
@@ -269,19 +453,38 @@ fn test_evaluate_list_command_filtered_completed() {
 
 #[test]
 fn test_evaluate_list_command_filtered_both() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
-    manager.toggle_task_status("1".as_ref());
-    manager.add_task("Task 2".as_ref(), &Priority::Low);
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    manager.toggle_task_status("1".as_ref(), false).unwrap();
+    manager.add_task("Task 2".as_ref(), &Priority::Low).unwrap();
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::List {
             priority: Some(Priority::Low),
             completed: Some(true),
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec![],
+            not_tags: vec![],
+            any_tag: false,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(
         displayer.notifications,
         vec![
@@ -293,17 +496,24 @@ fn test_evaluate_list_command_filtered_both() {
 
 #[test]
 fn test_evaluate_toggle_status_success() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::ToggleStatus {
-            id: "1".to_string(),
+            ids: vec!["1".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
-    assert_eq!(manager.get_tasks()[0].completed, true);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert!(manager.get_tasks()[0].completed);
     assert_eq!(
         displayer.notifications,
         vec!["Task status toggled successfully."]
@@ -312,15 +522,22 @@ fn test_evaluate_toggle_status_success() {
 
 #[test]
 fn test_evaluate_toggle_status_error() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
+    let mut manager = create_manager(StackMockDisplayer::new());
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::ToggleStatus {
-            id: "999".to_string(),
+            ids: vec!["999".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
     assert_eq!(
         displayer.notifications,
         vec!["Error: Task with ID: 999 not found"]
@@ -329,31 +546,46 @@ fn test_evaluate_toggle_status_error() {
 
 #[test]
 fn test_evaluate_remove_success() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::Remove {
-            id: "1".to_string(),
+            ids: vec!["1".to_string()],
+            filter: None,
+            dry_run: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(manager.get_tasks().len(), 0);
     assert_eq!(displayer.notifications, vec!["Task removed successfully."]);
 }
 
 #[test]
 fn test_evaluate_remove_error() {
-    let mut manager = Manager::new(Box::new(StackMockDisplayer::new()));
+    let mut manager = create_manager(StackMockDisplayer::new());
     let mut displayer = StackMockDisplayer::new();
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::Remove {
-            id: "999".to_string(),
+            ids: vec!["999".to_string()],
+            filter: None,
+            dry_run: false,
         }),
     };
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    let error = cli
+        .evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+    assert_eq!(error, TodoError::NotFound("999".to_string()));
+    assert_eq!(error.exit_code(), 3);
     assert_eq!(
         displayer.notifications,
         vec!["Error: Task with ID: 999 not found"]
@@ -363,109 +595,1382 @@ fn test_evaluate_remove_error() {
 #[test]
 fn test_evaluate_edit_pattern_success() {
     let mut displayer = StackMockDisplayer::new();
-    let mut manager = Manager::new(Box::new(displayer.clone()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
+    let mut manager = create_manager(displayer.clone());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
 
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: None,
     };
 
-    let command = 
-        CliCommand::Edit { id: Some("1".to_string()), pattern: Some("Task".to_string()),
-            replace: Some("Tarea".to_string()), priority: None  };
-    let command_list= CliCommand::List { priority: None, completed: None };
-    
-    cli.evaluate_command(command, &mut manager, &mut displayer);
-    cli.evaluate_command(command_list, &mut manager, &mut displayer);
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: Some("Task".to_string()),
+        replace: Some("Tarea".to_string()),
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
     assert!(
-        displayer.notifications.clone()
-        .contains(&"ID: 1, Description: Tarea 1, Priority: Low, Completed: false".to_string())
+        displayer
+            .notifications
+            .clone()
+            .contains(&"ID: 1, Description: Tarea 1, Priority: Low, Completed: false".to_string())
     );
 }
 
 #[test]
 fn test_evaluate_edit_pattern_task_not_found() {
     let mut displayer = StackMockDisplayer::new();
-    let mut manager = Manager::new(Box::new(displayer.clone()));
+    let mut manager = create_manager(displayer.clone());
 
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: None,
     };
 
-    let command = 
-        CliCommand::Edit { id: Some("1".to_string()), pattern: Some("Task".to_string()),
-            replace: Some("Tarea".to_string()), priority: None  };
-    
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: Some("Task".to_string()),
+        replace: Some("Tarea".to_string()),
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
     assert!(
-        displayer.notifications.clone()
-        .contains(&"Error: Task with ID: 1 not found".to_string())
+        displayer
+            .notifications
+            .clone()
+            .contains(&"Error: Task with ID: 1 not found".to_string())
     );
 }
 
 #[test]
 fn test_evaluate_edit_pattern_no_match() {
     let mut displayer = StackMockDisplayer::new();
-    let mut manager = Manager::new(Box::new(displayer.clone()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
+    let mut manager = create_manager(displayer.clone());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
 
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: None,
     };
 
-    let command = 
-        CliCommand::Edit { id: Some("1".to_string()), pattern: Some("Tarea".to_string()),
-            replace: Some("Tarea".to_string()), priority: None  };
-    let command_list= CliCommand::List { priority: None, completed: None };
-    
-    cli.evaluate_command(command, &mut manager, &mut displayer);
-    cli.evaluate_command(command_list, &mut manager, &mut displayer);
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: Some("Tarea".to_string()),
+        replace: Some("Tarea".to_string()),
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
     assert!(
-        displayer.notifications.clone()
-        .contains(&"ID: 1, Description: Task 1, Priority: Low, Completed: false".to_string())
+        displayer
+            .notifications
+            .clone()
+            .contains(&"ID: 1, Description: Task 1, Priority: Low, Completed: false".to_string())
     );
 }
 
 #[test]
 fn test_evaluate_edit_pattern_none_replace_not_blank() {
     let mut displayer = StackMockDisplayer::new();
-    let mut manager = Manager::new(Box::new(displayer.clone()));
-    manager.add_task("Task 1".as_ref(), &Priority::Low);
+    let mut manager = create_manager(displayer.clone());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
 
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: None,
     };
 
-    let command = 
-        CliCommand::Edit { id: Some("1".to_string()), pattern: None,
-            replace: Some("Tarea".to_string()), priority: None  };
-    let command_list= CliCommand::List { priority: None, completed: None };
-    
-    cli.evaluate_command(command, &mut manager, &mut displayer);
-    cli.evaluate_command(command_list, &mut manager, &mut displayer);
-    assert!(
-        displayer.notifications.clone()
-        .contains(&"Error: --pattern and --replace must both be provided or both omitted.".to_string())
-    );
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: Some("Tarea".to_string()),
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert!(displayer.notifications.clone().contains(
+        &"Error: --pattern and --replace must both be provided or both omitted.".to_string()
+    ));
 }
 
 #[test]
 fn test_evaluate_edit_alt_display() {
     let mut displayer = StackMockDisplayer::new();
-    let mut manager = Manager::new(Box::new(displayer.clone()));
+    let mut manager = create_manager(displayer.clone());
     let cli = Cli {
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
         command: Some(CliCommand::Edit {
-            id: None,
+            ids: vec![],
+            filter: None,
+            dry_run: false,
             pattern: None,
             replace: None,
-            priority: None
+            priority: None,
+            due: None,
+            no_due: false,
+            tags: vec![],
+            untags: vec![],
+            depends_on: vec![],
+            no_depends_on: vec![],
+            every: None,
+            no_every: false,
         }),
     };
 
     let command = cli.command.as_ref().expect("Error during test").clone();
-    cli.evaluate_command(command, &mut manager, &mut displayer);
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
     assert_eq!(
         displayer.notifications.clone(),
         vec!["You selected: Edit Task"]
     );
-}
\ No newline at end of file
+}
+#[test]
+fn test_evaluate_add_command_with_due() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    let command = CliCommand::Add {
+        description: Some("Test task".to_string()),
+        priority: Priority::High,
+        due: Some("2020-01-31 09:00".to_string()),
+        parent: None,
+        depends_on: vec![],
+        every: None,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    let due = manager.get_tasks()[0].due.expect("Due date not set");
+    assert_eq!(due.to_string(), "2020-01-31 09:00");
+
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: true,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "Task added successfully with ID: 1.",
+            "1 tasks found with overdue = true",
+            "ID: 1, Description: Test task, Priority: High, Completed: false, Due: 2020-01-31 09:00 (OVERDUE)",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_add_command_invalid_due() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    let command = CliCommand::Add {
+        description: Some("Test task".to_string()),
+        priority: Priority::High,
+        due: Some("someday".to_string()),
+        parent: None,
+        depends_on: vec![],
+        every: None,
+    };
+    let error = cli
+        .evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+    assert_eq!(error.exit_code(), 8);
+    assert_eq!(manager.get_tasks().len(), 0);
+    assert_eq!(
+        displayer.notifications,
+        vec!["Error: Invalid due date value: someday"]
+    );
+}
+
+#[test]
+fn test_evaluate_list_command_due_range() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    for (description, due) in [("Task 1", "2030-01-10"), ("Task 2", "2030-02-10")] {
+        let command = CliCommand::Add {
+            description: Some(description.to_string()),
+            priority: Priority::Low,
+            due: Some(due.to_string()),
+            parent: None,
+            depends_on: vec![],
+            every: None,
+        };
+        cli.evaluate_command(command, &mut manager, &mut displayer)
+            .unwrap();
+    }
+    manager.add_task("Task 3".as_ref(), &Priority::Low).unwrap();
+    displayer.notifications.clear();
+
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: Some("2030-02-01".to_string()),
+        due_after: Some("2030-01-01".to_string()),
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "1 tasks found with due before 2030-02-01 and due after 2030-01-01",
+            "ID: 1, Description: Task 1, Priority: Low, Completed: false, Due: 2030-01-10",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_edit_due() {
+    let mut displayer = StackMockDisplayer::new();
+    let mut manager = create_manager(displayer.clone());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: None,
+        priority: None,
+        due: Some("2030-05-01".to_string()),
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        manager.get_tasks()[0].due.unwrap().to_string(),
+        "2030-05-01"
+    );
+
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: None,
+        priority: None,
+        due: None,
+        no_due: true,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert!(manager.get_tasks()[0].due.is_none());
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "Setting task due date to '2030-05-01'",
+            "Removing task due date"
+        ]
+    );
+
+    manager.undo().expect("Undo failed");
+    assert_eq!(
+        manager.get_tasks()[0].due.unwrap().to_string(),
+        "2030-05-01"
+    );
+}
+
+fn create_manager_with_tagged_tasks() -> Manager {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    manager
+        .add_task("Deploy api +backend +ops".as_ref(), &Priority::High)
+        .unwrap();
+    manager
+        .add_task("Write guide +docs".as_ref(), &Priority::Low)
+        .unwrap();
+    manager
+        .add_task("Fix endpoint +backend".as_ref(), &Priority::Medium)
+        .unwrap();
+    manager.toggle_task_status("3".as_ref(), false).unwrap();
+    manager
+}
+
+#[test]
+fn test_parse_list_command_with_tags() {
+    let cli = Cli::parse_from([
+        "ToDo",
+        "list",
+        "-t",
+        "backend",
+        "--tag",
+        "ops",
+        "--not-tag",
+        "docs",
+        "--any-tag",
+    ]);
+    assert_eq!(
+        cli.command,
+        Some(CliCommand::List {
+            priority: None,
+            completed: None,
+            overdue: false,
+            due_before: None,
+            due_after: None,
+            tags: vec!["backend".to_string(), "ops".to_string()],
+            not_tags: vec!["docs".to_string()],
+            any_tag: true,
+            ready: false,
+            blocked: false,
+            created_since: None,
+            updated_since: None,
+            completed_since: None,
+            sort: vec![],
+            reverse: false,
+            verbose: false,
+            archived: false,
+        })
+    );
+}
+
+#[test]
+fn test_evaluate_list_command_filtered_tags() {
+    let mut manager = create_manager_with_tagged_tasks();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec!["backend".to_string(), "ops".to_string()],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "1 tasks found with tags backend and ops",
+            "ID: 1, Description: Deploy api, Priority: High, Completed: false, Tags: backend, ops",
+        ]
+    );
+
+    displayer.notifications.clear();
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec!["+Docs".to_string(), "ops".to_string()],
+        not_tags: vec![],
+        any_tag: true,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications[0],
+        "2 tasks found with tags docs or ops"
+    );
+
+    displayer.notifications.clear();
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec!["backend".to_string()],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "1 tasks found with excluded tags backend",
+            "ID: 2, Description: Write guide, Priority: Low, Completed: false, Tags: docs",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_edit_tags_and_undo() {
+    let mut manager = create_manager_with_tagged_tasks();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: None,
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec!["urgent".to_string()],
+        untags: vec!["ops".to_string()],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    let tags = manager
+        .get_task("1")
+        .unwrap()
+        .tags
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec!["backend", "urgent"]);
+    assert_eq!(
+        displayer.notifications,
+        vec!["Adding tag 'urgent'", "Removing tag 'ops'"]
+    );
+
+    manager.undo().expect("Undo failed");
+    let tags = manager
+        .get_task("1")
+        .unwrap()
+        .tags
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec!["backend", "ops"]);
+
+    manager.redo().expect("Redo failed");
+    let tags = manager
+        .get_task("1")
+        .unwrap()
+        .tags
+        .iter()
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(tags, vec!["backend", "urgent"]);
+}
+
+#[test]
+fn test_evaluate_tags_command() {
+    let mut manager = create_manager_with_tagged_tasks();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    cli.evaluate_command(CliCommand::Tags, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "backend: 1 open, 1 closed",
+            "docs: 1 open, 0 closed",
+            "ops: 1 open, 0 closed",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_tags_command_empty() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    cli.evaluate_command(CliCommand::Tags, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(displayer.notifications, vec!["No tags found."]);
+}
+
+#[test]
+fn test_evaluate_list_command_tree() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    manager
+        .add_task("Release".as_ref(), &Priority::High)
+        .unwrap();
+    manager.add_task("Other".as_ref(), &Priority::Low).unwrap();
+    for description in ["Build", "Publish"] {
+        let command = CliCommand::Add {
+            description: Some(description.to_string()),
+            priority: Priority::Medium,
+            due: None,
+            parent: Some("1".to_string()),
+            depends_on: vec![],
+            every: None,
+        };
+        cli.evaluate_command(command, &mut manager, &mut displayer)
+            .unwrap();
+    }
+    manager.toggle_task_status("3".as_ref(), false).unwrap();
+    displayer.notifications.clear();
+
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "4 tasks found",
+            "ID: 1, Description: Release, Priority: High, Completed: false, Progress: 1/2 done",
+            "└─ ID: 3, Description: Build, Priority: Medium, Completed: true",
+            "└─ ID: 4, Description: Publish, Priority: Medium, Completed: false",
+            "ID: 2, Description: Other, Priority: Low, Completed: false",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_add_command_parent_not_found() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    let command = CliCommand::Add {
+        description: Some("Orphan".to_string()),
+        priority: Priority::Medium,
+        due: None,
+        parent: Some("9".to_string()),
+        depends_on: vec![],
+        every: None,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+    assert_eq!(manager.get_tasks().len(), 0);
+    assert_eq!(
+        displayer.notifications,
+        vec!["Error: Task with ID: 9 not found"]
+    );
+}
+
+fn create_manager_with_dependencies() -> Manager {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+    manager
+        .add_task("Design".as_ref(), &Priority::High)
+        .unwrap();
+    let command = CliCommand::Add {
+        description: Some("Implement".to_string()),
+        priority: Priority::Medium,
+        due: None,
+        parent: None,
+        depends_on: vec!["1".to_string()],
+        every: None,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    manager
+}
+
+#[test]
+fn test_evaluate_list_command_ready_and_blocked() {
+    let mut manager = create_manager_with_dependencies();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: false,
+        blocked: true,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "1 tasks found with blocked = true",
+            "ID: 2, Description: Implement, Priority: Medium, Completed: false, Depends on: 1 (BLOCKED)",
+        ]
+    );
+
+    displayer.notifications.clear();
+    let command_list = CliCommand::List {
+        priority: None,
+        completed: None,
+        overdue: false,
+        due_before: None,
+        due_after: None,
+        tags: vec![],
+        not_tags: vec![],
+        any_tag: false,
+        ready: true,
+        blocked: false,
+        created_since: None,
+        updated_since: None,
+        completed_since: None,
+        sort: vec![],
+        reverse: false,
+        verbose: false,
+        archived: false,
+    };
+    cli.evaluate_command(command_list, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "1 tasks found with ready = true",
+            "ID: 1, Description: Design, Priority: High, Completed: false",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_toggle_status_blocked() {
+    let mut manager = create_manager_with_dependencies();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    cli.evaluate_command(
+        CliCommand::ToggleStatus {
+            ids: vec!["2".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
+        },
+        &mut manager,
+        &mut displayer,
+    )
+    .unwrap_err();
+    assert!(!manager.get_task("2").unwrap().completed);
+
+    cli.evaluate_command(
+        CliCommand::ToggleStatus {
+            ids: vec!["2".to_string()],
+            filter: None,
+            force: true,
+            dry_run: false,
+        },
+        &mut manager,
+        &mut displayer,
+    )
+    .unwrap();
+    assert!(manager.get_task("2").unwrap().completed);
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "Error: Task with ID: 2 depends on open tasks: 1. Use --force to complete it anyway.",
+            "Warning: Task with ID: 2 depends on open tasks: 1",
+            "Task status toggled successfully.",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_toggle_status_with_dependencies() {
+    let mut manager = create_manager_with_dependencies();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    // Task 1 is completed along with task 2, which is therefore not blocked
+    cli.evaluate_command(
+        CliCommand::ToggleStatus {
+            ids: vec!["1".to_string(), "2".to_string()],
+            filter: None,
+            force: false,
+            dry_run: false,
+        },
+        &mut manager,
+        &mut displayer,
+    )
+    .unwrap();
+    assert!(manager.get_task("1").unwrap().completed);
+    assert!(manager.get_task("2").unwrap().completed);
+    assert_eq!(
+        displayer.notifications,
+        vec!["Status of 2 tasks toggled successfully."]
+    );
+}
+
+#[test]
+fn test_evaluate_edit_dependency_cycle() {
+    let mut manager = create_manager_with_dependencies();
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    let command = CliCommand::Edit {
+        ids: vec!["1".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: None,
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec!["2".to_string()],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+    assert!(manager.get_task("1").unwrap().depends_on.is_empty());
+    assert_eq!(
+        displayer.notifications,
+        vec!["Error: Task with ID: 1 cannot depend on 2 because it would create a cycle"]
+    );
+
+    displayer.notifications.clear();
+    let command = CliCommand::Edit {
+        ids: vec!["2".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: None,
+        priority: None,
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec!["1".to_string()],
+        every: None,
+        no_every: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert!(!manager.is_blocked("2"));
+    assert_eq!(
+        displayer.notifications,
+        vec!["Removing dependency on task '1'"]
+    );
+}
+
+#[test]
+fn test_evaluate_add_and_edit_recurrence() {
+    let mut displayer = StackMockDisplayer::new();
+    let mut manager = create_manager(displayer.clone());
+    let cli = Cli::parse_from([
+        "ToDo",
+        "add",
+        "-d",
+        "Pay rent",
+        "--due",
+        "2030-01-01",
+        "--every",
+        "monthly 1",
+    ]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        manager.get_tasks()[0].to_string(),
+        "ID: 1, Description: Pay rent, Priority: Low, Completed: false, Due: 2030-01-01, Repeats: monthly on day 1"
+    );
+
+    let cli = Cli::parse_from(["ToDo", "toggle-status", "-i", "1"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(manager.get_tasks().len(), 2);
+    assert_eq!(
+        manager.get_tasks()[1].due.unwrap().to_string(),
+        "2030-02-01"
+    );
+
+    let cli = Cli::parse_from(["ToDo", "edit", "-i", "2", "--no-every"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert!(manager.get_tasks()[1].recurrence.is_none());
+
+    let cli = Cli::parse_from(["ToDo", "edit", "-i", "2", "--every", "yearly"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "Task added successfully with ID: 1.",
+            "Task status toggled successfully.",
+            "Removing task recurrence",
+            "Error: Invalid recurrence value: yearly",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_list_completed_since_verbose() {
+    let mut displayer = StackMockDisplayer::new();
+    let mut manager = create_manager(displayer.clone());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    manager.add_task("Task 2".as_ref(), &Priority::Low).unwrap();
+    manager.add_task("Task 3".as_ref(), &Priority::Low).unwrap();
+    manager.toggle_task_status("3".as_ref(), false).unwrap();
+    manager.toggle_task_status("1".as_ref(), false).unwrap();
+    manager.get_task_mut("3").unwrap().completed_at = NaiveDate::from_ymd_opt(2020, 1, 1)
+        .unwrap()
+        .and_hms_opt(8, 0, 0);
+    let cli = Cli::parse_from([
+        "ToDo",
+        "list",
+        "--completed-since",
+        "7d",
+        "--sort",
+        "completed",
+        "-v",
+    ]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+
+    let task = manager.get_task("1").unwrap();
+    assert_eq!(displayer.notifications.len(), 2);
+    assert!(displayer.notifications[0].starts_with("1 tasks found with completed since "));
+    assert_eq!(
+        displayer.notifications[1],
+        format!("{}{}", task, task.timestamps())
+    );
+    assert!(displayer.notifications[1].contains(", Completed on: "));
+
+    let cli = Cli::parse_from(["ToDo", "list", "--sort", "completed"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    displayer.notifications.clear();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    let ids = displayer.notifications[1..]
+        .iter()
+        .map(|line| line.split(',').next().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["ID: 2", "ID: 3", "ID: 1"]);
+}
+
+#[test]
+fn test_evaluate_list_sorted() {
+    let mut displayer = StackMockDisplayer::new();
+    let mut manager = create_manager(displayer.clone());
+    manager.add_task("Task 1".as_ref(), &Priority::Low).unwrap();
+    manager
+        .add_task("Task 2".as_ref(), &Priority::High)
+        .unwrap();
+    manager.add_task("Task 3".as_ref(), &Priority::Low).unwrap();
+    let cli = Cli::parse_from(["ToDo", "list", "--sort", "priority,id", "--reverse"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "3 tasks found",
+            "ID: 3, Description: Task 3, Priority: Low, Completed: false",
+            "ID: 1, Description: Task 1, Priority: Low, Completed: false",
+            "ID: 2, Description: Task 2, Priority: High, Completed: false",
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_search() {
+    let mut displayer = StackMockDisplayer::new();
+    let mut manager = create_manager(displayer.clone());
+    manager
+        .add_task("Buy milk".as_ref(), &Priority::Low)
+        .unwrap();
+    manager
+        .add_task("Call the milkman".as_ref(), &Priority::High)
+        .unwrap();
+    manager
+        .add_task("Walk the dog".as_ref(), &Priority::Low)
+        .unwrap();
+
+    let cli = Cli::parse_from(["ToDo", "search", "MILK", "--ignore-case", "-p", "low"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+
+    let cli = Cli::parse_from(["ToDo", "search", "milk", "--no-highlight", "-c", "true"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+
+    let cli = Cli::parse_from(["ToDo", "search", "milk|dog", "--regex", "--ids-only"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+
+    let cli = Cli::parse_from(["ToDo", "search", "[", "--regex"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+
+    assert_eq!(displayer.notifications.len(), 5);
+    assert_eq!(
+        displayer.notifications[..4],
+        [
+            "1 tasks found matching 'MILK' with priority Low",
+            "ID: 1, Description: Buy \x1b[1;33mmilk\x1b[0m, Priority: Low, Completed: false",
+            "No tasks found matching 'milk' with completed = true",
+            "1,2,3",
+        ]
+    );
+    assert!(displayer.notifications[4].starts_with("Error: Invalid search pattern '['"));
+}
+
+#[test]
+fn test_evaluate_structured_output() {
+    let mut displayer = StackMockDisplayer::new();
+    let mut manager = create_manager(displayer.clone());
+
+    let cli = Cli::parse_from(["ToDo", "add", "-d", "Buy milk", "--output", "jsonl"]);
+    assert_eq!(cli.output, OutputFormat::Jsonl);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+
+    let cli = Cli::parse_from(["ToDo", "--output", "csv", "list", "-p", "low"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+
+    let cli = Cli::parse_from(["ToDo", "toggle-status", "-i", "7", "--output", "jsonl"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+
+    let cli = Cli::parse_from(["ToDo", "edit", "--output", "json"]);
+    let command = cli.command.as_ref().expect("Error during test").clone();
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap_err();
+
+    assert_eq!(displayer.notifications.len(), 4);
+    assert_eq!(
+        displayer.notifications[0],
+        "{\"action\":\"add\",\"id\":\"1\"}"
+    );
+    assert!(displayer.notifications[1].starts_with("id,description,priority,completed,due,"));
+    assert!(displayer.notifications[1].contains("\n1,Buy milk,Low,false,"));
+    assert_eq!(
+        displayer.notifications[2],
+        "{\"error\":\"not_found\",\"message\":\"Task with ID: 7 not found\",\"exit_code\":3}"
+    );
+    assert!(displayer.notifications[3].contains("\"error\": \"validation\""));
+}
+
+#[test]
+fn test_evaluate_lists() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let lists = ListDirectory::new(temp_dir.path().to_path_buf());
+    let mut displayer = StackMockDisplayer::new();
+
+    for args in [
+        vec!["ToDo", "lists"],
+        vec!["ToDo", "lists", "create", "work"],
+        vec!["ToDo", "lists", "rename", "work", "job"],
+        vec!["ToDo", "lists", "delete", "work"],
+        vec!["ToDo", "lists", "--output", "csv"],
+        vec!["ToDo", "lists", "delete", "job"],
+    ] {
+        let cli = Cli::parse_from(args);
+        let Some(CliCommand::Lists { action }) = cli.command.clone() else {
+            panic!("Expected the lists command");
+        };
+        let _ = cli.evaluate_lists(action, &lists, &mut displayer);
+    }
+
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "No lists found.".to_string(),
+            "List 'work' created.".to_string(),
+            "List 'work' renamed to 'job'.".to_string(),
+            "Error: List 'work' not found".to_string(),
+            format!(
+                "name,path,tasks\njob,{},0",
+                temp_dir.path().join("job.json").display()
+            ),
+            "List 'job' deleted.".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_bulk_commands() {
+    let mut manager = create_manager(StackMockDisplayer::new());
+    for (description, priority) in [
+        ("One", Priority::Low),
+        ("Two", Priority::High),
+        ("Three", Priority::Low),
+    ] {
+        manager.add_task(description.as_ref(), &priority).unwrap();
+    }
+    let mut displayer = StackMockDisplayer::new();
+    let cli = Cli {
+        command: None,
+        output: OutputFormat::Text,
+        file: None,
+        list: None,
+    };
+
+    let command = CliCommand::ToggleStatus {
+        ids: vec![],
+        filter: Some("priority=low".to_string()),
+        force: false,
+        dry_run: true,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert!(manager.get_tasks().iter().all(|task| !task.completed));
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "Dry run, nothing was changed. 2 tasks would be toggled:",
+            "ID: 1, Description: One, Priority: Low, Completed: false",
+            "ID: 3, Description: Three, Priority: Low, Completed: false",
+        ]
+    );
+
+    displayer.notifications.clear();
+    let command = CliCommand::ToggleStatus {
+        ids: vec![],
+        filter: Some("priority=low".to_string()),
+        force: false,
+        dry_run: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec!["Status of 2 tasks toggled successfully."]
+    );
+    assert!(manager.get_task("1").unwrap().completed);
+    assert!(manager.get_task("3").unwrap().completed);
+
+    displayer.notifications.clear();
+    let command = CliCommand::Edit {
+        ids: vec!["1-2".to_string()],
+        filter: None,
+        dry_run: false,
+        pattern: None,
+        replace: None,
+        priority: Some(Priority::Medium),
+        due: None,
+        no_due: false,
+        tags: vec![],
+        untags: vec![],
+        depends_on: vec![],
+        no_depends_on: vec![],
+        every: None,
+        no_every: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(
+        displayer.notifications,
+        vec![
+            "Task with ID: 1",
+            "Replacing task priority from 'Low' to 'Medium'",
+            "Task with ID: 2",
+            "Replacing task priority from 'High' to 'Medium'",
+        ]
+    );
+
+    let command = CliCommand::Remove {
+        ids: vec!["1".to_string(), "3".to_string()],
+        filter: None,
+        dry_run: false,
+    };
+    cli.evaluate_command(command, &mut manager, &mut displayer)
+        .unwrap();
+    assert_eq!(manager.get_tasks().len(), 1);
+
+    manager.undo().expect("Undo failed");
+    assert_eq!(manager.get_tasks().len(), 3);
+    manager.undo().expect("Undo failed");
+    assert!(
+        manager
+            .get_tasks()
+            .iter()
+            .all(|task| task.priority != Priority::Medium)
+    );
+    manager.undo().expect("Undo failed");
+    assert!(manager.get_tasks().iter().all(|task| !task.completed));
+}
@@ -0,0 +1,126 @@
+use to_do::error::TodoError;
+use to_do::model::priority::Priority;
+use to_do::model::todo_list::TodoList;
+use to_do::storage::memory::MemoryStorage;
+use to_do::ui::cli_argument_parser::output_format::{
+    ActionRecord, ErrorRecord, OutputFormat, TagRecord, TaskRecord,
+};
+
+fn create_records() -> Vec<TaskRecord> {
+    let mut todo_list = TodoList::with_storage(Box::new(MemoryStorage::new()));
+    todo_list
+        .add_task("Buy \"milk\", eggs".into(), Priority::High)
+        .unwrap();
+    todo_list
+        .add_task("Line\twith tab".into(), Priority::Low)
+        .unwrap();
+    let task = todo_list.get_task_mut("1").unwrap();
+    task.tags.insert("home".into());
+    task.tags.insert("shop".into());
+    todo_list
+        .tasks
+        .iter()
+        .map(|task| {
+            let mut record = TaskRecord::from(task);
+            record.created_at = None;
+            record.updated_at = None;
+            record
+        })
+        .collect()
+}
+
+#[test]
+fn test_text_output_renders_nothing() {
+    let records = create_records();
+    assert_eq!(OutputFormat::Text.render_list(&records).unwrap(), None);
+}
+
+#[test]
+fn test_json_output_shape() {
+    let records = create_records();
+    let output = OutputFormat::Json.render_list(&records).unwrap().unwrap();
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        value[0],
+        serde_json::json!({
+            "id": "1",
+            "description": "Buy \"milk\", eggs",
+            "priority": "High",
+            "completed": false,
+            "due": null,
+            "tags": ["home", "shop"],
+            "parent": null,
+            "depends_on": [],
+            "recurrence": null,
+            "created_at": null,
+            "updated_at": null,
+            "completed_at": null,
+        })
+    );
+    assert_eq!(value.as_array().unwrap().len(), 2);
+
+    let action = ActionRecord {
+        action: "add".into(),
+        id: "3".into(),
+    };
+    let output = OutputFormat::Json.render_one(&action).unwrap().unwrap();
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(value, serde_json::json!({"action": "add", "id": "3"}));
+}
+
+#[test]
+fn test_jsonl_output() {
+    let records = create_records();
+    let output = OutputFormat::Jsonl.render_list(&records).unwrap().unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("{\"id\":\"2\",\"description\":\"Line\\twith tab\""));
+
+    let empty: Vec<TaskRecord> = Vec::new();
+    assert_eq!(
+        OutputFormat::Jsonl.render_list(&empty).unwrap(),
+        Some(String::new())
+    );
+}
+
+#[test]
+fn test_csv_output_quotes_fields() {
+    let records = create_records();
+    let output = OutputFormat::Csv.render_list(&records).unwrap().unwrap();
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec![
+            "id,description,priority,completed,due,tags,parent,depends_on,recurrence,created_at,updated_at,completed_at",
+            "1,\"Buy \"\"milk\"\", eggs\",High,false,,home;shop,,,,,,",
+            "2,Line\twith tab,Low,false,,,,,,,,",
+        ]
+    );
+}
+
+#[test]
+fn test_tsv_output_escapes_tabs() {
+    let records = create_records();
+    let output = OutputFormat::Tsv.render_list(&records).unwrap().unwrap();
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0].split('\t').count(), 12);
+    assert_eq!(lines[2], "2\tLine\\twith tab\tLow\tfalse\t\t\t\t\t\t\t\t");
+
+    let tags = vec![TagRecord {
+        tag: "home".into(),
+        open: 1,
+        closed: 2,
+    }];
+    assert_eq!(
+        OutputFormat::Tsv.render_list(&tags).unwrap().unwrap(),
+        "tag\topen\tclosed\nhome\t1\t2"
+    );
+}
+
+#[test]
+fn test_error_record() {
+    let error = ErrorRecord::from(&TodoError::NotFound("9".into()));
+    assert_eq!(
+        OutputFormat::Csv.render_one(&error).unwrap().unwrap(),
+        "error,message,exit_code\nnot_found,Task with ID: 9 not found,3"
+    );
+}
@@ -1,6 +1,7 @@
 use std::io::Cursor;
 use to_do::model::priority::Priority;
 use to_do::service::manager::{Manager, ManagerTrait};
+use to_do::storage::memory::MemoryStorage;
 use to_do::ui::{
     console_ui::{
         generic_console_displayer::GenericConsoleDisplayer, mock_displayer::MockDisplayer,
@@ -13,8 +14,13 @@ use to_do::ui::{
 fn create_manager_with_tasks() -> Manager {
     let displayer: Box<dyn Displayer> = Box::new(MockDisplayer);
     let mut manager = Manager::new(displayer);
-    manager.add_task("Test Task 1".as_ref(), &Priority::High);
-    manager.add_task("Test Task 2".as_ref(), &Priority::Medium);
+    manager.todo_list.storage = Box::new(MemoryStorage::new());
+    manager
+        .add_task("Test Task 1".as_ref(), &Priority::High)
+        .unwrap();
+    manager
+        .add_task("Test Task 2".as_ref(), &Priority::Medium)
+        .unwrap();
     manager
 }
 
@@ -49,10 +55,10 @@ fn test_exit() {
 
 #[test]
 fn test_display_add_task() {
-    let input_vec = vec![MenuOption::get_input_key(&MenuOption::AddTask).to_string()];
+    let input_vec = [MenuOption::get_input_key(&MenuOption::AddTask).to_string()];
 
     let mut input = input_vec.join("\n");
-    input.push_str("\n");
+    input.push('\n');
 
     let input = Cursor::new(input);
     let output = Cursor::new(Vec::new());
@@ -65,7 +71,7 @@ fn test_display_add_task() {
     let output = String::from_utf8(displayer.output.into_inner()).unwrap();
     assert!(output.contains("ToDo Operations:"));
     assert!(output.contains("1. Add Task"));
-    assert!(output.contains("Enter your choice (1-5):"));
+    assert!(output.contains("Enter your choice (1-7):"));
 }
 
 #[test]
@@ -206,7 +212,7 @@ fn test_handle_undo() {
     let output = Cursor::new(Vec::new());
     let mut displayer = create_displayer_mocked_editor(input, output, Vec::new());
     let mut manager = create_manager_with_tasks();
-    manager.remove_task("1".as_ref());
+    manager.remove_task("1".as_ref()).unwrap();
     displayer.handle_undo(&mut manager).expect("Undo failed");
     let output = String::from_utf8(displayer.output.into_inner()).unwrap();
     let tasks = manager.get_tasks();
@@ -221,7 +227,7 @@ fn test_handle_redo() {
     let mut displayer = create_displayer_mocked_editor(input, output, Vec::new());
     let mut manager = create_manager_with_tasks();
     let id_to_remove = manager.get_tasks()[0].id.clone();
-    manager.remove_task(id_to_remove.as_ref());
+    manager.remove_task(id_to_remove.as_ref()).unwrap();
     manager.undo().expect("Undo failed");
     displayer.handle_redo(&mut manager).expect("Redo failed");
     let output = String::from_utf8(displayer.output.into_inner()).unwrap();
@@ -230,16 +236,37 @@ fn test_handle_redo() {
     assert!(!tasks.iter().any(|t| t.id == "1"));
 }
 
+#[test]
+fn test_handle_history_goes_back_to_an_action() {
+    let input = Cursor::new("9\n2\n".to_string());
+    let output = Cursor::new(Vec::new());
+    let mut displayer = create_displayer_mocked_editor(input, output, Vec::new());
+    let mut manager = create_manager_with_tasks();
+    manager.toggle_task_status("1", false).unwrap();
+    displayer
+        .handle_history(&mut manager)
+        .expect("History failed");
+    let output = String::from_utf8(displayer.output.into_inner()).unwrap();
+    assert!(output.contains("Undo history (most recent first):"));
+    assert!(output.contains(" Complete task 1\n"));
+    assert!(output.contains(" Add task 2 \"Test Task 2\" (Medium)\n"));
+    assert!(output.contains("Invalid action number, please type again a valid one."));
+    assert!(output.contains("2 actions undone."));
+    assert_eq!(manager.get_tasks().len(), 1);
+    assert!(!manager.get_task("1").unwrap().completed);
+}
+
 #[test]
 fn test_run_add_and_exit() {
-    let input_vec = vec![
+    let input_vec = [
         MenuOption::get_input_key(&MenuOption::AddTask).to_string(),
         "Test Task".to_string(),
         "2".to_string(),
+        "".to_string(), //No due date
         MenuOption::get_input_key(&MenuOption::Exit).to_string(),
     ];
     let mut input = input_vec.join("\n");
-    input.push_str("\n");
+    input.push('\n');
 
     let input = Cursor::new(input);
     let output = Cursor::new(Vec::new());
@@ -262,12 +289,12 @@ fn test_run_add_and_exit() {
 
 #[test]
 fn test_run_invalid_input() {
-    let input_vec = vec![
+    let input_vec = [
         "invalid".to_string(),
         MenuOption::get_input_key(&MenuOption::Exit).to_string(),
     ];
     let mut input = input_vec.join("\n");
-    input.push_str("\n");
+    input.push('\n');
 
     let input = Cursor::new(input);
     let output = Cursor::new(Vec::new());
@@ -283,14 +310,15 @@ fn test_run_invalid_input() {
 
 #[test]
 fn test_console_displayer_run() {
-    let input_vec = vec![
+    let input_vec = [
         MenuOption::get_input_key(&MenuOption::AddTask).to_string(),
         "Test Task".to_string(),
         "2".to_string(),
+        "".to_string(), //No due date
         MenuOption::get_input_key(&MenuOption::Exit).to_string(),
     ];
     let mut input = input_vec.join("\n");
-    input.push_str("\n");
+    input.push('\n');
 
     let input = Cursor::new(input);
     let output = Cursor::new(Vec::new());
@@ -313,7 +341,7 @@ fn test_console_displayer_run() {
 
 #[test]
 fn test_handle_edit_task_no_change() {
-    let input_vec = vec![
+    let input_vec = [
         MenuOption::get_input_key(&MenuOption::ListTasks).to_string(),
         MenuOption::get_input_key(&MenuOption::EditTask).to_string(),
         "1".into(), //ID to edit
@@ -321,7 +349,7 @@ fn test_handle_edit_task_no_change() {
         MenuOption::get_input_key(&MenuOption::Exit).to_string(),
     ];
     let mut input = input_vec.join("\n");
-    input.push_str("\n");
+    input.push('\n');
 
     let input = Cursor::new(input);
     let output = Cursor::new(Vec::new());
@@ -329,6 +357,7 @@ fn test_handle_edit_task_no_change() {
         //Edited fields inputed for editor
         "Test Task 1".into(),
         "1".into(),
+        "".into(),
     ]);
     let mut displayer = GenericConsoleDisplayer::new(input, output, editor);
     let mut manager = create_manager_with_tasks();
@@ -344,7 +373,7 @@ fn test_handle_edit_task_no_change() {
 
 #[test]
 fn test_handle_edit_task_change_fields() {
-    let input_vec = vec![
+    let input_vec = [
         MenuOption::get_input_key(&MenuOption::ListTasks).to_string(),
         MenuOption::get_input_key(&MenuOption::EditTask).to_string(),
         "1".into(), //ID to edit
@@ -352,7 +381,7 @@ fn test_handle_edit_task_change_fields() {
         MenuOption::get_input_key(&MenuOption::Exit).to_string(),
     ];
     let mut input = input_vec.join("\n");
-    input.push_str("\n");
+    input.push('\n');
 
     let input = Cursor::new(input);
     let output = Cursor::new(Vec::new());
@@ -360,6 +389,7 @@ fn test_handle_edit_task_change_fields() {
         //Edited fields inputed for editor
         "New Description".into(),
         "3".into(),
+        "".into(),
     ]);
     let mut displayer = GenericConsoleDisplayer::new(input, output, editor);
     let mut manager = create_manager_with_tasks();
@@ -372,3 +402,82 @@ fn test_handle_edit_task_change_fields() {
     assert!(output.contains("Description: New Description, Priority: Low, Completed: false"));
     assert!(output.contains("Exiting ToDo application... Goodbye!"));
 }
+
+#[test]
+fn test_handle_sort_tasks() {
+    let input_vec = [
+        MenuOption::get_input_key(&MenuOption::SortTasks).to_string(),
+        "size".into(),
+        "description".into(),
+        "y".into(),
+        MenuOption::get_input_key(&MenuOption::Exit).to_string(),
+    ];
+    let mut input = input_vec.join("\n");
+    input.push('\n');
+
+    let input = Cursor::new(input);
+    let output = Cursor::new(Vec::new());
+    let mut displayer = create_displayer_mocked_editor(input, output, Vec::new());
+    let mut manager = create_manager_with_tasks();
+    displayer.run(&mut manager);
+    let output = String::from_utf8(displayer.output.into_inner()).unwrap();
+
+    assert!(output.contains("You selected: List Tasks Sorted By..."));
+    assert!(output.contains("Invalid sort key: size, please type again a valid one."));
+    let first = output
+        .find("Description: Test Task 2")
+        .expect("Task 2 not listed");
+    let second = output
+        .find("Description: Test Task 1")
+        .expect("Task 1 not listed");
+    assert!(first < second);
+}
+
+#[test]
+fn test_handle_search_tasks() {
+    let input_vec = [
+        MenuOption::get_input_key(&MenuOption::SearchTasks).to_string(),
+        "task 2".into(),
+        "9".into(),
+        "task 2".into(),
+        "2".into(),
+        MenuOption::get_input_key(&MenuOption::Exit).to_string(),
+    ];
+    let mut input = input_vec.join("\n");
+    input.push('\n');
+
+    let input = Cursor::new(input);
+    let output = Cursor::new(Vec::new());
+    let mut displayer =
+        create_displayer_mocked_editor(input, output, Vec::new()).with_highlight(true);
+    let mut manager = create_manager_with_tasks();
+    displayer.run(&mut manager);
+    let output = String::from_utf8(displayer.output.into_inner()).unwrap();
+
+    assert!(output.contains("You selected: Search Tasks"));
+    assert!(output.contains("Invalid match mode, please type again a valid one."));
+    assert!(output.contains("Description: Test \x1b[1;33mTask 2\x1b[0m, Priority: Medium"));
+    assert!(!output.contains("Description: Test Task 1"));
+}
+
+#[test]
+fn test_handle_search_tasks_without_highlight() {
+    let input_vec = [
+        MenuOption::get_input_key(&MenuOption::SearchTasks).to_string(),
+        "task 2".into(),
+        "2".into(),
+        MenuOption::get_input_key(&MenuOption::Exit).to_string(),
+    ];
+    let mut input = input_vec.join("\n");
+    input.push('\n');
+
+    let input = Cursor::new(input);
+    let output = Cursor::new(Vec::new());
+    let mut displayer = create_displayer_mocked_editor(input, output, Vec::new());
+    let mut manager = create_manager_with_tasks();
+    displayer.run(&mut manager);
+    let output = String::from_utf8(displayer.output.into_inner()).unwrap();
+
+    assert!(output.contains("Description: Test Task 2, Priority: Medium"));
+    assert!(!output.contains('\x1b'));
+}